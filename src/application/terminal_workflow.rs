@@ -1,11 +1,26 @@
 use crate::domain::model::color_theme_value::ColorTheme;
-use crate::domain::model::input_value::MouseEvent;
+use crate::domain::model::hyperlink_value::Hyperlink;
+use crate::domain::model::input_value::{InputKey, MouseEvent};
 use crate::domain::model::terminal_buffer_entity::TerminalBufferEntity;
 use crate::domain::model::terminal_config_value::TerminalConfig;
+use crate::domain::model::wheel_scroll_value::WheelScroll;
+use crate::domain::model::working_directory_value::WorkingDirectory;
 use crate::domain::repository::configuration_repository::{ConfigError, ConfigurationRepository};
 use crate::domain::repository::key_translator_repository::KeyTranslatorRepository;
 use crate::domain::repository::terminal_output_repository::TerminalOutputRepository;
 use crate::domain::service::ansi_parser_domain_service::AnsiParserDomainService;
+use crate::domain::service::utf16_decoder_domain_service::Utf16DecoderDomainService;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+mod alternate_scroll;
+mod clipboard;
+mod command_block;
+mod paste;
+
+/// 同期出力 (DECSET 2026) の終了を待つ最大時間。
+/// アプリケーションが終了を通知しないまま止まっても画面が更新されなくならないようにする。
+pub const SYNCHRONIZED_OUTPUT_TIMEOUT: Duration = Duration::from_millis(200);
 
 pub struct TerminalWorkflow {
     buffer: TerminalBufferEntity,
@@ -23,6 +38,16 @@ pub struct TerminalWorkflow {
     pub(crate) config: TerminalConfig,
     pub(crate) color_theme: ColorTheme,
     is_dark: bool,
+    /// 送信待ちの貼り付けデータ
+    pending_paste: VecDeque<Vec<u8>>,
+    /// 同期出力が開始された時刻
+    synchronized_output_started_at: Option<Instant>,
+    /// 前回の描画以降に同期出力のフレームが完了したか
+    is_synchronized_frame_ended: bool,
+    /// WM_CHAR で分割して届くサロゲートペアの復元
+    utf16_decoder: Utf16DecoderDomainService,
+    /// 代替スクロールで 1 行に満たなかったホイールの回転量
+    alternate_scroll: WheelScroll,
 }
 
 impl TerminalWorkflow {
@@ -50,8 +75,11 @@ impl TerminalWorkflow {
         let font_italic = config.font_italic;
         let color_theme = config.get_color_theme(is_dark);
 
+        let mut buffer = TerminalBufferEntity::new(cols, rows);
+        buffer.get_palette_mut().set_theme(color_theme.clone());
+
         Self {
-            buffer: TerminalBufferEntity::new(cols, rows),
+            buffer,
             parser: AnsiParserDomainService::new(),
             output_repo,
             config_repo,
@@ -63,17 +91,93 @@ impl TerminalWorkflow {
             config,
             color_theme,
             is_dark,
+            pending_paste: VecDeque::new(),
+            synchronized_output_started_at: None,
+            is_synchronized_frame_ended: false,
+            utf16_decoder: Utf16DecoderDomainService::new(),
+            alternate_scroll: WheelScroll::new(),
         }
     }
 
     pub fn process_output(&mut self, output_bytes: &[u8]) {
-        self.parser.parse(output_bytes, &mut self.buffer);
+        let replies = self.parser.parse(output_bytes, &mut self.buffer);
+        for reply in replies {
+            if let Err(e) = self.send_input(&reply) {
+                log::error!("Failed to send terminal reply: {}", e);
+            }
+        }
+        // 1 回の出力に `CSI ? 2026 l` と次のフレームの `CSI ? 2026 h` が含まれる場合に備え、
+        // 最終状態だけでなく途中の区切りも反映する
+        let events = self.buffer.take_synchronized_frame_events();
+        self.is_synchronized_frame_ended |= events.is_ended;
+        if !self.buffer.is_synchronized_output_enabled() {
+            self.synchronized_output_started_at = None;
+        } else if events.is_started {
+            self.synchronized_output_started_at = Some(Instant::now());
+        } else {
+            self.synchronized_output_started_at
+                .get_or_insert_with(Instant::now);
+        }
+    }
+
+    /// 描画してよいかを判定する。
+    /// 同期出力中は保留すべき残り時間を返し、None の場合はすぐに描画してよい。
+    /// 前回の描画以降に完了したフレームがあれば、次のフレームの途中でも描画する。
+    pub fn get_repaint_hold(&mut self) -> Option<Duration> {
+        if std::mem::take(&mut self.is_synchronized_frame_ended) {
+            return None;
+        }
+        let started_at = self.synchronized_output_started_at?;
+        SYNCHRONIZED_OUTPUT_TIMEOUT
+            .checked_sub(started_at.elapsed())
+            .filter(|remaining| !remaining.is_zero())
     }
 
     pub fn send_input(&self, input_bytes: &[u8]) -> std::io::Result<()> {
         self.output_repo.send_input(input_bytes)
     }
 
+    /// WM_CHAR で届いた UTF-16 の 1 単位を送信する。サロゲートペアは両方が揃った時点で 1 文字として送る。
+    pub fn send_char(&mut self, unit: u16) -> std::io::Result<()> {
+        let text = self.utf16_decoder.push(unit);
+        if text.is_empty() {
+            return Ok(());
+        }
+        self.reset_viewport();
+        self.send_input(text.as_bytes())
+    }
+
+    /// WM_UNICHAR で届いた UTF-32 の文字を送信する。
+    pub fn send_code_point(&mut self, code_point: u32) -> std::io::Result<()> {
+        let c = char::from_u32(code_point).unwrap_or(char::REPLACEMENT_CHARACTER);
+        self.reset_viewport();
+        self.send_input(c.encode_utf8(&mut [0; 4]).as_bytes())
+    }
+
+    /// キー入力を現在の入力モード (DECCKM / DECKPAM / win32-input-mode / kitty キーボードプロトコル) に従って翻訳し、シェルへ送信する。
+    /// 翻訳対象外のキーの場合は false を返す。
+    pub fn send_key(&mut self, key: InputKey) -> std::io::Result<bool> {
+        let Some(seq) = self
+            .translator
+            .translate(key, self.buffer.get_input_modes())
+        else {
+            return Ok(false);
+        };
+        if !key.is_key_up {
+            self.reset_viewport();
+        }
+        self.send_input(&seq)?;
+        Ok(true)
+    }
+
+    /// フォーカスの取得・喪失をアプリケーションへ通知する (DECSET 1004 が有効な場合のみ)
+    pub fn report_focus(&self, is_focused: bool) -> std::io::Result<()> {
+        if !self.buffer.is_focus_reporting_enabled() {
+            return Ok(());
+        }
+        self.send_input(if is_focused { b"\x1b[I" } else { b"\x1b[O" })
+    }
+
     pub fn resize(&mut self, cols: usize, rows: usize) {
         if let Err(err) = self.output_repo.resize(cols as u16, rows as u16) {
             log::error!(
@@ -86,6 +190,11 @@ impl TerminalWorkflow {
         self.buffer.resize(cols, rows);
     }
 
+    /// 描画に使用している 1 セルあたりのピクセル数を通知する (Sixel 画像の配置に使用)
+    pub fn set_cell_pixel_size(&mut self, width: usize, height: usize) {
+        self.buffer.set_cell_pixel_size(width, height);
+    }
+
     /// 現在の設定を永続化領域に保存する
     #[allow(dead_code)]
     pub fn persist_config(&self) -> Result<(), ConfigError> {
@@ -99,6 +208,9 @@ impl TerminalWorkflow {
         self.font_face = config.font_face.clone();
         self.font_size = config.font_size;
         self.color_theme = config.get_color_theme(self.is_dark);
+        self.buffer
+            .get_palette_mut()
+            .set_theme(self.color_theme.clone());
         self.config = config;
     }
 
@@ -131,6 +243,21 @@ impl TerminalWorkflow {
         &self.buffer
     }
 
+    /// OSC 0/2 で設定された現在のウィンドウタイトルを取得する
+    pub fn get_title(&self) -> &str {
+        &self.buffer.get_title().window_title
+    }
+
+    /// OSC 7 / OSC 9;9 で通知されたシェルのカレントディレクトリを取得する
+    pub fn get_current_directory(&self) -> Option<&WorkingDirectory> {
+        self.buffer.get_current_directory()
+    }
+
+    /// 表示上のセル位置にある OSC 8 ハイパーリンクを解決する
+    pub fn get_hyperlink_at(&self, x: usize, visual_row: usize) -> Option<&Hyperlink> {
+        self.buffer.get_hyperlink_at(x, visual_row)
+    }
+
     /// ヒストリーの現在の行数を取得する
     pub fn get_history_count(&self) -> usize {
         self.buffer.get_history_len()
@@ -143,7 +270,7 @@ impl TerminalWorkflow {
 
     /// マウスイベントを処理する
     pub fn handle_mouse_event(&mut self, event: MouseEvent) -> std::io::Result<bool> {
-        use crate::domain::model::input_value::MouseButton;
+        use crate::domain::model::input_value::{Modifiers, MouseButton};
         use crate::domain::model::terminal_types_entity::MouseTrackingMode;
 
         let mode = self.buffer.get_mouse_tracking_mode();
@@ -152,11 +279,6 @@ impl TerminalWorkflow {
             return Ok(false);
         }
 
-        // SGR 1006 が有効でない場合は、現在サポートしていないため送信しない
-        if !self.buffer.is_sgr_mouse_encoding_enabled() {
-            return Ok(false);
-        }
-
         // 座標が変わっていない移動（ホバーまたはドラッグ）は抑制する
         if (event.button == MouseButton::None || event.is_drag)
             && self.buffer.get_last_mouse_pos() == Some((event.x, event.y))
@@ -166,6 +288,10 @@ impl TerminalWorkflow {
 
         // モードに応じたフィルタリング
         let should_send = match mode {
+            MouseTrackingMode::X10 => {
+                // 9: ボタンプレスのみ（リリース/ドラッグ/ホバー除外）
+                !event.is_release && !event.is_drag && event.button != MouseButton::None
+            }
             MouseTrackingMode::Default => {
                 // 1000: ボタンプレス/リリースのみ（ドラッグ/ホバー除外）
                 !event.is_drag && event.button != MouseButton::None
@@ -181,7 +307,21 @@ impl TerminalWorkflow {
             _ => false,
         };
 
-        if should_send && let Some(seq) = self.translator.translate_mouse(event) {
+        // X10 互換モードでは修飾キーを送らない
+        let event = if mode == MouseTrackingMode::X10 {
+            MouseEvent {
+                modifiers: Modifiers::none(),
+                ..event
+            }
+        } else {
+            event
+        };
+
+        if should_send
+            && let Some(seq) = self
+                .translator
+                .translate_mouse(event, self.buffer.get_mouse_encoding())
+        {
             log::debug!(
                 "Sending mouse VT sequence: {:?}",
                 String::from_utf8_lossy(&seq)
@@ -195,3 +335,198 @@ impl TerminalWorkflow {
         Ok(false)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::input_value::InputModes;
+    use crate::domain::model::terminal_types_entity::MouseEncoding;
+    use std::sync::{Arc, Mutex};
+
+    struct MockOutputRepository {
+        sent: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl TerminalOutputRepository for MockOutputRepository {
+        fn send_input(&self, input_bytes: &[u8]) -> std::io::Result<()> {
+            self.sent.lock().unwrap().extend_from_slice(input_bytes);
+            Ok(())
+        }
+        fn resize(&self, _cols: u16, _rows: u16) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct MockConfigRepository {
+        config: TerminalConfig,
+    }
+
+    impl ConfigurationRepository for MockConfigRepository {
+        fn load(&self) -> TerminalConfig {
+            self.config.clone()
+        }
+        fn save(&self, _config: &TerminalConfig) -> Result<(), ConfigError> {
+            Ok(())
+        }
+        fn get_terminal_config(&self) -> TerminalConfig {
+            self.load()
+        }
+    }
+
+    struct MockTranslator;
+
+    impl KeyTranslatorRepository for MockTranslator {
+        fn translate(&self, _key: InputKey, _modes: InputModes) -> Option<Vec<u8>> {
+            None
+        }
+        fn translate_mouse(&self, _event: MouseEvent, _encoding: MouseEncoding) -> Option<Vec<u8>> {
+            None
+        }
+    }
+
+    pub(super) fn create_workflow(
+        config: TerminalConfig,
+    ) -> (TerminalWorkflow, Arc<Mutex<Vec<u8>>>) {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let workflow = TerminalWorkflow::new(
+            80,
+            24,
+            Box::new(MockOutputRepository { sent: sent.clone() }),
+            Box::new(MockConfigRepository { config }),
+            Box::new(MockTranslator),
+            true,
+        );
+        (workflow, sent)
+    }
+
+    #[test]
+    fn test_palette_query_replies() {
+        let (mut workflow, sent) = create_workflow(TerminalConfig::default());
+        workflow.process_output(b"\x1b]11;?\x07\x1b]4;1;?\x1b\\");
+        assert_eq!(
+            String::from_utf8_lossy(&sent.lock().unwrap()),
+            "\x1b]11;rgb:2828/2c2c/3434\x07\x1b]4;1;rgb:e0e0/6c6c/7575\x1b\\"
+        );
+
+        sent.lock().unwrap().clear();
+        workflow.process_output(b"\x1b]10;#102030;rgb:ff/ff/ff\x07\x1b]10;?;?\x07");
+        assert_eq!(
+            String::from_utf8_lossy(&sent.lock().unwrap()),
+            "\x1b]10;rgb:1010/2020/3030\x07\x1b]11;rgb:ffff/ffff/ffff\x07"
+        );
+
+        // 110/111 でテーマの色に戻る
+        sent.lock().unwrap().clear();
+        workflow.process_output(b"\x1b]110\x07\x1b]111\x07\x1b]10;?\x07");
+        assert_eq!(
+            String::from_utf8_lossy(&sent.lock().unwrap()),
+            "\x1b]10;rgb:dcdc/dfdf/e4e4\x07"
+        );
+    }
+
+    #[test]
+    fn test_palette_set_and_reset() {
+        use crate::domain::model::color_theme_value::RgbColor;
+
+        let (mut workflow, _) = create_workflow(TerminalConfig::default());
+        let palette_color = |w: &TerminalWorkflow, i| w.get_buffer().get_palette().get_indexed(i);
+        let original = palette_color(&workflow, 3);
+
+        workflow.process_output(b"\x1b]4;3;#ff0000;100;rgb:00/00/ff\x07");
+        assert_eq!(palette_color(&workflow, 3), RgbColor::new(255, 0, 0));
+        assert_eq!(palette_color(&workflow, 100), RgbColor::new(0, 0, 255));
+
+        workflow.process_output(b"\x1b]104;3\x07");
+        assert_eq!(palette_color(&workflow, 3), original);
+        assert_eq!(palette_color(&workflow, 100), RgbColor::new(0, 0, 255));
+
+        workflow.process_output(b"\x1b]104\x07");
+        assert_eq!(
+            palette_color(&workflow, 100),
+            RgbColor::from_xterm_index(100).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_replies_are_flushed_in_order() {
+        let (mut workflow, sent) = create_workflow(TerminalConfig::default());
+        workflow.process_output(b"abc\x1b[6n\x1b]10;?\x1b\\\x1b[c");
+        assert_eq!(
+            String::from_utf8_lossy(&sent.lock().unwrap()),
+            "\x1b[1;4R\x1b]10;rgb:dcdc/dfdf/e4e4\x1b\\\x1b[?62;4;22c"
+        );
+    }
+
+    #[test]
+    fn test_focus_reporting() {
+        let (mut workflow, sent) = create_workflow(TerminalConfig::default());
+        workflow.report_focus(true).unwrap();
+        assert!(sent.lock().unwrap().is_empty());
+
+        workflow.process_output(b"\x1b[?1004h");
+        workflow.report_focus(false).unwrap();
+        workflow.report_focus(true).unwrap();
+        assert_eq!(&*sent.lock().unwrap(), b"\x1b[O\x1b[I");
+
+        sent.lock().unwrap().clear();
+        workflow.process_output(b"\x1b[?1004l");
+        workflow.report_focus(false).unwrap();
+        assert!(sent.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_synchronized_output_holds_repaint() {
+        let (mut workflow, sent) = create_workflow(TerminalConfig::default());
+        assert_eq!(workflow.get_repaint_hold(), None);
+
+        workflow.process_output(b"\x1b[?2026h\x1b[2Jframe");
+        assert!(workflow.get_repaint_hold().is_some());
+        let hold = workflow.get_repaint_hold().unwrap();
+        assert!(hold <= SYNCHRONIZED_OUTPUT_TIMEOUT);
+
+        // フレームの途中で出力が続いても保留の期限は延びない
+        workflow.process_output(b"more");
+        assert!(workflow.get_repaint_hold().unwrap() <= hold);
+
+        workflow.process_output(b"\x1b[?2026$p\x1b[?2026l");
+        assert_eq!(workflow.get_repaint_hold(), None);
+        assert_eq!(&*sent.lock().unwrap(), b"\x1b[?2026;1$y");
+    }
+
+    #[test]
+    fn test_synchronized_output_frame_boundary_in_one_chunk() {
+        let (mut workflow, _sent) = create_workflow(TerminalConfig::default());
+        workflow.process_output(b"\x1b[?2026hframe1");
+        workflow.synchronized_output_started_at =
+            Some(Instant::now() - SYNCHRONIZED_OUTPUT_TIMEOUT / 2);
+
+        // フレームの完了と次のフレームの開始が同じ出力に含まれても、完了したフレームは描画する
+        workflow.process_output(b"\x1b[?2026l\x1b[?2026hframe2");
+        assert_eq!(workflow.get_repaint_hold(), None);
+
+        // 次のフレームは新たに保留し、期限は 2026h を受け取った時点から数える
+        let hold = workflow.get_repaint_hold().unwrap();
+        assert!(hold > SYNCHRONIZED_OUTPUT_TIMEOUT / 2);
+    }
+
+    #[test]
+    fn test_synchronized_output_times_out() {
+        let (mut workflow, _sent) = create_workflow(TerminalConfig::default());
+        workflow.process_output(b"\x1b[?2026h");
+        workflow.synchronized_output_started_at =
+            Some(Instant::now() - SYNCHRONIZED_OUTPUT_TIMEOUT);
+        assert_eq!(workflow.get_repaint_hold(), None);
+    }
+
+    #[test]
+    fn test_send_char_joins_surrogate_pairs() {
+        let (mut workflow, sent) = create_workflow(TerminalConfig::default());
+        // U+1F600 (😀) が WM_CHAR 2 回に分かれて届く
+        workflow.send_char(0xD83D).unwrap();
+        assert!(sent.lock().unwrap().is_empty());
+        workflow.send_char(0xDE00).unwrap();
+        workflow.send_char(b'a' as u16).unwrap();
+        workflow.send_code_point(0x20B9F).unwrap();
+        assert_eq!(String::from_utf8_lossy(&sent.lock().unwrap()), "😀a𠮟");
+    }
+}
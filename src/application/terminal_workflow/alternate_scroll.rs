@@ -0,0 +1,73 @@
+use super::TerminalWorkflow;
+use crate::domain::model::terminal_types_entity::MouseTrackingMode;
+
+/// 代替画面でのホイールによるスクロール
+impl TerminalWorkflow {
+    /// 代替スクロール (DECSET 1007): マウス報告が無効な代替画面では、ホイールをカーソルキー (DECCKM に従う) として送る。
+    /// `wheel_delta` は WM_MOUSEWHEEL の回転量 (1 ノッチ 120、正が上方向)。対象外の場合は false を返す。
+    pub fn send_alternate_scroll(&mut self, wheel_delta: i16) -> std::io::Result<bool> {
+        if !self.buffer.is_alternate_screen()
+            || !self.buffer.is_alternate_scroll_enabled()
+            || self.buffer.get_mouse_tracking_mode() != MouseTrackingMode::None
+        {
+            return Ok(false);
+        }
+
+        let lines = self
+            .alternate_scroll
+            .take_lines(wheel_delta, self.config.alternate_scroll_lines);
+        if lines == 0 {
+            return Ok(true);
+        }
+        let final_byte = if lines > 0 { b'A' } else { b'B' };
+        let key: &[u8] = if self.buffer.get_input_modes().is_application_cursor_keys {
+            &[0x1B, b'O', final_byte]
+        } else {
+            &[0x1B, b'[', final_byte]
+        };
+        self.send_input(&key.repeat(lines.unsigned_abs() as usize))?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::create_workflow;
+    use crate::domain::model::terminal_config_value::TerminalConfig;
+
+    #[test]
+    fn test_alternate_scroll() {
+        let (mut workflow, sent) = create_workflow(TerminalConfig {
+            alternate_scroll_lines: 2,
+            ..TerminalConfig::default()
+        });
+        // 主画面では自身のスクロールバックを動かす
+        assert!(!workflow.send_alternate_scroll(120).unwrap());
+
+        workflow.process_output(b"\x1b[?1049h");
+        assert!(workflow.send_alternate_scroll(120).unwrap());
+        assert!(workflow.send_alternate_scroll(-240).unwrap());
+        assert_eq!(
+            &*sent.lock().unwrap(),
+            b"\x1b[A\x1b[A\x1b[B\x1b[B\x1b[B\x1b[B"
+        );
+
+        sent.lock().unwrap().clear();
+        workflow.process_output(b"\x1b[?1h");
+        workflow.send_alternate_scroll(120).unwrap();
+        assert_eq!(&*sent.lock().unwrap(), b"\x1bOA\x1bOA");
+
+        // 1 行に満たない回転量は次の回転へ持ち越す (2 行/ノッチでは 60 ごとに 1 行)
+        sent.lock().unwrap().clear();
+        for _ in 0..4 {
+            assert!(workflow.send_alternate_scroll(-40).unwrap());
+        }
+        assert_eq!(&*sent.lock().unwrap(), b"\x1bOB\x1bOB");
+
+        // マウス報告が有効な場合や 1007 を無効にした場合は対象外
+        workflow.process_output(b"\x1b[?1000h");
+        assert!(!workflow.send_alternate_scroll(120).unwrap());
+        workflow.process_output(b"\x1b[?1000l\x1b[?1007l");
+        assert!(!workflow.send_alternate_scroll(120).unwrap());
+    }
+}
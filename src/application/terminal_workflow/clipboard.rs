@@ -0,0 +1,95 @@
+use super::TerminalWorkflow;
+use crate::domain::model::clipboard_value::{ClipboardRequest, ClipboardSelection};
+use crate::domain::service::base64_codec_domain_service::Base64CodecDomainService;
+
+/// OSC 52 によるクリップボード操作
+impl TerminalWorkflow {
+    /// OSC 52 で要求されたクリップボード操作のうち、設定のポリシーで許可されたものを取り出す。
+    /// GUI はこれを実行し、読み取り要求には `reply_clipboard_read` で応答する。
+    pub fn take_clipboard_requests(&mut self) -> Vec<ClipboardRequest> {
+        let policy = self.config.clipboard_policy;
+        self.buffer
+            .take_clipboard_requests()
+            .into_iter()
+            .filter(|request| {
+                let is_allowed = match request {
+                    ClipboardRequest::Write { .. } => policy.allows_write(),
+                    ClipboardRequest::Read { .. } => policy.allows_read(),
+                };
+                if !is_allowed {
+                    log::warn!(
+                        "OSC 52 request denied by clipboard policy {:?}: {:?}",
+                        policy,
+                        request
+                    );
+                }
+                is_allowed
+            })
+            .collect()
+    }
+
+    /// OSC 52 の読み取り要求に対して、クリップボードの内容を応答する
+    pub fn reply_clipboard_read(
+        &self,
+        selection: ClipboardSelection,
+        text: &str,
+    ) -> std::io::Result<()> {
+        let reply = format!(
+            "\x1b]52;{};{}\x1b\\",
+            selection.to_param(),
+            Base64CodecDomainService::encode(text.as_bytes())
+        );
+        self.send_input(reply.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::create_workflow;
+    use crate::domain::model::clipboard_value::{ClipboardRequest, ClipboardSelection};
+    use crate::domain::model::terminal_config_value::{ClipboardPolicy, TerminalConfig};
+
+    fn config_with_policy(clipboard_policy: ClipboardPolicy) -> TerminalConfig {
+        TerminalConfig {
+            clipboard_policy,
+            ..TerminalConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_clipboard_write_only_policy_denies_read() {
+        let (mut workflow, _) = create_workflow(config_with_policy(ClipboardPolicy::WriteOnly));
+        workflow.process_output(b"\x1b]52;c;aGVsbG8=\x07\x1b]52;c;?\x07");
+        assert_eq!(
+            workflow.take_clipboard_requests(),
+            vec![ClipboardRequest::Write {
+                selection: ClipboardSelection::Clipboard,
+                text: "hello".to_string(),
+            }]
+        );
+        assert!(workflow.take_clipboard_requests().is_empty());
+    }
+
+    #[test]
+    fn test_clipboard_disabled_policy_denies_all() {
+        let (mut workflow, _) = create_workflow(config_with_policy(ClipboardPolicy::Disabled));
+        workflow.process_output(b"\x1b]52;c;aGVsbG8=\x07\x1b]52;c;?\x07");
+        assert!(workflow.take_clipboard_requests().is_empty());
+    }
+
+    #[test]
+    fn test_clipboard_read_reply() {
+        let (mut workflow, sent) = create_workflow(config_with_policy(ClipboardPolicy::ReadWrite));
+        workflow.process_output(b"\x1b]52;p;?\x1b\\");
+        assert_eq!(
+            workflow.take_clipboard_requests(),
+            vec![ClipboardRequest::Read {
+                selection: ClipboardSelection::Primary
+            }]
+        );
+        workflow
+            .reply_clipboard_read(ClipboardSelection::Primary, "hello")
+            .unwrap();
+        assert_eq!(&*sent.lock().unwrap(), b"\x1b]52;p;aGVsbG8=\x1b\\");
+    }
+}
@@ -0,0 +1,150 @@
+use super::TerminalWorkflow;
+use crate::domain::model::command_block_value::CommandBlock;
+use crate::domain::service::command_block_domain_service::CommandBlockDomainService;
+
+/// OSC 133 のマークによるコマンド単位の操作 (出力の取得、プロンプト間の移動)
+impl TerminalWorkflow {
+    /// OSC 133 のマークから組み立てたコマンドの一覧を古い順に取得する。
+    /// `limit` を指定すると直近のものだけを返す。
+    pub fn get_command_blocks(&self, limit: Option<usize>) -> Vec<CommandBlock> {
+        let lines: Vec<_> = self.buffer.primary_lines().collect();
+        let mut blocks = CommandBlockDomainService::collect(&lines);
+        if let Some(limit) = limit {
+            blocks.drain(..blocks.len().saturating_sub(limit));
+        }
+        blocks
+    }
+
+    /// 指定したコマンドの出力部分だけを取り出す
+    pub fn get_command_output(&self, block: &CommandBlock) -> Option<String> {
+        let lines: Vec<_> = self.buffer.primary_lines().collect();
+        CommandBlockDomainService::extract_output(&lines, block)
+    }
+
+    /// 終了済みのコマンドのうち最も新しいものと、その出力を取得する
+    pub fn get_last_command_output(&self) -> Option<(CommandBlock, String)> {
+        let block = self
+            .get_command_blocks(None)
+            .into_iter()
+            .rfind(|b| b.is_finished())?;
+        let output = self.get_command_output(&block)?;
+        Some((block, output))
+    }
+
+    /// 表示中の先頭行より前にある直近のプロンプトへスクロールする
+    pub fn jump_to_previous_prompt(&mut self) -> bool {
+        let top = self.get_viewport_top_row();
+        let target = CommandBlockDomainService::find_prompt_row(
+            |row| self.buffer.get_primary_line(row),
+            (0..top).rev(),
+        );
+        self.scroll_to_row(target)
+    }
+
+    /// 表示中の先頭行より後にある次のプロンプトへスクロールする
+    pub fn jump_to_next_prompt(&mut self) -> bool {
+        let top = self.get_viewport_top_row();
+        let target = CommandBlockDomainService::find_prompt_row(
+            |row| self.buffer.get_primary_line(row),
+            top + 1..self.buffer.get_primary_line_count(),
+        );
+        self.scroll_to_row(target)
+    }
+
+    /// ビューポート先頭行の通し番号（スクロールバック先頭を 0 とする）
+    fn get_viewport_top_row(&self) -> usize {
+        self.buffer
+            .get_history_len()
+            .saturating_sub(self.buffer.get_viewport_offset())
+    }
+
+    fn scroll_to_row(&mut self, row: Option<usize>) -> bool {
+        let Some(row) = row else {
+            return false;
+        };
+        let offset = self.buffer.get_history_len().saturating_sub(row);
+        if offset == self.buffer.get_viewport_offset() {
+            return false;
+        }
+        self.buffer.scroll_to(offset);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::create_workflow;
+    use crate::domain::model::terminal_config_value::TerminalConfig;
+
+    const TWO_COMMANDS: &[u8] = b"\x1b]133;A\x07$ \x1b]133;B\x07echo hi\r\n\x1b]133;C\x07hi\r\n\
+            \x1b]133;D;0\x07\x1b]133;A\x07$ \x1b]133;B\x07false\r\n\x1b]133;C\x07\x1b]133;D;1\x07\
+            \x1b]133;A\x07$ \x1b]133;B\x07";
+
+    #[test]
+    fn test_command_blocks() {
+        let (mut workflow, _) = create_workflow(TerminalConfig::default());
+        workflow.process_output(TWO_COMMANDS);
+
+        let blocks = workflow.get_command_blocks(None);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].command, "echo hi");
+        assert_eq!(blocks[0].exit_code, Some(0));
+        assert!(blocks[0].duration().is_some());
+        assert_eq!(
+            workflow.get_command_output(&blocks[0]).as_deref(),
+            Some("hi")
+        );
+
+        assert_eq!(blocks[1].command, "false");
+        assert_eq!(blocks[1].exit_code, Some(1));
+        assert_eq!(workflow.get_command_output(&blocks[1]).as_deref(), Some(""));
+
+        // 入力待ちのプロンプトはまだ終了していない
+        assert!(!blocks[2].is_finished());
+        assert_eq!(workflow.get_command_output(&blocks[2]), None);
+
+        let recent = workflow.get_command_blocks(Some(2));
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].command, "false");
+
+        // 入力待ちのプロンプトを飛ばし、終了済みの直近のコマンドを返す
+        let (block, output) = workflow.get_last_command_output().unwrap();
+        assert_eq!(block.command, "false");
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn test_jump_between_prompts() {
+        let (mut workflow, _) = create_workflow(TerminalConfig::default());
+        workflow.process_output(TWO_COMMANDS);
+        // プロンプトがスクロールバックへ押し出されるまで出力を流す
+        workflow.process_output(&b"x\r\n".repeat(30));
+        let history = workflow.get_history_count();
+        assert!(history > 0);
+
+        assert!(workflow.jump_to_previous_prompt());
+        let offset = workflow.get_viewport_offset();
+        assert!(workflow.jump_to_previous_prompt());
+        assert!(workflow.jump_to_previous_prompt());
+        assert_eq!(workflow.get_viewport_offset(), history);
+        assert!(!workflow.jump_to_previous_prompt());
+
+        assert!(workflow.jump_to_next_prompt());
+        assert!(workflow.jump_to_next_prompt());
+        assert_eq!(workflow.get_viewport_offset(), offset);
+    }
+
+    #[test]
+    fn test_jump_to_multiline_prompt() {
+        let (mut workflow, _) = create_workflow(TerminalConfig::default());
+        // 2 行のプロンプトでは B の行ではなく A の行へ移動する
+        workflow.process_output(b"\x1b]133;A\x07~/src\r\n$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07");
+        workflow.process_output(&b"x\r\n".repeat(30));
+        let history = workflow.get_history_count();
+
+        assert!(workflow.jump_to_previous_prompt());
+        assert_eq!(workflow.get_viewport_offset(), history);
+        assert!(!workflow.jump_to_previous_prompt());
+        assert!(!workflow.jump_to_next_prompt());
+    }
+}
@@ -0,0 +1,79 @@
+use super::TerminalWorkflow;
+use crate::domain::service::paste_domain_service::{PASTE_CHUNK_SIZE, PasteDomainService};
+
+/// 貼り付け (ブラケットペースト、確認の要否、大きなデータの分割送信)
+impl TerminalWorkflow {
+    /// 貼り付けの前に利用者へ確認すべきかを判定する。
+    /// ブラケットペーストが有効な場合はアプリケーション側で改行が実行されないため確認しない。
+    pub fn needs_paste_confirmation(&self, text: &str) -> bool {
+        self.config.confirm_multiline_paste
+            && !self.buffer.is_bracketed_paste_enabled()
+            && PasteDomainService::is_multiline(&PasteDomainService::sanitize(text))
+    }
+
+    /// テキストを貼り付ける。
+    /// 大きなデータは分割して送信するため、戻り値が true の場合は `pump_paste` で残りを送信すること。
+    pub fn paste(&mut self, text: &str) -> std::io::Result<bool> {
+        let payload =
+            PasteDomainService::build_payload(text, self.buffer.is_bracketed_paste_enabled());
+        self.reset_viewport();
+        self.pending_paste
+            .extend(PasteDomainService::split_chunks(&payload, PASTE_CHUNK_SIZE));
+        self.pump_paste()
+    }
+
+    /// 送信待ちの貼り付けデータを 1 チャンク送信し、まだ残りがあるかを返す
+    pub fn pump_paste(&mut self) -> std::io::Result<bool> {
+        if let Some(chunk) = self.pending_paste.pop_front()
+            && let Err(e) = self.send_input(&chunk)
+        {
+            self.pending_paste.clear();
+            return Err(e);
+        }
+        Ok(!self.pending_paste.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::create_workflow;
+    use crate::domain::model::terminal_config_value::TerminalConfig;
+    use crate::domain::service::paste_domain_service::PASTE_CHUNK_SIZE;
+
+    #[test]
+    fn test_paste_bracketed_and_chunked() {
+        let (mut workflow, sent) = create_workflow(TerminalConfig::default());
+        assert!(!workflow.paste("echo hi\r\n").unwrap());
+        assert_eq!(&*sent.lock().unwrap(), b"echo hi\r");
+
+        sent.lock().unwrap().clear();
+        workflow.process_output(b"\x1b[?2004h");
+        let text = "a".repeat(PASTE_CHUNK_SIZE * 2);
+        assert!(workflow.paste(&text).unwrap());
+        let mut chunks = 1;
+        while workflow.pump_paste().unwrap() {
+            chunks += 1;
+        }
+        assert_eq!(chunks, 2);
+        assert!(!workflow.pump_paste().unwrap());
+        assert_eq!(
+            String::from_utf8_lossy(&sent.lock().unwrap()),
+            format!("\x1b[200~{}\x1b[201~", text)
+        );
+    }
+
+    #[test]
+    fn test_paste_confirmation() {
+        let (mut workflow, _) = create_workflow(TerminalConfig::default());
+        assert!(!workflow.needs_paste_confirmation("echo hi"));
+        assert!(workflow.needs_paste_confirmation("echo hi\r\nrm x"));
+        workflow.process_output(b"\x1b[?2004h");
+        assert!(!workflow.needs_paste_confirmation("echo hi\r\nrm x"));
+
+        let (workflow, _) = create_workflow(TerminalConfig {
+            confirm_multiline_paste: false,
+            ..TerminalConfig::default()
+        });
+        assert!(!workflow.needs_paste_confirmation("echo hi\r\nrm x"));
+    }
+}
@@ -0,0 +1,148 @@
+/// ESC ( 等で G0〜G3 に指定できる文字集合
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CharacterSet {
+    #[default]
+    Ascii,
+    /// DEC Special Graphics (罫線素片)
+    DecSpecialGraphics,
+    /// UK (ASCII の '#' を '£' に置き換える)
+    Uk,
+}
+
+impl CharacterSet {
+    /// 指定シーケンスの終端文字から文字集合を判定する
+    pub fn from_designator(byte: u8) -> Option<Self> {
+        match byte {
+            b'B' => Some(Self::Ascii),
+            b'0' => Some(Self::DecSpecialGraphics),
+            b'A' => Some(Self::Uk),
+            _ => None,
+        }
+    }
+
+    /// ASCII 文字をこの文字集合の文字に変換する
+    pub fn translate(self, c: char) -> char {
+        match self {
+            Self::Ascii => c,
+            Self::Uk if c == '#' => '£',
+            Self::Uk => c,
+            Self::DecSpecialGraphics => match c {
+                '_' => ' ',
+                '`' => '◆',
+                'a' => '▒',
+                'b' => '␉',
+                'c' => '␌',
+                'd' => '␍',
+                'e' => '␊',
+                'f' => '°',
+                'g' => '±',
+                'h' => '␤',
+                'i' => '␋',
+                'j' => '┘',
+                'k' => '┐',
+                'l' => '┌',
+                'm' => '└',
+                'n' => '┼',
+                'o' => '⎺',
+                'p' => '⎻',
+                'q' => '─',
+                'r' => '⎼',
+                's' => '⎽',
+                't' => '├',
+                'u' => '┤',
+                'v' => '┴',
+                'w' => '┬',
+                'x' => '│',
+                'y' => '≤',
+                'z' => '≥',
+                '{' => 'π',
+                '|' => '≠',
+                '}' => '£',
+                '~' => '·',
+                _ => c,
+            },
+        }
+    }
+}
+
+/// G0〜G3 の指定と、GL に呼び出されている文字集合の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CharsetState {
+    designations: [CharacterSet; 4],
+    /// GL に呼び出されている G 集合の番号 (SI/SO, LS2/LS3)
+    invoked: usize,
+    /// SS2/SS3 により次の 1 文字だけ呼び出される G 集合の番号
+    single_shift: Option<usize>,
+}
+
+impl CharsetState {
+    /// ESC ( ) * + : G0〜G3 に文字集合を指定する
+    pub fn designate(&mut self, slot: usize, charset: CharacterSet) {
+        if let Some(designation) = self.designations.get_mut(slot) {
+            *designation = charset;
+        }
+    }
+
+    /// SI / SO / LS2 / LS3 : GL に G 集合を呼び出す
+    pub fn invoke(&mut self, slot: usize) {
+        if slot < self.designations.len() {
+            self.invoked = slot;
+        }
+    }
+
+    /// SS2 / SS3 : 次の 1 文字だけ G2 / G3 を使用する
+    pub fn single_shift(&mut self, slot: usize) {
+        if slot < self.designations.len() {
+            self.single_shift = Some(slot);
+        }
+    }
+
+    /// 出力する文字を現在の文字集合で変換する
+    pub fn translate(&mut self, c: char) -> char {
+        let slot = self.single_shift.take().unwrap_or(self.invoked);
+        if c.is_ascii() {
+            self.designations[slot].translate(c)
+        } else {
+            c
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translate_all(state: &mut CharsetState, text: &str) -> String {
+        text.chars().map(|c| state.translate(c)).collect()
+    }
+
+    #[test]
+    fn test_dec_special_graphics() {
+        let mut state = CharsetState::default();
+        state.designate(0, CharacterSet::DecSpecialGraphics);
+        assert_eq!(translate_all(&mut state, "lqqk"), "┌──┐");
+        assert_eq!(translate_all(&mut state, "x  x"), "│  │");
+        assert_eq!(translate_all(&mut state, "mqqj"), "└──┘");
+        // 大文字や ASCII 以外は変換しない
+        assert_eq!(translate_all(&mut state, "AZあ"), "AZあ");
+    }
+
+    #[test]
+    fn test_shift_in_and_out() {
+        let mut state = CharsetState::default();
+        state.designate(1, CharacterSet::DecSpecialGraphics);
+        assert_eq!(translate_all(&mut state, "q"), "q");
+        state.invoke(1);
+        assert_eq!(translate_all(&mut state, "q"), "─");
+        state.invoke(0);
+        assert_eq!(translate_all(&mut state, "q"), "q");
+    }
+
+    #[test]
+    fn test_single_shift() {
+        let mut state = CharsetState::default();
+        state.designate(2, CharacterSet::Uk);
+        state.single_shift(2);
+        assert_eq!(translate_all(&mut state, "##"), "£#");
+    }
+}
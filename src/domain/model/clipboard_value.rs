@@ -0,0 +1,46 @@
+/// OSC 52 で指定されるクリップボードのセレクション
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardSelection {
+    Clipboard,
+    Primary,
+    Select,
+}
+
+impl ClipboardSelection {
+    /// OSC 52 の Pc パラメータから、対応しているセレクションの一覧を取得する。
+    /// Pc が空の場合は xterm と同様に既定値（c）として扱う。
+    pub fn parse_list(param: &[u8]) -> Vec<Self> {
+        if param.is_empty() {
+            return vec![Self::Clipboard];
+        }
+        param
+            .iter()
+            .filter_map(|b| match b {
+                b'c' => Some(Self::Clipboard),
+                b'p' => Some(Self::Primary),
+                b's' => Some(Self::Select),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn to_param(self) -> char {
+        match self {
+            Self::Clipboard => 'c',
+            Self::Primary => 'p',
+            Self::Select => 's',
+        }
+    }
+}
+
+/// アプリケーションから OSC 52 で要求されたクリップボード操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardRequest {
+    Write {
+        selection: ClipboardSelection,
+        text: String,
+    },
+    Read {
+        selection: ClipboardSelection,
+    },
+}
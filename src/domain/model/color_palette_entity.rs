@@ -0,0 +1,184 @@
+use super::color_theme_value::{ColorTheme, RgbColor};
+use super::terminal_types_entity::TerminalColor;
+use std::collections::HashMap;
+
+/// OSC 10/11/12 で操作される動的な色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DynamicColor {
+    Foreground,
+    Background,
+    Cursor,
+}
+
+impl DynamicColor {
+    /// OSC 10/11/12 の番号から取得する
+    pub fn from_osc(number: u16) -> Option<Self> {
+        match number {
+            10 => Some(Self::Foreground),
+            11 => Some(Self::Background),
+            12 => Some(Self::Cursor),
+            _ => None,
+        }
+    }
+
+    pub fn to_osc(self) -> u16 {
+        match self {
+            Self::Foreground => 10,
+            Self::Background => 11,
+            Self::Cursor => 12,
+        }
+    }
+
+    /// OSC 10;a;b のように続けて指定された場合の次の色（xterm の仕様）
+    pub fn next(self) -> Option<Self> {
+        Self::from_osc(self.to_osc() + 1)
+    }
+}
+
+/// セッションごとの配色
+/// 設定から決まる `ColorTheme` の上に、アプリケーションが OSC で変更した色を重ねて保持する。
+pub struct ColorPaletteEntity {
+    theme: ColorTheme,
+    indexed_overrides: HashMap<u8, RgbColor>,
+    foreground_override: Option<RgbColor>,
+    background_override: Option<RgbColor>,
+    cursor_override: Option<RgbColor>,
+}
+
+impl ColorPaletteEntity {
+    pub fn new(theme: ColorTheme) -> Self {
+        Self {
+            theme,
+            indexed_overrides: HashMap::new(),
+            foreground_override: None,
+            background_override: None,
+            cursor_override: None,
+        }
+    }
+
+    /// 基となるテーマを差し替える。アプリケーションによる変更は維持される。
+    pub fn set_theme(&mut self, theme: ColorTheme) {
+        self.theme = theme;
+    }
+
+    pub fn get_indexed(&self, index: u8) -> RgbColor {
+        if let Some(color) = self.indexed_overrides.get(&index) {
+            return *color;
+        }
+        RgbColor::from_xterm_index(index).unwrap_or_else(|| self.theme.ansi_palette[index as usize])
+    }
+
+    pub fn set_indexed(&mut self, index: u8, color: RgbColor) {
+        self.indexed_overrides.insert(index, color);
+    }
+
+    /// OSC 104: 指定した番号（None の場合は全て）をテーマの色に戻す
+    pub fn reset_indexed(&mut self, index: Option<u8>) {
+        match index {
+            Some(index) => {
+                self.indexed_overrides.remove(&index);
+            }
+            None => self.indexed_overrides.clear(),
+        }
+    }
+
+    /// RIS: アプリケーションによる変更をすべて取り消し、テーマの色に戻す
+    pub fn reset(&mut self) {
+        self.indexed_overrides.clear();
+        self.foreground_override = None;
+        self.background_override = None;
+        self.cursor_override = None;
+    }
+
+    /// 動的な色を取得する。カーソル色が未指定の場合は前景色を返す。
+    pub fn get_dynamic(&self, target: DynamicColor) -> RgbColor {
+        match target {
+            DynamicColor::Foreground => self.foreground_override.unwrap_or(self.theme.default_fg),
+            DynamicColor::Background => self.background_override.unwrap_or(self.theme.default_bg),
+            DynamicColor::Cursor => self
+                .cursor_override
+                .unwrap_or_else(|| self.get_dynamic(DynamicColor::Foreground)),
+        }
+    }
+
+    /// 動的な色を設定する（None でテーマの色に戻す）
+    pub fn set_dynamic(&mut self, target: DynamicColor, color: Option<RgbColor>) {
+        match target {
+            DynamicColor::Foreground => self.foreground_override = color,
+            DynamicColor::Background => self.background_override = color,
+            DynamicColor::Cursor => self.cursor_override = color,
+        }
+    }
+
+    /// アプリケーションが明示的に指定したカーソル色。未指定の場合は反転表示とする。
+    pub fn get_cursor_override(&self) -> Option<RgbColor> {
+        self.cursor_override
+    }
+
+    /// セルの色を実際の RGB 値に解決する
+    pub fn resolve(&self, color: &TerminalColor, is_background: bool) -> RgbColor {
+        match color {
+            TerminalColor::Default => self.get_dynamic(if is_background {
+                DynamicColor::Background
+            } else {
+                DynamicColor::Foreground
+            }),
+            TerminalColor::Ansi(n) => self.get_indexed((*n).min(15)),
+            TerminalColor::Xterm(n) => self.get_indexed(*n),
+            TerminalColor::Rgb(r, g, b) => RgbColor::new(*r, *g, *b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_and_reset() {
+        let theme = ColorTheme::one_half_dark();
+        let mut palette = ColorPaletteEntity::new(theme.clone());
+        let red = RgbColor::new(255, 0, 0);
+
+        palette.set_indexed(1, red);
+        palette.set_indexed(200, red);
+        palette.set_dynamic(DynamicColor::Background, Some(red));
+        assert_eq!(palette.resolve(&TerminalColor::Ansi(1), false), red);
+        assert_eq!(palette.resolve(&TerminalColor::Xterm(200), false), red);
+        assert_eq!(palette.resolve(&TerminalColor::Default, true), red);
+
+        // テーマを差し替えてもアプリケーションによる変更は残る
+        palette.set_theme(ColorTheme::one_half_light());
+        assert_eq!(palette.get_indexed(1), red);
+        assert_eq!(
+            palette.get_indexed(2),
+            ColorTheme::one_half_light().ansi_palette[2]
+        );
+
+        palette.reset_indexed(Some(1));
+        assert_eq!(
+            palette.get_indexed(1),
+            ColorTheme::one_half_light().ansi_palette[1]
+        );
+        assert_eq!(palette.get_indexed(200), red);
+        palette.reset_indexed(None);
+        assert_eq!(palette.get_indexed(200), RgbColor::new(255, 0, 215));
+    }
+
+    #[test]
+    fn test_cursor_color_falls_back_to_foreground() {
+        let theme = ColorTheme::one_half_dark();
+        let mut palette = ColorPaletteEntity::new(theme.clone());
+        assert_eq!(palette.get_cursor_override(), None);
+        assert_eq!(palette.get_dynamic(DynamicColor::Cursor), theme.default_fg);
+
+        let fg = RgbColor::new(1, 2, 3);
+        palette.set_dynamic(DynamicColor::Foreground, Some(fg));
+        assert_eq!(palette.get_dynamic(DynamicColor::Cursor), fg);
+        palette.set_dynamic(DynamicColor::Foreground, None);
+        assert_eq!(
+            palette.get_dynamic(DynamicColor::Foreground),
+            theme.default_fg
+        );
+    }
+}
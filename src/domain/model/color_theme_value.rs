@@ -9,6 +9,65 @@ impl RgbColor {
     pub const fn new(r: u8, g: u8, b: u8) -> Self {
         Self { r, g, b }
     }
+
+    /// xterm 256 色の 16〜255 番（6x6x6 のカラーキューブとグレースケール）の色を計算する。
+    /// 0〜15 番はテーマに依存するため None を返す。
+    pub fn from_xterm_index(index: u8) -> Option<Self> {
+        match index {
+            0..=15 => None,
+            16..=231 => {
+                let idx = index - 16;
+                let level = |v: u8| if v > 0 { v * 40 + 55 } else { 0 };
+                Some(Self::new(
+                    level(idx / 36),
+                    level((idx % 36) / 6),
+                    level(idx % 6),
+                ))
+            }
+            232..=255 => {
+                let val = (index - 232) * 10 + 8;
+                Some(Self::new(val, val, val))
+            }
+        }
+    }
+
+    /// XParseColor 形式の色指定（`rgb:r/g/b` または `#rgb` 系）を解釈する。
+    /// 各成分は 1〜4 桁の 16 進数で、8 bit に丸めて保持する。
+    pub fn parse_x11_spec(spec: &str) -> Option<Self> {
+        let components: Vec<&str> = if let Some(rest) = spec.strip_prefix("rgb:") {
+            rest.split('/').collect()
+        } else if let Some(hex) = spec.strip_prefix('#') {
+            if hex.is_empty() || hex.len() % 3 != 0 || hex.len() > 12 {
+                return None;
+            }
+            let n = hex.len() / 3;
+            (0..3)
+                .map(|i| hex.get(i * n..(i + 1) * n))
+                .collect::<Option<_>>()?
+        } else {
+            return None;
+        };
+        let [r, g, b] = components.as_slice() else {
+            return None;
+        };
+        let scale = |c: &str| -> Option<u8> {
+            if c.is_empty() || c.len() > 4 {
+                return None;
+            }
+            let value = u32::from_str_radix(c, 16).ok()?;
+            let max = (1u32 << (c.len() * 4)) - 1;
+            Some(((value * 255 + max / 2) / max) as u8)
+        };
+        Some(Self::new(scale(r)?, scale(g)?, scale(b)?))
+    }
+
+    /// OSC の問い合わせ応答で使用する `rgb:rrrr/gggg/bbbb` 形式に変換する
+    pub fn to_x11_spec(self) -> String {
+        format!(
+            "rgb:{:02x}{:02x}/{:02x}{:02x}/{:02x}{:02x}",
+            self.r, self.r, self.g, self.g, self.b, self.b
+        )
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -90,4 +149,44 @@ mod tests {
         assert_eq!(theme.ansi_palette[0], RgbColor::new(56, 58, 66));
         assert_eq!(theme.ansi_palette[8], RgbColor::new(127, 132, 142));
     }
+
+    #[test]
+    fn test_xterm_index_colors() {
+        assert_eq!(RgbColor::from_xterm_index(7), None);
+        assert_eq!(RgbColor::from_xterm_index(16), Some(RgbColor::new(0, 0, 0)));
+        assert_eq!(
+            RgbColor::from_xterm_index(196),
+            Some(RgbColor::new(255, 0, 0))
+        );
+        assert_eq!(
+            RgbColor::from_xterm_index(244),
+            Some(RgbColor::new(128, 128, 128))
+        );
+    }
+
+    #[test]
+    fn test_x11_color_spec() {
+        let orange = Some(RgbColor::new(0xff, 0x80, 0x00));
+        assert_eq!(RgbColor::parse_x11_spec("rgb:ff/80/00"), orange);
+        assert_eq!(RgbColor::parse_x11_spec("rgb:ffff/8080/0000"), orange);
+        assert_eq!(RgbColor::parse_x11_spec("#ff8000"), orange);
+        assert_eq!(RgbColor::parse_x11_spec("#fff800000"), orange);
+        assert_eq!(
+            RgbColor::parse_x11_spec("rgb:f/0/8"),
+            Some(RgbColor::new(0xff, 0x00, 0x88))
+        );
+        assert_eq!(
+            RgbColor::parse_x11_spec("#f80"),
+            Some(RgbColor::new(0xff, 0x88, 0x00))
+        );
+        assert_eq!(RgbColor::parse_x11_spec("rgb:ff/80"), None);
+        assert_eq!(RgbColor::parse_x11_spec("#ff80"), None);
+        assert_eq!(RgbColor::parse_x11_spec("orange"), None);
+        assert_eq!(RgbColor::parse_x11_spec("rgb:zz/00/00"), None);
+
+        assert_eq!(
+            RgbColor::new(0x28, 0x2c, 0x34).to_x11_spec(),
+            "rgb:2828/2c2c/3434"
+        );
+    }
 }
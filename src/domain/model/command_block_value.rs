@@ -0,0 +1,47 @@
+use std::time::{Duration, Instant};
+
+/// OSC 133 のマークから組み立てた 1 コマンド分の区間
+/// 位置は (行, 列) で表し、行はスクロールバック先頭を 0 とする通し番号である。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandBlock {
+    /// プロンプト開始 (A) の行
+    pub prompt_row: usize,
+    /// コマンド入力開始 (B) の位置
+    pub input_start: Option<(usize, usize)>,
+    /// コマンド出力開始 (C) の位置
+    pub output_start: Option<(usize, usize)>,
+    /// コマンド終了 (D) の位置
+    pub output_end: Option<(usize, usize)>,
+    /// 入力されたコマンドライン
+    pub command: String,
+    pub exit_code: Option<i32>,
+    pub started_at: Option<Instant>,
+    pub finished_at: Option<Instant>,
+}
+
+impl CommandBlock {
+    pub fn new(prompt_row: usize) -> Self {
+        Self {
+            prompt_row,
+            input_start: None,
+            output_start: None,
+            output_end: None,
+            command: String::new(),
+            exit_code: None,
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished_at.is_some()
+    }
+
+    /// コマンドの実行時間（出力開始から終了まで）
+    pub fn duration(&self) -> Option<Duration> {
+        Some(
+            self.finished_at?
+                .saturating_duration_since(self.started_at?),
+        )
+    }
+}
@@ -0,0 +1,109 @@
+use super::hyperlink_value::Hyperlink;
+use std::collections::{HashMap, HashSet};
+
+/// セルに保持するハイパーリンクの識別子
+pub type HyperlinkId = u32;
+
+/// 参照されなくなったリンクの掃除を始める登録数の下限
+const MIN_SWEEP_THRESHOLD: usize = 256;
+
+/// OSC 8 のハイパーリンクを ID へ正規化（インターン）して保持するエンティティ。
+/// セルには ID のみを保持させることで、スクロールバックへ移動した後もリンクを解決できる。
+/// 行ごとに異なるリンクを出力し続けても増え続けないよう、登録数が前回の掃除後の 2 倍に達したら
+/// どのセルからも参照されなくなったリンクの登録を外す。
+pub struct HyperlinkRegistryEntity {
+    links: HashMap<HyperlinkId, Hyperlink>,
+    ids: HashMap<Hyperlink, HyperlinkId>,
+    next_id: HyperlinkId,
+    sweep_threshold: usize,
+}
+
+impl HyperlinkRegistryEntity {
+    pub fn new() -> Self {
+        Self {
+            links: HashMap::new(),
+            ids: HashMap::new(),
+            next_id: 0,
+            sweep_threshold: MIN_SWEEP_THRESHOLD,
+        }
+    }
+
+    /// ハイパーリンクを登録し、その ID を返す。同一のリンクには同じ ID を返す。
+    pub fn intern(&mut self, link: Hyperlink) -> HyperlinkId {
+        if let Some(id) = self.ids.get(&link) {
+            return *id;
+        }
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.links.insert(id, link.clone());
+        self.ids.insert(link, id);
+        id
+    }
+
+    pub fn get(&self, id: HyperlinkId) -> Option<&Hyperlink> {
+        self.links.get(&id)
+    }
+
+    #[cfg(test)]
+    pub fn get_link_count(&self) -> usize {
+        self.links.len()
+    }
+
+    /// 参照されなくなったリンクを掃除すべき登録数に達したか
+    pub fn needs_sweep(&self) -> bool {
+        self.links.len() >= self.sweep_threshold
+    }
+
+    /// `live` に含まれない ID の登録を外す
+    pub fn retain(&mut self, live: &HashSet<HyperlinkId>) {
+        self.links.retain(|id, _| live.contains(id));
+        self.ids.retain(|_, id| live.contains(id));
+        self.sweep_threshold = (self.links.len() * 2).max(MIN_SWEEP_THRESHOLD);
+    }
+}
+
+impl Default for HyperlinkRegistryEntity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(uri: &str) -> Hyperlink {
+        Hyperlink::new(uri.to_string(), None)
+    }
+
+    #[test]
+    fn test_retain_keeps_live_ids() {
+        let mut registry = HyperlinkRegistryEntity::new();
+        let a = registry.intern(link("https://a"));
+        let b = registry.intern(link("https://b"));
+        assert_eq!(registry.intern(link("https://a")), a);
+
+        registry.retain(&HashSet::from([b]));
+        assert_eq!(registry.get_link_count(), 1);
+        assert_eq!(registry.get(a), None);
+        assert_eq!(registry.get(b), Some(&link("https://b")));
+        // 登録を外したリンクは新しい ID で登録し直し、残っている ID とは重ならない
+        let c = registry.intern(link("https://a"));
+        assert_ne!(c, b);
+    }
+
+    #[test]
+    fn test_sweep_threshold_grows_with_live_links() {
+        let mut registry = HyperlinkRegistryEntity::new();
+        let live: HashSet<_> = (0..MIN_SWEEP_THRESHOLD)
+            .map(|i| registry.intern(link(&format!("https://{}", i))))
+            .collect();
+        assert!(registry.needs_sweep());
+        registry.retain(&live);
+        assert!(!registry.needs_sweep());
+        for i in 0..MIN_SWEEP_THRESHOLD {
+            registry.intern(link(&format!("https://x{}", i)));
+        }
+        assert!(registry.needs_sweep());
+    }
+}
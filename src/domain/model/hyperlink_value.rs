@@ -0,0 +1,167 @@
+use super::working_directory_value::WorkingDirectory;
+
+/// OSC 8 で指定されたハイパーリンク
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hyperlink {
+    pub uri: String,
+    /// `id=` パラメータ。同じ ID を持つ離れたセル同士を一つのリンクとして扱うために使用される
+    pub id: Option<String>,
+}
+
+/// 既定のハンドラ（ブラウザ等）で開くことを許可するスキーム
+const WEB_SCHEMES: &[&str] = &["http", "https", "mailto", "ftp"];
+
+impl Hyperlink {
+    pub fn new(uri: String, id: Option<String>) -> Self {
+        Self { uri, id }
+    }
+
+    fn scheme(&self) -> Option<&str> {
+        self.uri.split_once(':').map(|(scheme, _)| scheme)
+    }
+
+    /// 既定のハンドラで開いてよい URI であるかを判定する。
+    /// 任意のスキームを開くと実行ファイル等を起動できてしまうため、許可リストで制限する。
+    pub fn is_web_uri(&self) -> bool {
+        self.scheme()
+            .is_some_and(|s| WEB_SCHEMES.iter().any(|w| w.eq_ignore_ascii_case(s)))
+    }
+
+    /// file: URI を Windows のローカルパスに変換する。
+    /// `file:src/main.rs` のような相対パスは、OSC 7 で通知されたカレントディレクトリを基準に解決する。
+    /// `local_host` はこのマシンのコンピューター名。WSL のホスト名は既定で Windows と同じになるため、
+    /// 一致するホストのパスは OSC 7 と同じ規則で解決し (`/mnt/c/x` は `C:\x`)、それ以外のホストは UNC パスとして扱う。
+    pub fn to_file_path(
+        &self,
+        current_dir: Option<&WorkingDirectory>,
+        local_host: Option<&str>,
+    ) -> Option<String> {
+        let Some(rest) = self.uri.strip_prefix("file://") else {
+            let relative = self.uri.strip_prefix("file:")?;
+            return current_dir?.join(&percent_decode(relative));
+        };
+        let (host, path) = rest.split_at(rest.find('/')?);
+        let is_local_host = host.is_empty()
+            || host.eq_ignore_ascii_case("localhost")
+            || local_host.is_some_and(|local| host.eq_ignore_ascii_case(local));
+        if !is_local_host {
+            // file://server/share/... は UNC パスとして扱う
+            return Some(format!(
+                r"\\{}{}",
+                host,
+                percent_decode(path).replace('/', "\\")
+            ));
+        }
+        // WSL 内部のパス等、Windows で解決できないものは開かない
+        WorkingDirectory::from_file_uri(&self.uri)
+            .filter(|directory| directory.is_windows_path())
+            .map(|directory| directory.as_str().to_string())
+    }
+}
+
+/// %XX 形式でエンコードされた文字列を復号する
+pub(crate) fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && let Ok(hex) = std::str::from_utf8(&bytes[i + 1..i + 3])
+            && let Ok(value) = u8::from_str_radix(hex, 16)
+        {
+            decoded.push(value);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(uri: &str) -> Hyperlink {
+        Hyperlink::new(uri.to_string(), None)
+    }
+
+    #[test]
+    fn test_to_file_path() {
+        assert_eq!(
+            link("file:///C:/Users/me/a%20b.txt")
+                .to_file_path(None, None)
+                .as_deref(),
+            Some(r"C:\Users\me\a b.txt")
+        );
+        assert_eq!(
+            link("file://localhost/D:/work/x.rs")
+                .to_file_path(None, None)
+                .as_deref(),
+            Some(r"D:\work\x.rs")
+        );
+        assert_eq!(
+            link("file://server/share/dir/file.txt")
+                .to_file_path(None, None)
+                .as_deref(),
+            Some(r"\\server\share\dir\file.txt")
+        );
+        assert_eq!(link("https://example.com").to_file_path(None, None), None);
+    }
+
+    #[test]
+    fn test_to_file_path_local_host() {
+        let to_path = |uri: &str| link(uri).to_file_path(None, Some("DESKTOP-01"));
+        // WSL のホスト名はこのマシンと同じため、ローカルのパスとして解決する
+        assert_eq!(
+            to_path("file://desktop-01/mnt/c/x").as_deref(),
+            Some(r"C:\x")
+        );
+        assert_eq!(
+            to_path("file://DESKTOP-01/C:/work/a.rs").as_deref(),
+            Some(r"C:\work\a.rs")
+        );
+        assert_eq!(to_path("file://DESKTOP-01/home/me/a.rs"), None);
+        assert_eq!(
+            to_path("file://server/share/a.rs").as_deref(),
+            Some(r"\\server\share\a.rs")
+        );
+    }
+
+    #[test]
+    fn test_to_file_path_relative() {
+        let cwd = WorkingDirectory::from_path(r"C:\work\proj");
+        assert_eq!(
+            link("file:src/a%20b.rs")
+                .to_file_path(cwd.as_ref(), None)
+                .as_deref(),
+            Some(r"C:\work\proj\src\a b.rs")
+        );
+        // 絶対パスはカレントディレクトリに依存しない
+        assert_eq!(
+            link("file:///D:/x.rs")
+                .to_file_path(cwd.as_ref(), None)
+                .as_deref(),
+            Some(r"D:\x.rs")
+        );
+        // カレントディレクトリが不明、または Windows で解決できない場合は開かない
+        assert_eq!(link("file:src/main.rs").to_file_path(None, None), None);
+        let wsl = WorkingDirectory::from_path("/home/me");
+        assert_eq!(
+            link("file:src/main.rs").to_file_path(wsl.as_ref(), None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_web_uri() {
+        assert!(link("https://example.com").is_web_uri());
+        assert!(link("HTTP://example.com").is_web_uri());
+        assert!(link("mailto:someone@example.com").is_web_uri());
+        assert!(!link("file:///C:/a.txt").is_web_uri());
+        assert!(!link("ms-settings:display").is_web_uri());
+        assert!(!link("C:/Windows/System32/calc.exe").is_web_uri());
+    }
+}
@@ -1,3 +1,5 @@
+use super::kitty_keyboard_value::KittyKeyboardFlags;
+
 /// 修飾キーの状態を表す構造体
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Modifiers {
@@ -7,7 +9,6 @@ pub struct Modifiers {
 }
 
 impl Modifiers {
-    #[allow(dead_code)]
     pub fn none() -> Self {
         Self {
             is_ctrl_pressed: false,
@@ -15,6 +16,14 @@ impl Modifiers {
             is_alt_pressed: false,
         }
     }
+
+    /// xterm の修飾キーパラメータ `1 + (Shift | Alt << 1 | Ctrl << 2)`。修飾キーが無い場合は None
+    pub fn xterm_parameter(&self) -> Option<u8> {
+        let bits = (self.is_shift_pressed as u8)
+            | (self.is_alt_pressed as u8) << 1
+            | (self.is_ctrl_pressed as u8) << 2;
+        (bits != 0).then_some(1 + bits)
+    }
 }
 
 /// ターミナルへの入力キーイベントを表すドメインモデル
@@ -22,12 +31,100 @@ impl Modifiers {
 pub struct InputKey {
     pub vk_code: u16,
     pub modifiers: Modifiers,
+    /// キーボードのスキャンコード (win32-input-mode で送信する)
+    pub scan_code: u16,
+    /// 拡張キー (テンキー以外の矢印キーや右 Ctrl 等) であるか
+    pub is_extended: bool,
+    /// キーを離したイベントであるか
+    pub is_key_up: bool,
+    /// 押し続けによるオートリピートのイベントであるか
+    pub is_repeat: bool,
+    /// 右側の Ctrl が押されているか (win32-input-mode で左右を区別して送信する)
+    pub is_right_ctrl_pressed: bool,
+    /// 右側の Alt (AltGr) が押されているか
+    pub is_right_alt_pressed: bool,
+    /// キーボードレイアウトに従ってキーが入力する文字 (UTF-16)。文字を伴わないキーでは 0
+    pub unicode_char: u16,
 }
 
 impl InputKey {
     pub fn new(vk_code: u16, modifiers: Modifiers) -> Self {
-        Self { vk_code, modifiers }
+        Self {
+            vk_code,
+            modifiers,
+            scan_code: 0,
+            is_extended: false,
+            is_key_up: false,
+            is_repeat: false,
+            is_right_ctrl_pressed: false,
+            is_right_alt_pressed: false,
+            unicode_char: 0,
+        }
+    }
+
+    /// スキャンコード・拡張キー・押下状態を設定する
+    pub fn with_key_state(mut self, scan_code: u16, is_extended: bool, is_key_up: bool) -> Self {
+        self.scan_code = scan_code;
+        self.is_extended = is_extended;
+        self.is_key_up = is_key_up;
+        self
+    }
+
+    /// オートリピートによる押下であるかを設定する
+    pub fn with_repeat(mut self, is_repeat: bool) -> Self {
+        self.is_repeat = is_repeat;
+        self
     }
+
+    /// 右側の Ctrl / Alt が押されているかを設定する
+    pub fn with_right_modifiers(
+        mut self,
+        is_right_ctrl_pressed: bool,
+        is_right_alt_pressed: bool,
+    ) -> Self {
+        self.is_right_ctrl_pressed = is_right_ctrl_pressed;
+        self.is_right_alt_pressed = is_right_alt_pressed;
+        self
+    }
+
+    /// キーが入力する文字を設定する
+    pub fn with_unicode_char(mut self, unicode_char: u16) -> Self {
+        self.unicode_char = unicode_char;
+        self
+    }
+}
+
+#[cfg(test)]
+impl Modifiers {
+    /// テスト用: 修飾キーを "C" (Ctrl) / "S" (Shift) / "A" (Alt) の組み合わせで表す
+    pub fn from_spec(spec: &str) -> Self {
+        Self {
+            is_ctrl_pressed: spec.contains('C'),
+            is_shift_pressed: spec.contains('S'),
+            is_alt_pressed: spec.contains('A'),
+        }
+    }
+}
+
+#[cfg(test)]
+impl InputKey {
+    /// テスト用: 修飾キーを `Modifiers::from_spec` の形式で指定して生成する
+    pub fn from_spec(vk_code: u16, spec: &str) -> Self {
+        Self::new(vk_code, Modifiers::from_spec(spec))
+    }
+}
+
+/// キー入力の翻訳に影響するターミナルの入力モード
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InputModes {
+    /// DECCKM: カーソルキーを SS3 (`ESC O A` 等) で送信する
+    pub is_application_cursor_keys: bool,
+    /// DECKPAM: テンキーを SS3 (`ESC O p` 等) で送信する
+    pub is_application_keypad: bool,
+    /// DECSET 9001: キーの押下・解放を Win32 の KEY_EVENT 形式 (`CSI Vk;Sc;Uc;Kd;Cs;Rc _`) で送信する
+    pub is_win32_input_mode: bool,
+    /// kitty キーボードプロトコルで有効化されている拡張フラグ
+    pub kitty_keyboard_flags: KittyKeyboardFlags,
 }
 
 /// マウスボタンを表す列挙型
@@ -0,0 +1,259 @@
+use super::input_value::InputKey;
+
+/// フラグスタックに積める最大数。超えた場合は最も古いものから捨てる
+const FLAG_STACK_LIMIT: usize = 16;
+
+/// kitty キーボードプロトコルの段階的拡張フラグ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct KittyKeyboardFlags(u16);
+
+impl KittyKeyboardFlags {
+    /// 0b1: 従来の符号化では区別できないキー (Esc, Ctrl+I 等) を CSI u で送信する
+    pub const DISAMBIGUATE: u16 = 0b1;
+    /// 0b10: キーの押下に加えて解放を通知する
+    pub const REPORT_EVENT_TYPES: u16 = 0b10;
+    /// 0b100: Shift を押した場合の文字を代替キーとして通知する
+    pub const REPORT_ALTERNATE_KEYS: u16 = 0b100;
+    /// 0b1000: 文字を入力するキーも含め、すべてのキーを CSI u で送信する
+    pub const REPORT_ALL_KEYS: u16 = 0b1000;
+
+    /// 定義済みのフラグ。0b10000 (入力される文字の付加) は未対応だが、問い合わせにはそのまま返す
+    const ALL: u16 = 0b11111;
+
+    pub fn from_bits(bits: u16) -> Self {
+        Self(bits & Self::ALL)
+    }
+
+    pub fn bits(self) -> u16 {
+        self.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, flag: u16) -> bool {
+        self.0 & flag == flag
+    }
+}
+
+/// 画面ごとに保持する kitty キーボードプロトコルのフラグスタック
+/// スタックの先頭が現在有効なフラグであり、空の場合はすべて無効となる。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KittyKeyboardStack {
+    stack: Vec<KittyKeyboardFlags>,
+}
+
+impl KittyKeyboardStack {
+    pub fn current(&self) -> KittyKeyboardFlags {
+        self.stack.last().copied().unwrap_or_default()
+    }
+
+    /// CSI > flags u: フラグを積む
+    pub fn push(&mut self, flags: KittyKeyboardFlags) {
+        if self.stack.len() >= FLAG_STACK_LIMIT {
+            self.stack.remove(0);
+        }
+        self.stack.push(flags);
+    }
+
+    /// CSI < n u: フラグを n 個取り除く
+    pub fn pop(&mut self, count: usize) {
+        let len = self.stack.len().saturating_sub(count);
+        self.stack.truncate(len);
+    }
+
+    /// CSI = flags ; mode u: 現在のフラグを変更する。
+    /// mode 1 は置き換え、2 は指定したビットを有効化、3 は指定したビットを無効化する。
+    pub fn set(&mut self, flags: KittyKeyboardFlags, mode: u16) {
+        let current = self.current().bits();
+        let bits = match mode {
+            1 => flags.bits(),
+            2 => current | flags.bits(),
+            3 => current & !flags.bits(),
+            _ => return,
+        };
+        let updated = KittyKeyboardFlags::from_bits(bits);
+        match self.stack.last_mut() {
+            Some(top) => *top = updated,
+            None => self.stack.push(updated),
+        }
+    }
+}
+
+// 仮想キーコード (winuser.h)。Domain 層が windows クレートに依存しないよう再定義している。
+pub(crate) const VK_BACK: u16 = 0x08;
+pub(crate) const VK_TAB: u16 = 0x09;
+pub(crate) const VK_RETURN: u16 = 0x0D;
+pub(crate) const VK_SHIFT: u16 = 0x10;
+pub(crate) const VK_CONTROL: u16 = 0x11;
+pub(crate) const VK_MENU: u16 = 0x12;
+pub(crate) const VK_ESCAPE: u16 = 0x1B;
+pub(crate) const VK_SPACE: u16 = 0x20;
+pub(crate) const VK_PRIOR: u16 = 0x21;
+pub(crate) const VK_NEXT: u16 = 0x22;
+pub(crate) const VK_END: u16 = 0x23;
+pub(crate) const VK_HOME: u16 = 0x24;
+pub(crate) const VK_LEFT: u16 = 0x25;
+pub(crate) const VK_UP: u16 = 0x26;
+pub(crate) const VK_RIGHT: u16 = 0x27;
+pub(crate) const VK_DOWN: u16 = 0x28;
+pub(crate) const VK_INSERT: u16 = 0x2D;
+pub(crate) const VK_DELETE: u16 = 0x2E;
+pub(crate) const VK_NUMPAD0: u16 = 0x60;
+pub(crate) const VK_NUMPAD9: u16 = 0x69;
+pub(crate) const VK_MULTIPLY: u16 = 0x6A;
+pub(crate) const VK_ADD: u16 = 0x6B;
+pub(crate) const VK_SEPARATOR: u16 = 0x6C;
+pub(crate) const VK_SUBTRACT: u16 = 0x6D;
+pub(crate) const VK_DECIMAL: u16 = 0x6E;
+pub(crate) const VK_DIVIDE: u16 = 0x6F;
+pub(crate) const VK_F1: u16 = 0x70;
+pub(crate) const VK_F12: u16 = 0x7B;
+pub(crate) const VK_LSHIFT: u16 = 0xA0;
+pub(crate) const VK_RSHIFT: u16 = 0xA1;
+pub(crate) const VK_LCONTROL: u16 = 0xA2;
+pub(crate) const VK_RCONTROL: u16 = 0xA3;
+pub(crate) const VK_LMENU: u16 = 0xA4;
+pub(crate) const VK_RMENU: u16 = 0xA5;
+
+/// 右 Shift のスキャンコード。VK_SHIFT は左右を区別しないためスキャンコードで判定する
+const SCAN_CODE_RSHIFT: u16 = 0x36;
+
+// kitty が Unicode の私用領域に割り当てているキーコード
+const KP_0: u32 = 57399;
+const KP_DECIMAL: u32 = 57409;
+const KP_DIVIDE: u32 = 57410;
+const KP_MULTIPLY: u32 = 57411;
+const KP_SUBTRACT: u32 = 57412;
+const KP_ADD: u32 = 57413;
+const KP_SEPARATOR: u32 = 57416;
+const LEFT_SHIFT: u32 = 57441;
+const LEFT_CONTROL: u32 = 57442;
+const LEFT_ALT: u32 = 57443;
+const RIGHT_SHIFT: u32 = 57447;
+const RIGHT_CONTROL: u32 = 57448;
+const RIGHT_ALT: u32 = 57449;
+
+/// kitty キーボードプロトコルにおけるキーの分類
+pub(crate) enum KittyKey {
+    /// 文字を入力するキー (英数字・スペース)。Shift を押した場合の文字を伴う
+    Text {
+        code: u32,
+        shifted_code: Option<u32>,
+    },
+    /// Enter / Tab / Backspace。修飾キーが無ければ従来の符号化を保つ
+    Editing(u32),
+    Escape,
+    /// `CSI 1 ; m X` 形式の機能キー
+    Letter(u8),
+    /// `CSI n ; m ~` 形式の機能キー
+    Tilde(u8),
+    /// テンキー
+    Keypad(u32),
+    /// 単独で押された修飾キー
+    Modifier(u32),
+}
+
+impl KittyKey {
+    /// 仮想キーコードから kitty キーボードプロトコルでのキーの分類を求める。対象外のキーは None
+    pub(crate) fn classify(key: InputKey) -> Option<Self> {
+        let vk_code = key.vk_code;
+        let kitty_key = match vk_code {
+            // 英字は Shift の有無によらず小文字をキーコードとする
+            0x41..=0x5A => Self::Text {
+                code: (vk_code + 0x20) as u32,
+                shifted_code: Some(vk_code as u32),
+            },
+            // Shift を押した場合の記号は配列に依存するため代替キーは通知しない
+            0x30..=0x39 | VK_SPACE => Self::Text {
+                code: vk_code as u32,
+                shifted_code: None,
+            },
+            VK_RETURN => Self::Editing(13),
+            VK_TAB => Self::Editing(9),
+            VK_BACK => Self::Editing(127),
+            VK_ESCAPE => Self::Escape,
+            VK_UP => Self::Letter(b'A'),
+            VK_DOWN => Self::Letter(b'B'),
+            VK_RIGHT => Self::Letter(b'C'),
+            VK_LEFT => Self::Letter(b'D'),
+            VK_HOME => Self::Letter(b'H'),
+            VK_END => Self::Letter(b'F'),
+            VK_INSERT => Self::Tilde(2),
+            VK_DELETE => Self::Tilde(3),
+            VK_PRIOR => Self::Tilde(5),
+            VK_NEXT => Self::Tilde(6),
+            VK_F1..=VK_F12 => match vk_code - VK_F1 {
+                0 => Self::Letter(b'P'),
+                1 => Self::Letter(b'Q'),
+                2 => Self::Tilde(13),
+                3 => Self::Letter(b'S'),
+                n => Self::Tilde([15, 17, 18, 19, 20, 21, 23, 24][n as usize - 4]),
+            },
+            VK_NUMPAD0..=VK_NUMPAD9 => Self::Keypad(KP_0 + (vk_code - VK_NUMPAD0) as u32),
+            VK_DECIMAL => Self::Keypad(KP_DECIMAL),
+            VK_DIVIDE => Self::Keypad(KP_DIVIDE),
+            VK_MULTIPLY => Self::Keypad(KP_MULTIPLY),
+            VK_SUBTRACT => Self::Keypad(KP_SUBTRACT),
+            VK_ADD => Self::Keypad(KP_ADD),
+            VK_SEPARATOR => Self::Keypad(KP_SEPARATOR),
+            // 右 Ctrl / 右 Alt は拡張キーとして通知される
+            VK_SHIFT if key.scan_code == SCAN_CODE_RSHIFT => Self::Modifier(RIGHT_SHIFT),
+            VK_CONTROL if key.is_extended => Self::Modifier(RIGHT_CONTROL),
+            VK_MENU if key.is_extended => Self::Modifier(RIGHT_ALT),
+            VK_SHIFT | VK_LSHIFT => Self::Modifier(LEFT_SHIFT),
+            VK_CONTROL | VK_LCONTROL => Self::Modifier(LEFT_CONTROL),
+            VK_MENU | VK_LMENU => Self::Modifier(LEFT_ALT),
+            VK_RSHIFT => Self::Modifier(RIGHT_SHIFT),
+            VK_RCONTROL => Self::Modifier(RIGHT_CONTROL),
+            VK_RMENU => Self::Modifier(RIGHT_ALT),
+            _ => return None,
+        };
+        Some(kitty_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_and_pop() {
+        let mut stack = KittyKeyboardStack::default();
+        assert!(stack.current().is_empty());
+        stack.push(KittyKeyboardFlags::from_bits(1));
+        stack.push(KittyKeyboardFlags::from_bits(0b1011));
+        assert_eq!(stack.current().bits(), 0b1011);
+        stack.pop(1);
+        assert_eq!(stack.current().bits(), 1);
+        // 積んだ数より多く取り除いても空になるだけ
+        stack.pop(5);
+        assert!(stack.current().is_empty());
+    }
+
+    #[test]
+    fn test_set_modes() {
+        let mut stack = KittyKeyboardStack::default();
+        stack.set(KittyKeyboardFlags::from_bits(0b101), 1);
+        assert_eq!(stack.current().bits(), 0b101);
+        stack.set(KittyKeyboardFlags::from_bits(0b10), 2);
+        assert_eq!(stack.current().bits(), 0b111);
+        stack.set(KittyKeyboardFlags::from_bits(0b1), 3);
+        assert_eq!(stack.current().bits(), 0b110);
+        // 未定義のビットは無視する
+        stack.set(KittyKeyboardFlags::from_bits(0xFF), 1);
+        assert_eq!(stack.current().bits(), 0b11111);
+    }
+
+    #[test]
+    fn test_stack_limit() {
+        let mut stack = KittyKeyboardStack::default();
+        for bits in 0..20 {
+            stack.push(KittyKeyboardFlags::from_bits(bits % 16));
+        }
+        stack.pop(FLAG_STACK_LIMIT - 1);
+        // 古いものから捨てられ、最も古い残りは 4 回目に積んだフラグ
+        assert_eq!(stack.current().bits(), 4);
+    }
+}
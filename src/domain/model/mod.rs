@@ -1,8 +1,25 @@
+pub mod character_set_value;
+pub mod clipboard_value;
+pub mod color_palette_entity;
 pub mod color_theme_value;
+pub mod command_block_value;
+pub mod hyperlink_registry_entity;
+pub mod hyperlink_value;
 pub mod input_value;
+pub mod kitty_keyboard_value;
+pub mod semantic_prompt_value;
+pub mod sixel_image_registry_entity;
+pub mod sixel_image_value;
+pub mod sixel_palette_value;
+pub mod tab_stops_value;
 pub mod terminal_buffer_entity;
 pub mod terminal_config_value;
 pub mod terminal_grid_entity;
+pub mod terminal_line_entity;
+pub mod terminal_reflow_value;
 pub mod terminal_scrollback_entity;
+pub mod terminal_title_value;
 pub mod terminal_types_entity;
+pub mod wheel_scroll_value;
 pub mod window_id_value;
+pub mod working_directory_value;
@@ -0,0 +1,41 @@
+use std::time::Instant;
+
+/// OSC 133 (FinalTerm 形式) のセマンティックプロンプトマークの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptMarkKind {
+    /// A: プロンプトの開始
+    PromptStart,
+    /// B: コマンド入力の開始（プロンプトの終了）
+    CommandStart,
+    /// C: コマンド出力の開始
+    OutputStart,
+    /// D: コマンドの終了。終了コードは省略されることがある
+    CommandFinished { exit_code: Option<i32> },
+}
+
+impl PromptMarkKind {
+    /// OSC 133 のパラメータ (`A`, `B`, `C`, `D;exit`) を解釈する
+    pub fn from_params(command: &[u8], args: &[&[u8]]) -> Option<Self> {
+        match command {
+            b"A" => Some(Self::PromptStart),
+            b"B" => Some(Self::CommandStart),
+            b"C" => Some(Self::OutputStart),
+            b"D" => Some(Self::CommandFinished {
+                exit_code: args
+                    .first()
+                    .and_then(|code| std::str::from_utf8(code).ok())
+                    .and_then(|code| code.parse().ok()),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// 行に記録されたセマンティックプロンプトマーク
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PromptMark {
+    pub kind: PromptMarkKind,
+    /// マークが記録された時点のカーソル列
+    pub column: usize,
+    pub timestamp: Instant,
+}
@@ -0,0 +1,91 @@
+use super::sixel_image_value::SixelImage;
+use std::collections::VecDeque;
+use std::sync::{Arc, Weak};
+
+/// 画面とスクロールバックに配置されている Sixel 画像が使用できるメモリの上限 (バイト)。
+/// 最大の大きさ (1000x1000) の画像 16 枚分
+pub const MAX_IMAGE_MEMORY: usize = 16 * 1000 * 1000 * 4;
+
+/// 配置された Sixel 画像を配置順に記録し、使用メモリを管理するエンティティ。
+/// 画像はセルが保持しているため弱参照で記録し、セルから消えた画像は自然に対象外となる。
+pub struct SixelImageRegistryEntity {
+    images: VecDeque<Weak<SixelImage>>,
+    memory_limit: usize,
+}
+
+impl SixelImageRegistryEntity {
+    pub fn new(memory_limit: usize) -> Self {
+        Self {
+            images: VecDeque::new(),
+            memory_limit,
+        }
+    }
+
+    /// 配置する画像を登録する。
+    /// 使用メモリが上限を超える場合は古い画像から登録を外して返すため、呼び出し側でセルから取り除く。
+    /// 登録した画像自体は上限を超えていても外さない。
+    pub fn register(&mut self, image: &Arc<SixelImage>) -> Vec<Weak<SixelImage>> {
+        self.images.retain(|placed| placed.strong_count() > 0);
+        self.images.push_back(Arc::downgrade(image));
+        let mut used: usize = self
+            .images
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|placed| placed.get_pixels().len())
+            .sum();
+        let mut evicted = Vec::new();
+        while used > self.memory_limit && self.images.len() > 1 {
+            let Some(oldest) = self.images.pop_front() else {
+                break;
+            };
+            if let Some(placed) = oldest.upgrade() {
+                used -= placed.get_pixels().len();
+            }
+            evicted.push(oldest);
+        }
+        evicted
+    }
+}
+
+impl Default for SixelImageRegistryEntity {
+    fn default() -> Self {
+        Self::new(MAX_IMAGE_MEMORY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: usize) -> Arc<SixelImage> {
+        Arc::new(SixelImage::new(width, 1, vec![0; width * 4]).unwrap())
+    }
+
+    #[test]
+    fn test_evicts_oldest_images() {
+        // 40 バイト (10 ピクセル) まで
+        let mut registry = SixelImageRegistryEntity::new(40);
+        let first = image(4);
+        let second = image(4);
+        assert!(registry.register(&first).is_empty());
+        assert!(registry.register(&second).is_empty());
+
+        let third = image(4);
+        let evicted = registry.register(&third);
+        assert_eq!(evicted.len(), 1);
+        assert!(Weak::ptr_eq(&evicted[0], &Arc::downgrade(&first)));
+
+        // 上限を超える画像でも、最後に配置したものは残す
+        let large = image(20);
+        let evicted = registry.register(&large);
+        assert_eq!(evicted.len(), 2);
+    }
+
+    #[test]
+    fn test_released_images_are_not_counted() {
+        let mut registry = SixelImageRegistryEntity::new(40);
+        // セルから消えた (参照が無くなった) 画像は使用メモリに含めない
+        drop(registry.register(&image(8)));
+        assert!(registry.register(&image(8)).is_empty());
+    }
+}
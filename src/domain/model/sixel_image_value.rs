@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+/// Sixel から復号した画像 (RGBA 8bit、左上から行優先)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SixelImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl SixelImage {
+    /// RGBA の画素列から生成する。画素数が大きさと一致しない場合は None を返す。
+    pub fn new(width: usize, height: usize, pixels: Vec<u8>) -> Option<Self> {
+        if width == 0 || height == 0 || pixels.len() != width * height * 4 {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+}
+
+/// セルに貼り付けられた画像の一部
+/// 画像を配置時のセルの大きさで格子状に分割し、各セルがその 1 区画を保持する。
+/// セルと共に移動するため、文字と同じようにスクロールバックへ流れる。
+#[derive(Debug, Clone)]
+pub struct ImageFragment {
+    pub image: Arc<SixelImage>,
+    /// 画像内の区画の位置 (列, 行)
+    pub column: usize,
+    pub row: usize,
+    /// 配置時の 1 セルあたりのピクセル数 (幅, 高さ)
+    pub cell_size: (usize, usize),
+}
+
+impl ImageFragment {
+    /// この区画が画像上で占める矩形 (left, top, right, bottom)。画像の端で切り詰める。
+    pub fn source_rect(&self) -> (usize, usize, usize, usize) {
+        let (cell_width, cell_height) = self.cell_size;
+        let left = (self.column * cell_width).min(self.image.width);
+        let top = (self.row * cell_height).min(self.image.height);
+        (
+            left,
+            top,
+            (left + cell_width).min(self.image.width),
+            (top + cell_height).min(self.image.height),
+        )
+    }
+}
+
+impl PartialEq for ImageFragment {
+    /// 同じ画像の同じ区画であれば等しい。画素の比較は行わない。
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.image, &other.image)
+            && self.column == other.column
+            && self.row == other.row
+            && self.cell_size == other.cell_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_rect_is_clipped() {
+        let image = Arc::new(SixelImage::new(25, 30, vec![0; 25 * 30 * 4]).unwrap());
+        let fragment = |column, row| ImageFragment {
+            image: image.clone(),
+            column,
+            row,
+            cell_size: (10, 20),
+        };
+        assert_eq!(fragment(0, 0).source_rect(), (0, 0, 10, 20));
+        assert_eq!(fragment(2, 1).source_rect(), (20, 20, 25, 30));
+        assert_eq!(fragment(1, 0), fragment(1, 0));
+        assert_ne!(fragment(1, 0), fragment(0, 0));
+    }
+
+    #[test]
+    fn test_invalid_size() {
+        assert!(SixelImage::new(2, 2, vec![0; 15]).is_none());
+        assert!(SixelImage::new(0, 0, Vec::new()).is_none());
+    }
+}
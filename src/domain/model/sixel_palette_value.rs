@@ -0,0 +1,119 @@
+/// 色レジスタの数
+const COLOR_REGISTER_COUNT: usize = 256;
+
+/// VT340 の既定の色レジスタ (RGB 各 0〜100%)
+const DEFAULT_PALETTE: [(u16, u16, u16); 16] = [
+    (0, 0, 0),
+    (20, 20, 80),
+    (80, 13, 13),
+    (20, 80, 20),
+    (80, 20, 80),
+    (20, 80, 80),
+    (80, 80, 20),
+    (53, 53, 53),
+    (26, 26, 26),
+    (33, 33, 60),
+    (60, 26, 26),
+    (33, 60, 33),
+    (60, 33, 60),
+    (33, 60, 60),
+    (60, 60, 33),
+    (80, 80, 80),
+];
+
+/// Sixel の色レジスタ (RGBA)
+/// 範囲外のレジスタ番号はレジスタ数で折り返して扱う。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SixelPalette {
+    registers: Vec<[u8; 4]>,
+}
+
+impl SixelPalette {
+    /// VT340 の既定色で初期化する。17 番以降は黒
+    pub fn new() -> Self {
+        let mut registers = vec![[0, 0, 0, 255]; COLOR_REGISTER_COUNT];
+        for (entry, &(r, g, b)) in registers.iter_mut().zip(DEFAULT_PALETTE.iter()) {
+            *entry = [percent_to_u8(r), percent_to_u8(g), percent_to_u8(b), 255];
+        }
+        Self { registers }
+    }
+
+    /// レジスタ番号をレジスタ数の範囲に収める
+    pub fn register_index(register: usize) -> usize {
+        register % COLOR_REGISTER_COUNT
+    }
+
+    pub fn get(&self, register: usize) -> [u8; 4] {
+        self.registers[Self::register_index(register)]
+    }
+
+    /// `#Pc;Pu;Px;Py;Pz` の色を定義する。Pu は 1: HLS、2: RGB。その他の色空間は無視する。
+    pub fn define(&mut self, register: usize, color_space: u16, x: u16, y: u16, z: u16) {
+        let color = match color_space {
+            1 => hls_to_rgba(x, y, z),
+            2 => [percent_to_u8(x), percent_to_u8(y), percent_to_u8(z), 255],
+            _ => return,
+        };
+        self.registers[Self::register_index(register)] = color;
+    }
+}
+
+impl Default for SixelPalette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn percent_to_u8(value: u16) -> u8 {
+    (value.min(100) as u32 * 255 / 100) as u8
+}
+
+/// Sixel の HLS (色相 0〜360、明度・彩度 0〜100%) を RGBA に変換する。
+/// Sixel の色相は青を 0 度とするため、一般的な HLS (赤が 0 度) へ 240 度ずらして計算する。
+fn hls_to_rgba(hue: u16, lightness: u16, saturation: u16) -> [u8; 4] {
+    let h = ((hue as u32 + 240) % 360) as f32;
+    let l = lightness.min(100) as f32 / 100.0;
+    let s = saturation.min(100) as f32 / 100.0;
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let m = l - c / 2.0;
+    let (r, g, b) = match h as u32 {
+        0..60 => (c, x, 0.0),
+        60..120 => (x, c, 0.0),
+        120..180 => (0.0, c, x),
+        180..240 => (0.0, x, c),
+        240..300 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let to_u8 = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    [to_u8(r), to_u8(g), to_u8(b), 255]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hls_colors() {
+        // Sixel の HLS は青が 0 度、赤が 120 度、緑が 240 度
+        assert_eq!(hls_to_rgba(0, 50, 100), [0, 0, 255, 255]);
+        assert_eq!(hls_to_rgba(120, 50, 100), [255, 0, 0, 255]);
+        assert_eq!(hls_to_rgba(240, 50, 100), [0, 255, 0, 255]);
+        assert_eq!(hls_to_rgba(0, 100, 0), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_define_registers() {
+        let mut palette = SixelPalette::new();
+        assert_eq!(palette.get(0), [0, 0, 0, 255]);
+        assert_eq!(palette.get(15), [204, 204, 204, 255]);
+        palette.define(1, 2, 100, 0, 50);
+        assert_eq!(palette.get(1), [255, 0, 127, 255]);
+        // 範囲外のレジスタ番号は折り返す
+        palette.define(258, 2, 0, 100, 0);
+        assert_eq!(palette.get(2), [0, 255, 0, 255]);
+        // 未知の色空間は無視する
+        palette.define(3, 3, 100, 100, 100);
+        assert_eq!(palette.get(3), SixelPalette::new().get(3));
+    }
+}
@@ -0,0 +1,82 @@
+/// 既定のタブ間隔
+const DEFAULT_TAB_WIDTH: usize = 8;
+
+/// 水平タブの停止位置
+/// HTS / TBC で変更され、リサイズや RIS で既定の 8 桁間隔に戻る。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabStops {
+    stops: Vec<bool>,
+}
+
+impl TabStops {
+    pub fn new(width: usize) -> Self {
+        Self {
+            stops: (0..width)
+                .map(|col| col > 0 && col % DEFAULT_TAB_WIDTH == 0)
+                .collect(),
+        }
+    }
+
+    /// HTS: 指定した桁に停止位置を設定する
+    pub fn set(&mut self, col: usize) {
+        if let Some(stop) = self.stops.get_mut(col) {
+            *stop = true;
+        }
+    }
+
+    /// TBC 0: 指定した桁の停止位置を解除する
+    pub fn clear(&mut self, col: usize) {
+        if let Some(stop) = self.stops.get_mut(col) {
+            *stop = false;
+        }
+    }
+
+    /// TBC 3: すべての停止位置を解除する
+    pub fn clear_all(&mut self) {
+        self.stops.fill(false);
+    }
+
+    /// col より右にある次の停止位置。無い場合は右端の桁を返す。
+    pub fn next(&self, col: usize) -> usize {
+        (col + 1..self.stops.len())
+            .find(|&c| self.stops[c])
+            .unwrap_or(self.stops.len().saturating_sub(1))
+    }
+
+    /// col より左にある前の停止位置。無い場合は左端の桁を返す。
+    pub fn previous(&self, col: usize) -> usize {
+        (0..col.min(self.stops.len()))
+            .rev()
+            .find(|&c| self.stops[c])
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_stops() {
+        let stops = TabStops::new(20);
+        assert_eq!(stops.next(0), 8);
+        assert_eq!(stops.next(8), 16);
+        assert_eq!(stops.next(16), 19);
+        assert_eq!(stops.next(19), 19);
+        assert_eq!(stops.previous(17), 16);
+        assert_eq!(stops.previous(16), 8);
+        assert_eq!(stops.previous(3), 0);
+    }
+
+    #[test]
+    fn test_set_and_clear() {
+        let mut stops = TabStops::new(20);
+        stops.set(3);
+        assert_eq!(stops.next(0), 3);
+        stops.clear(8);
+        assert_eq!(stops.next(3), 16);
+        stops.clear_all();
+        assert_eq!(stops.next(0), 19);
+        assert_eq!(stops.previous(19), 0);
+    }
+}
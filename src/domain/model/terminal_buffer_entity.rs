@@ -2,14 +2,41 @@ use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 // 基本型を再エクスポートし、外部からアクセス可能にする
+use super::character_set_value::CharsetState;
+use super::clipboard_value::ClipboardRequest;
+use super::color_palette_entity::ColorPaletteEntity;
+use super::color_theme_value::ColorTheme;
+use super::hyperlink_registry_entity::HyperlinkRegistryEntity;
+use super::input_value::InputModes;
+use super::kitty_keyboard_value::KittyKeyboardStack;
+use super::sixel_image_registry_entity::SixelImageRegistryEntity;
+use super::tab_stops_value::TabStops;
 use super::terminal_grid_entity::TerminalGridEntity;
+use super::terminal_line_entity::TerminalLine;
 use super::terminal_scrollback_entity::TerminalScrollbackEntity;
+use super::terminal_title_value::TerminalTitle;
 pub use super::terminal_types_entity::{
-    Cell, Cursor, CursorStyle, MouseTrackingMode, TerminalAttribute, TerminalColor,
+    Cell, Cursor, CursorStyle, MouseEncoding, MouseTrackingMode, SavedCursor,
+    SynchronizedFrameEvents, TerminalAttribute, TerminalColor,
 };
+use super::working_directory_value::WorkingDirectory;
+
+mod alternate_screen;
+mod charset;
+mod hyperlink;
+mod image_placement;
+mod kitty_keyboard;
+mod resize;
+mod semantic_prompt;
+mod synchronized_output;
+mod tab_stops;
+mod title;
 
 pub struct TerminalBufferEntity {
     grid: TerminalGridEntity,
+    /// 非アクティブな画面のグリッド（主画面表示中は代替画面、代替画面表示中は主画面）
+    inactive_grid: TerminalGridEntity,
+    is_alternate_screen: bool,
     scrollback: TerminalScrollbackEntity,
     width: usize,
     height: usize,
@@ -18,18 +45,64 @@ pub struct TerminalBufferEntity {
     scroll_top: usize,
     scroll_bottom: usize,
     is_origin_mode: bool,
+    /// DECAWM: 行末に達した文字の次で自動的に折り返すか
+    is_autowrap: bool,
+    /// IRM: 文字の出力時に既存の文字を右へずらして挿入するか
+    is_insert_mode: bool,
+    /// REP で繰り返す、直前に出力した書記素クラスタ
+    last_graphic: Option<String>,
+    /// 最終桁に文字を書いた直後で、次の文字の出力時に折り返す状態 (xterm の "last column flag")
+    is_pending_wrap: bool,
+    tab_stops: TabStops,
+    /// G0〜G3 の文字集合の指定と呼び出し状態
+    charsets: CharsetState,
     last_inverse_render_pos: Option<(usize, usize)>,
-    saved_cursor: Option<(usize, usize)>,
+    saved_cursor: Option<SavedCursor>,
+    /// 非アクティブな画面側で保存されているカーソル位置
+    inactive_saved_cursor: Option<SavedCursor>,
     pending_cluster: String,
     mouse_tracking_mode: MouseTrackingMode,
-    use_sgr_mouse_encoding: bool,
+    mouse_encoding: MouseEncoding,
+    is_bracketed_paste: bool,
+    /// DECSET 1004: フォーカスの取得・喪失を通知するか
+    is_focus_reporting: bool,
+    /// DECSET 1007: マウス報告が無効な代替画面でホイールをカーソルキーとして送るか。
+    /// less や man をそのままスクロールできるよう、既定で有効にしている
+    is_alternate_scroll: bool,
+    /// DECSET 2026: アプリケーションが 1 フレーム分の出力を終えるまで描画を保留するか
+    is_synchronized_output: bool,
+    /// 1 回の出力の途中で同期出力が切り替わっても取りこぼさないよう、区切りを記録する
+    synchronized_frame_events: SynchronizedFrameEvents,
+    /// DECCKM / DECKPAM で切り替えられるキー入力のモード
+    input_modes: InputModes,
+    /// kitty キーボードプロトコルのフラグスタック (主画面と代替画面で別々に保持する)
+    kitty_keyboard: KittyKeyboardStack,
+    inactive_kitty_keyboard: KittyKeyboardStack,
     last_mouse_pos: Option<(usize, usize)>,
+    title: TerminalTitle,
+    title_stack: Vec<TerminalTitle>,
+    hyperlinks: HyperlinkRegistryEntity,
+    /// OSC 52 で要求され、まだ GUI 側で処理されていないクリップボード操作
+    pending_clipboard_requests: Vec<ClipboardRequest>,
+    /// OSC 7 / OSC 9;9 で通知されたシェルのカレントディレクトリ
+    current_directory: Option<WorkingDirectory>,
+    /// テーマの上に OSC 4/10/11/12 の変更を重ねた配色
+    palette: ColorPaletteEntity,
+    /// 1 セルあたりのピクセル数 (幅, 高さ)。Sixel 画像が占めるセル数の計算に使う
+    cell_pixel_size: (usize, usize),
+    /// 画面とスクロールバックに配置された Sixel 画像の使用メモリ
+    image_registry: SixelImageRegistryEntity,
 }
 
+/// 描画側からセルの大きさが通知されるまで使用する 1 セルあたりのピクセル数
+const DEFAULT_CELL_PIXEL_SIZE: (usize, usize) = (10, 20);
+
 impl TerminalBufferEntity {
     pub fn new(width: usize, height: usize) -> Self {
         Self {
             grid: TerminalGridEntity::new(width, height),
+            inactive_grid: TerminalGridEntity::new(width, height),
+            is_alternate_screen: false,
             scrollback: TerminalScrollbackEntity::new(10000),
             width,
             height,
@@ -38,12 +111,35 @@ impl TerminalBufferEntity {
             scroll_top: 0,
             scroll_bottom: height.saturating_sub(1),
             is_origin_mode: false,
+            is_autowrap: true,
+            is_insert_mode: false,
+            last_graphic: None,
+            is_pending_wrap: false,
+            tab_stops: TabStops::new(width),
+            charsets: CharsetState::default(),
             last_inverse_render_pos: None,
             saved_cursor: None,
+            inactive_saved_cursor: None,
             pending_cluster: String::new(),
             mouse_tracking_mode: MouseTrackingMode::None,
-            use_sgr_mouse_encoding: false,
+            mouse_encoding: MouseEncoding::Default,
+            is_bracketed_paste: false,
+            is_focus_reporting: false,
+            is_alternate_scroll: true,
+            is_synchronized_output: false,
+            synchronized_frame_events: SynchronizedFrameEvents::default(),
+            input_modes: InputModes::default(),
+            kitty_keyboard: KittyKeyboardStack::default(),
+            inactive_kitty_keyboard: KittyKeyboardStack::default(),
             last_mouse_pos: None,
+            title: TerminalTitle::default(),
+            title_stack: Vec::new(),
+            hyperlinks: HyperlinkRegistryEntity::new(),
+            pending_clipboard_requests: Vec::new(),
+            current_directory: None,
+            palette: ColorPaletteEntity::new(ColorTheme::default()),
+            cell_pixel_size: DEFAULT_CELL_PIXEL_SIZE,
+            image_registry: SixelImageRegistryEntity::default(),
         }
     }
 
@@ -56,6 +152,7 @@ impl TerminalBufferEntity {
                 ..TerminalAttribute::default()
             },
             is_wide_continuation: false,
+            image: None,
         }
     }
 
@@ -69,6 +166,8 @@ impl TerminalBufferEntity {
         if c.is_control() && !"\r\n\t\x08".contains(c) {
             return;
         }
+        // 書記素の分割より前に、指定されている文字集合の文字へ変換する
+        let c = self.charsets.translate(c);
         self.pending_cluster.push(c);
         let mut clusters: Vec<String> = self
             .pending_cluster
@@ -78,23 +177,7 @@ impl TerminalBufferEntity {
         if clusters.len() > 1 {
             let last = clusters.pop().unwrap();
             for cluster in clusters {
-                let w = cluster.width().clamp(1, 2);
-                if self.cursor.x + w > self.width {
-                    self.cursor.x = 0;
-                    self.index();
-                }
-                self.grid.put_cell(
-                    self.cursor.x,
-                    self.cursor.y,
-                    Cell {
-                        text: cluster,
-                        attribute: self.current_attribute.clone(),
-                        is_wide_continuation: false,
-                    },
-                    w,
-                    &self.current_attribute.bg,
-                );
-                self.cursor.x += w;
+                self.write_cluster(cluster);
             }
             self.pending_cluster = last;
         }
@@ -105,11 +188,30 @@ impl TerminalBufferEntity {
             return;
         }
         let cluster = std::mem::take(&mut self.pending_cluster);
+        self.write_cluster(cluster);
+    }
+
+    /// 書記素クラスタを 1 つカーソル位置に書き込む。
+    /// xterm と同様に、最終桁へ書いた後の折り返しは次の文字を出力するまで保留する。
+    fn write_cluster(&mut self, cluster: String) {
         let w = cluster.width().clamp(1, 2);
-        if self.cursor.x + w > self.width {
-            self.cursor.x = 0;
-            self.index();
+        if self.is_pending_wrap || self.cursor.x + w > self.width {
+            if self.is_autowrap {
+                if let Some(line) = self.grid.lines_mut().get_mut(self.cursor.y) {
+                    line.set_wrapped(true);
+                }
+                self.cursor.x = 0;
+                self.index();
+            } else {
+                // 折り返さない場合は行末の桁を上書きし続ける
+                self.cursor.x = self.width.saturating_sub(w);
+            }
+            self.is_pending_wrap = false;
         }
+        if self.is_insert_mode {
+            self.insert_cells(w);
+        }
+        self.last_graphic = Some(cluster.clone());
         self.grid.put_cell(
             self.cursor.x,
             self.cursor.y,
@@ -117,20 +219,31 @@ impl TerminalBufferEntity {
                 text: cluster,
                 attribute: self.current_attribute.clone(),
                 is_wide_continuation: false,
+                image: None,
             },
             w,
             &self.current_attribute.bg,
         );
-        self.cursor.x += w;
+        if self.cursor.x + w >= self.width {
+            self.cursor.x = self.width.saturating_sub(1);
+            self.is_pending_wrap = self.is_autowrap;
+        } else {
+            self.cursor.x += w;
+        }
     }
 
     pub fn scroll_up(&mut self) {
         let empty = self.get_empty_cell();
         if self.scroll_top == 0 && self.scroll_bottom == self.height.saturating_sub(1) {
-            if let Some(line) = self.grid.lines_mut().pop_front() {
+            // 代替画面の内容はスクロールバックに残さない
+            if let Some(line) = self.grid.lines_mut().pop_front()
+                && !self.is_alternate_screen
+            {
                 self.scrollback.push(line);
             }
-            self.grid.lines_mut().push_back(vec![empty; self.width]);
+            self.grid
+                .lines_mut()
+                .push_back(TerminalLine::from(vec![empty; self.width]));
         } else {
             self.grid.delete_lines(
                 self.scroll_top,
@@ -152,6 +265,7 @@ impl TerminalBufferEntity {
     }
 
     pub fn index(&mut self) {
+        self.is_pending_wrap = false;
         if self.cursor.y == self.scroll_bottom {
             self.scroll_up();
         } else if self.cursor.y < self.height - 1 {
@@ -160,6 +274,7 @@ impl TerminalBufferEntity {
     }
 
     pub fn reverse_index(&mut self) {
+        self.is_pending_wrap = false;
         if self.cursor.y == self.scroll_top {
             self.scroll_down();
         } else if self.cursor.y > 0 {
@@ -168,6 +283,7 @@ impl TerminalBufferEntity {
     }
 
     pub fn move_cursor_to_pos(&mut self, r: usize, c: usize) {
+        self.is_pending_wrap = false;
         let y = if self.is_origin_mode {
             (self.scroll_top + r).saturating_sub(1)
         } else {
@@ -178,21 +294,27 @@ impl TerminalBufferEntity {
     }
 
     pub fn move_cursor_to_col(&mut self, col: usize) {
+        self.is_pending_wrap = false;
         self.cursor.x = col.min(self.width.saturating_sub(1));
     }
     pub fn move_cursor_to_row(&mut self, row: usize) {
+        self.is_pending_wrap = false;
         self.cursor.y = row.min(self.height.saturating_sub(1));
     }
     pub fn move_cursor_up(&mut self, n: usize) {
+        self.is_pending_wrap = false;
         self.cursor.y = self.cursor.y.saturating_sub(n).max(self.scroll_top);
     }
     pub fn move_cursor_down(&mut self, n: usize) {
+        self.is_pending_wrap = false;
         self.cursor.y = (self.cursor.y + n).min(self.scroll_bottom);
     }
     pub fn move_cursor_forward(&mut self, n: usize) {
+        self.is_pending_wrap = false;
         self.cursor.x = (self.cursor.x + n).min(self.width.saturating_sub(1));
     }
     pub fn move_cursor_backward(&mut self, n: usize) {
+        self.is_pending_wrap = false;
         for _ in 0..n {
             if self.cursor.x == 0 {
                 break;
@@ -206,27 +328,8 @@ impl TerminalBufferEntity {
         }
     }
 
-    pub fn handle_tab(&mut self) {
-        let nx = (self.cursor.x / 8 + 1) * 8;
-        if nx >= self.width {
-            self.cursor.x = 0;
-            self.index();
-        } else {
-            while self.cursor.x < nx {
-                let empty = self.get_empty_cell();
-                self.grid.put_cell(
-                    self.cursor.x,
-                    self.cursor.y,
-                    empty,
-                    1,
-                    &self.current_attribute.bg,
-                );
-                self.cursor.x += 1;
-            }
-        }
-    }
-
     pub fn insert_lines(&mut self, n: usize) {
+        self.is_pending_wrap = false;
         if self.cursor.y < self.scroll_top || self.cursor.y > self.scroll_bottom {
             return;
         }
@@ -241,6 +344,7 @@ impl TerminalBufferEntity {
     }
 
     pub fn delete_lines(&mut self, n: usize) {
+        self.is_pending_wrap = false;
         if self.cursor.y < self.scroll_top || self.cursor.y > self.scroll_bottom {
             return;
         }
@@ -255,6 +359,7 @@ impl TerminalBufferEntity {
     }
 
     pub fn insert_cells(&mut self, n: usize) {
+        self.is_pending_wrap = false;
         let n = n.min(self.width - self.cursor.x);
         if n > 0 {
             self.grid.ensure_safe_boundary(
@@ -262,6 +367,12 @@ impl TerminalBufferEntity {
                 self.cursor.x,
                 &self.current_attribute.bg,
             );
+            // 右端から押し出される位置で全角文字が分断されないようにする
+            self.grid.ensure_safe_boundary(
+                self.cursor.y,
+                self.width - n,
+                &self.current_attribute.bg,
+            );
             let empty = self.get_empty_cell();
             let line = &mut self.grid.lines_mut()[self.cursor.y];
             for _ in 0..n {
@@ -272,6 +383,7 @@ impl TerminalBufferEntity {
     }
 
     pub fn delete_cells(&mut self, n: usize) {
+        self.is_pending_wrap = false;
         let n = n.min(self.width - self.cursor.x);
         if n == 0 {
             return;
@@ -290,12 +402,20 @@ impl TerminalBufferEntity {
     }
 
     pub fn erase_cells(&mut self, n: usize) {
+        self.is_pending_wrap = false;
         let empty = self.get_empty_cell();
         self.grid
             .fill_line(self.cursor.y, self.cursor.x, self.cursor.x + n, empty);
     }
     pub fn erase_in_line(&mut self, mode: u8) {
+        self.is_pending_wrap = false;
         let empty = self.get_empty_cell();
+        // 行末まで消去した行は次の行へ続かなくなる
+        if matches!(mode, 0 | 2)
+            && let Some(line) = self.grid.lines_mut().get_mut(self.cursor.y)
+        {
+            line.set_wrapped(false);
+        }
         match mode {
             0 => self
                 .grid
@@ -309,17 +429,20 @@ impl TerminalBufferEntity {
     }
 
     pub fn erase_in_display(&mut self, mode: u8) {
+        self.is_pending_wrap = false;
         let empty = self.get_empty_cell();
         match mode {
             0 => {
                 self.erase_in_line(0);
                 for y in (self.cursor.y + 1)..self.height {
                     self.grid.fill_line(y, 0, self.width, empty.clone());
+                    self.grid.lines_mut()[y].set_wrapped(false);
                 }
             }
             1 => {
                 for y in 0..self.cursor.y {
                     self.grid.fill_line(y, 0, self.width, empty.clone());
+                    self.grid.lines_mut()[y].set_wrapped(false);
                 }
                 self.erase_in_line(1);
             }
@@ -327,12 +450,18 @@ impl TerminalBufferEntity {
                 for y in 0..self.height {
                     self.grid.fill_line(y, 0, self.width, empty.clone());
                 }
+                // 画面消去で消えたプロンプトのマークと折り返し情報も取り除く
+                for line in self.grid.lines_mut() {
+                    line.clear_prompt_marks();
+                    line.set_wrapped(false);
+                }
             }
             _ => {}
         }
     }
 
     pub fn set_scroll_region(&mut self, top: usize, bottom: usize) {
+        self.is_pending_wrap = false;
         let t = top.saturating_sub(1).min(self.height.saturating_sub(1));
         let b = bottom.saturating_sub(1).min(self.height.saturating_sub(1));
         if t < b {
@@ -351,10 +480,34 @@ impl TerminalBufferEntity {
     }
 
     pub fn set_origin_mode(&mut self, on: bool) {
+        self.is_pending_wrap = false;
         self.is_origin_mode = on;
         self.cursor.y = if on { self.scroll_top } else { 0 };
         self.cursor.x = 0;
     }
+    /// REP: 直前に出力した文字を n 回繰り返す
+    pub fn repeat_last_graphic(&mut self, n: usize) {
+        let Some(cluster) = self.last_graphic.clone() else {
+            return;
+        };
+        for _ in 0..n {
+            self.write_cluster(cluster.clone());
+        }
+    }
+
+    pub fn is_insert_mode(&self) -> bool {
+        self.is_insert_mode
+    }
+    pub fn set_insert_mode(&mut self, enabled: bool) {
+        self.is_insert_mode = enabled;
+    }
+    pub fn is_autowrap_enabled(&self) -> bool {
+        self.is_autowrap
+    }
+    pub fn set_autowrap(&mut self, enabled: bool) {
+        self.is_autowrap = enabled;
+        self.is_pending_wrap = false;
+    }
     pub fn set_cursor_visible(&mut self, v: bool) {
         self.cursor.is_visible = v;
     }
@@ -367,26 +520,28 @@ impl TerminalBufferEntity {
     pub fn get_current_attribute(&self) -> &TerminalAttribute {
         &self.current_attribute
     }
-    pub fn save_cursor(&mut self) {
-        self.saved_cursor = Some((self.cursor.x, self.cursor.y));
-    }
-    pub fn restore_cursor(&mut self) {
-        if let Some((x, y)) = self.saved_cursor {
-            self.cursor.y = y.min(self.height.saturating_sub(1));
-            self.cursor.x = x.min(self.width.saturating_sub(1));
-        }
-    }
-    pub fn resize(&mut self, w: usize, h: usize) {
-        self.grid.resize(w, h);
-        self.width = w;
-        self.height = h;
-        self.scroll_top = 0;
-        self.scroll_bottom = h.saturating_sub(1);
-        self.cursor.y = self.cursor.y.min(h.saturating_sub(1));
-        self.cursor.x = self.cursor.x.min(w.saturating_sub(1));
+
+    /// RIS: 端末を初期状態に戻す。
+    /// スクロールバック・タイトル・カレントディレクトリ・ハイパーリンクの登録は維持し、配色はテーマに戻す。
+    pub fn reset(&mut self) {
+        let initial = Self::new(self.width, self.height);
+        let previous = std::mem::replace(self, initial);
+        self.scrollback = previous.scrollback;
+        self.title = previous.title;
+        self.title_stack = previous.title_stack;
+        self.hyperlinks = previous.hyperlinks;
+        self.pending_clipboard_requests = previous.pending_clipboard_requests;
+        self.current_directory = previous.current_directory;
+        self.palette = previous.palette;
+        self.palette.reset();
+        self.cell_pixel_size = previous.cell_pixel_size;
+        // スクロールバックに残る画像も使用メモリに含める
+        self.image_registry = previous.image_registry;
+        // 消去した画面にだけあったリンクの登録を外す
+        self.prune_hyperlinks();
     }
 
-    pub fn get_line_at_visual_row(&self, visual_row: usize) -> Option<&Vec<Cell>> {
+    pub fn get_line_at_visual_row(&self, visual_row: usize) -> Option<&TerminalLine> {
         let dist = (self.height.saturating_sub(1).saturating_sub(visual_row))
             + self.scrollback.viewport_offset();
         if dist < self.grid.lines().len() {
@@ -415,6 +570,10 @@ impl TerminalBufferEntity {
         self.height
     }
     pub fn get_history_len(&self) -> usize {
+        // 代替画面ではスクロールバックを参照させない
+        if self.is_alternate_screen {
+            return 0;
+        }
         self.scrollback.history().len()
     }
     pub fn get_viewport_offset(&self) -> usize {
@@ -426,6 +585,18 @@ impl TerminalBufferEntity {
     pub fn get_cursor_pos(&self) -> (usize, usize) {
         (self.cursor.x, self.cursor.y)
     }
+    /// CPR で報告するカーソル位置 (行, 列)。1 始まりで、原点モードではスクロール領域からの相対位置となる
+    pub fn get_cursor_report_pos(&self) -> (usize, usize) {
+        let row = if self.is_origin_mode {
+            self.cursor.y.saturating_sub(self.scroll_top)
+        } else {
+            self.cursor.y
+        };
+        (row + 1, self.cursor.x.min(self.width.saturating_sub(1)) + 1)
+    }
+    pub fn is_origin_mode(&self) -> bool {
+        self.is_origin_mode
+    }
     pub fn get_cursor_style(&self) -> CursorStyle {
         self.cursor.style
     }
@@ -442,9 +613,15 @@ impl TerminalBufferEntity {
         )
     }
     pub fn scroll_to(&mut self, o: usize) {
+        if self.is_alternate_screen {
+            return;
+        }
         self.scrollback.scroll_to(o);
     }
     pub fn scroll_lines(&mut self, d: isize) {
+        if self.is_alternate_screen {
+            return;
+        }
         self.scrollback.scroll_lines(d);
     }
     pub fn reset_viewport(&mut self) {
@@ -457,11 +634,53 @@ impl TerminalBufferEntity {
     pub fn set_mouse_tracking_mode(&mut self, mode: MouseTrackingMode) {
         self.mouse_tracking_mode = mode;
     }
-    pub fn is_sgr_mouse_encoding_enabled(&self) -> bool {
-        self.use_sgr_mouse_encoding
+    pub fn get_mouse_encoding(&self) -> MouseEncoding {
+        self.mouse_encoding
+    }
+    pub fn set_mouse_encoding(&mut self, encoding: MouseEncoding) {
+        self.mouse_encoding = encoding;
+    }
+    /// 指定した送信形式が有効な場合のみ既定の形式に戻す
+    pub fn reset_mouse_encoding(&mut self, encoding: MouseEncoding) {
+        if self.mouse_encoding == encoding {
+            self.mouse_encoding = MouseEncoding::Default;
+        }
+    }
+
+    pub fn is_bracketed_paste_enabled(&self) -> bool {
+        self.is_bracketed_paste
+    }
+    pub fn set_bracketed_paste(&mut self, enabled: bool) {
+        self.is_bracketed_paste = enabled;
+    }
+
+    pub fn is_focus_reporting_enabled(&self) -> bool {
+        self.is_focus_reporting
+    }
+    pub fn set_focus_reporting(&mut self, enabled: bool) {
+        self.is_focus_reporting = enabled;
+    }
+    pub fn is_alternate_scroll_enabled(&self) -> bool {
+        self.is_alternate_scroll
     }
-    pub fn set_sgr_mouse_encoding(&mut self, enabled: bool) {
-        self.use_sgr_mouse_encoding = enabled;
+    pub fn set_alternate_scroll(&mut self, enabled: bool) {
+        self.is_alternate_scroll = enabled;
+    }
+
+    pub fn get_input_modes(&self) -> InputModes {
+        InputModes {
+            kitty_keyboard_flags: self.kitty_keyboard.current(),
+            ..self.input_modes
+        }
+    }
+    pub fn set_application_cursor_keys(&mut self, enabled: bool) {
+        self.input_modes.is_application_cursor_keys = enabled;
+    }
+    pub fn set_application_keypad(&mut self, enabled: bool) {
+        self.input_modes.is_application_keypad = enabled;
+    }
+    pub fn set_win32_input_mode(&mut self, enabled: bool) {
+        self.input_modes.is_win32_input_mode = enabled;
     }
 
     pub fn get_last_mouse_pos(&self) -> Option<(usize, usize)> {
@@ -470,4 +689,27 @@ impl TerminalBufferEntity {
     pub fn set_last_mouse_pos(&mut self, pos: Option<(usize, usize)>) {
         self.last_mouse_pos = pos;
     }
+
+    pub fn push_clipboard_request(&mut self, request: ClipboardRequest) {
+        self.pending_clipboard_requests.push(request);
+    }
+
+    /// 溜まっているクリップボード要求を取り出す
+    pub fn take_clipboard_requests(&mut self) -> Vec<ClipboardRequest> {
+        std::mem::take(&mut self.pending_clipboard_requests)
+    }
+
+    pub fn get_current_directory(&self) -> Option<&WorkingDirectory> {
+        self.current_directory.as_ref()
+    }
+    pub fn set_current_directory(&mut self, directory: WorkingDirectory) {
+        self.current_directory = Some(directory);
+    }
+
+    pub fn get_palette(&self) -> &ColorPaletteEntity {
+        &self.palette
+    }
+    pub fn get_palette_mut(&mut self) -> &mut ColorPaletteEntity {
+        &mut self.palette
+    }
 }
@@ -0,0 +1,53 @@
+use super::{SavedCursor, TerminalBufferEntity};
+
+/// 代替画面 (DECSET 47/1047/1049) の切り替えと、画面ごとのカーソル保存 (DECSC/DECRC)
+impl TerminalBufferEntity {
+    pub fn save_cursor(&mut self) {
+        self.saved_cursor = Some(SavedCursor {
+            x: self.cursor.x,
+            y: self.cursor.y,
+            is_pending_wrap: self.is_pending_wrap,
+        });
+    }
+
+    pub fn restore_cursor(&mut self) {
+        self.is_pending_wrap = false;
+        if let Some(saved) = self.saved_cursor {
+            self.cursor.y = saved.y.min(self.height.saturating_sub(1));
+            self.cursor.x = saved.x.min(self.width.saturating_sub(1));
+            // 保存後に画面幅が変わった場合は、最終桁に戻れたときだけ折り返し待ちを復元する
+            self.is_pending_wrap =
+                saved.is_pending_wrap && self.cursor.x == self.width.saturating_sub(1);
+        }
+    }
+
+    /// 代替画面へ切り替える。主画面の内容とカーソル保存位置はそのまま退避される。
+    pub fn switch_to_alternate_screen(&mut self) {
+        if self.is_alternate_screen {
+            return;
+        }
+        self.swap_screens();
+        self.is_alternate_screen = true;
+        self.scrollback.reset_viewport();
+    }
+
+    /// 主画面へ戻る。退避していた主画面の内容とカーソル保存位置が復元される。
+    pub fn switch_to_primary_screen(&mut self) {
+        if !self.is_alternate_screen {
+            return;
+        }
+        self.swap_screens();
+        self.is_alternate_screen = false;
+    }
+
+    fn swap_screens(&mut self) {
+        self.is_pending_wrap = false;
+        std::mem::swap(&mut self.grid, &mut self.inactive_grid);
+        std::mem::swap(&mut self.saved_cursor, &mut self.inactive_saved_cursor);
+        std::mem::swap(&mut self.kitty_keyboard, &mut self.inactive_kitty_keyboard);
+    }
+
+    pub fn is_alternate_screen(&self) -> bool {
+        self.is_alternate_screen
+    }
+}
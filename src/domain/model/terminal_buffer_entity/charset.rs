@@ -0,0 +1,20 @@
+use super::TerminalBufferEntity;
+use crate::domain::model::character_set_value::CharacterSet;
+
+/// G0〜G3 の文字集合の指定と呼び出し
+impl TerminalBufferEntity {
+    /// ESC ( ) * + : G0〜G3 に文字集合を指定する
+    pub fn designate_charset(&mut self, slot: usize, charset: CharacterSet) {
+        self.charsets.designate(slot, charset);
+    }
+
+    /// SI / SO / LS2 / LS3 : GL に G 集合を呼び出す
+    pub fn invoke_charset(&mut self, slot: usize) {
+        self.charsets.invoke(slot);
+    }
+
+    /// SS2 / SS3 : 次の 1 文字だけ G2 / G3 を使用する
+    pub fn single_shift_charset(&mut self, slot: usize) {
+        self.charsets.single_shift(slot);
+    }
+}
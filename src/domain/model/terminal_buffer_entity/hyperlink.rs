@@ -0,0 +1,41 @@
+use std::collections::HashSet;
+
+use super::TerminalBufferEntity;
+use crate::domain::model::hyperlink_value::Hyperlink;
+
+/// OSC 8 のハイパーリンクの設定と、セル位置からの解決
+impl TerminalBufferEntity {
+    /// 以降に出力される文字へ OSC 8 のハイパーリンクを設定する（None で解除）
+    pub fn set_hyperlink(&mut self, link: Option<Hyperlink>) {
+        if link.is_some() && self.hyperlinks.needs_sweep() {
+            self.prune_hyperlinks();
+        }
+        self.current_attribute.hyperlink_id = link.map(|l| self.hyperlinks.intern(l));
+    }
+
+    /// 表示上の位置にあるセルのハイパーリンクを取得する（スクロールバック表示中も解決できる）
+    pub fn get_hyperlink_at(&self, x: usize, visual_row: usize) -> Option<&Hyperlink> {
+        let cell = self.get_line_at_visual_row(visual_row)?.get(x)?;
+        self.hyperlinks.get(cell.attribute.hyperlink_id?)
+    }
+
+    /// 両画面とスクロールバックのどのセルからも参照されなくなったハイパーリンクの登録を外す
+    pub(super) fn prune_hyperlinks(&mut self) {
+        let mut live: HashSet<_> = self.current_attribute.hyperlink_id.into_iter().collect();
+        let lines = self
+            .scrollback
+            .history()
+            .iter()
+            .chain(self.grid.lines())
+            .chain(self.inactive_grid.lines());
+        for line in lines {
+            live.extend(line.iter().filter_map(|cell| cell.attribute.hyperlink_id));
+        }
+        self.hyperlinks.retain(&live);
+    }
+
+    #[cfg(test)]
+    pub(crate) fn get_hyperlink_count(&self) -> usize {
+        self.hyperlinks.get_link_count()
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::{Arc, Weak};
+
+use super::{Cell, TerminalBufferEntity};
+use crate::domain::model::sixel_image_value::{ImageFragment, SixelImage};
+
+/// Sixel 画像のセルへの配置と、使用メモリの上限を超えた古い画像の破棄
+impl TerminalBufferEntity {
+    /// Sixel 画像をカーソル位置から配置する。
+    /// 画像が覆うセルにそれぞれの区画を割り当て、下端が画面を超える場合はスクロールする。
+    /// 配置後のカーソルは画像の直下の行の、配置を始めた列へ移動する。
+    pub fn place_image(&mut self, image: SixelImage) {
+        self.is_pending_wrap = false;
+        let (cell_width, cell_height) = self.cell_pixel_size;
+        let columns = image.get_width().div_ceil(cell_width);
+        let rows = image.get_height().div_ceil(cell_height);
+        let image = Arc::new(image);
+        let evicted = self.image_registry.register(&image);
+        if !evicted.is_empty() {
+            self.remove_images(&evicted);
+        }
+        let start_x = self.cursor.x;
+        let visible_columns = columns.min(self.width.saturating_sub(start_x));
+        for row in 0..rows {
+            for column in 0..visible_columns {
+                let cell = Cell {
+                    image: Some(ImageFragment {
+                        image: image.clone(),
+                        column,
+                        row,
+                        cell_size: self.cell_pixel_size,
+                    }),
+                    ..self.get_empty_cell()
+                };
+                let bg = cell.attribute.bg.clone();
+                self.grid
+                    .put_cell(start_x + column, self.cursor.y, cell, 1, &bg);
+            }
+            self.index();
+        }
+        self.cursor.x = start_x;
+    }
+
+    /// 使用メモリの上限を超えたため登録を外された画像を、画面とスクロールバックのセルから取り除く
+    fn remove_images(&mut self, images: &[Weak<SixelImage>]) {
+        let lines = self
+            .grid
+            .lines_mut()
+            .iter_mut()
+            .chain(self.inactive_grid.lines_mut().iter_mut())
+            .chain(self.scrollback.history_mut().iter_mut());
+        for cell in lines.flat_map(|line| line.iter_mut()) {
+            if cell.image.as_ref().is_some_and(|fragment| {
+                images
+                    .iter()
+                    .any(|image| std::ptr::eq(image.as_ptr(), Arc::as_ptr(&fragment.image)))
+            }) {
+                cell.image = None;
+            }
+        }
+    }
+
+    /// 描画に使用している 1 セルあたりのピクセル数を設定する
+    pub fn set_cell_pixel_size(&mut self, width: usize, height: usize) {
+        if width > 0 && height > 0 {
+            self.cell_pixel_size = (width, height);
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use super::TerminalBufferEntity;
+use crate::domain::model::kitty_keyboard_value::KittyKeyboardFlags;
+
+/// kitty キーボードプロトコルのフラグのスタック操作 (CSI > u / CSI < u / CSI = u)
+impl TerminalBufferEntity {
+    pub fn push_kitty_keyboard_flags(&mut self, flags: KittyKeyboardFlags) {
+        self.kitty_keyboard.push(flags);
+    }
+
+    pub fn pop_kitty_keyboard_flags(&mut self, count: usize) {
+        self.kitty_keyboard.pop(count);
+    }
+
+    pub fn set_kitty_keyboard_flags(&mut self, flags: KittyKeyboardFlags, mode: u16) {
+        self.kitty_keyboard.set(flags, mode);
+    }
+}
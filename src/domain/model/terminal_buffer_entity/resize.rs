@@ -0,0 +1,72 @@
+use super::{SavedCursor, TerminalBufferEntity};
+use crate::domain::model::tab_stops_value::TabStops;
+use crate::domain::model::terminal_grid_entity::TerminalGridEntity;
+use crate::domain::model::terminal_reflow_value::ReflowedLines;
+
+/// 画面サイズの変更と、主画面の折り返し直し (リフロー)
+impl TerminalBufferEntity {
+    /// 画面サイズを変更する。
+    /// 主画面は自動折り返しでつながった行を新しい幅で折り返し直し、画面に収まらない行はスクロールバックへ送る。
+    /// 代替画面はアプリケーションが再描画するため、単純に切り詰める。
+    pub fn resize(&mut self, w: usize, h: usize) {
+        self.is_pending_wrap = false;
+        if self.is_alternate_screen {
+            self.grid.resize(w, h);
+            // 主画面側のカーソルは DECSC (1049) で保存された位置を基準にする
+            let saved = self.inactive_saved_cursor.map(|saved| (saved.y, saved.x));
+            self.inactive_saved_cursor =
+                self.reflow_primary(w, h, saved).map(|(y, x)| SavedCursor {
+                    x,
+                    y,
+                    is_pending_wrap: false,
+                });
+        } else {
+            self.inactive_grid.resize(w, h);
+            let cursor = Some((self.cursor.y, self.cursor.x));
+            if let Some((y, x)) = self.reflow_primary(w, h, cursor) {
+                self.cursor.y = y;
+                self.cursor.x = x;
+            }
+        }
+        self.width = w;
+        self.height = h;
+        self.tab_stops = TabStops::new(w);
+        self.scroll_top = 0;
+        self.scroll_bottom = h.saturating_sub(1);
+        self.cursor.y = self.cursor.y.min(h.saturating_sub(1));
+        self.cursor.x = self.cursor.x.min(w.saturating_sub(1));
+    }
+
+    /// 主画面とスクロールバックを新しい幅で折り返し直し、画面上のカーソル位置 (行, 列) を返す
+    fn reflow_primary(
+        &mut self,
+        w: usize,
+        h: usize,
+        cursor: Option<(usize, usize)>,
+    ) -> Option<(usize, usize)> {
+        let grid = if self.is_alternate_screen {
+            &mut self.inactive_grid
+        } else {
+            &mut self.grid
+        };
+        let history = self.scrollback.take_history();
+        let history_len = history.len();
+        let cursor = cursor.map(|(y, x)| (history_len + y, x));
+        let reflowed = ReflowedLines::new(history.into_iter().chain(grid.take_lines()), w, cursor);
+
+        let mut lines = reflowed.lines;
+        // カーソルが画面内に残るよう、末尾から画面の高さ分を画面に割り当てる
+        let screen_top = lines.len().saturating_sub(h);
+        let screen = lines.split_off(screen_top);
+        for line in lines {
+            self.scrollback.push(line);
+        }
+        *grid = TerminalGridEntity::from_lines(screen, w, h);
+        reflowed.cursor.map(|(y, x)| {
+            (
+                y.saturating_sub(screen_top).min(h.saturating_sub(1)),
+                x.min(w.saturating_sub(1)),
+            )
+        })
+    }
+}
@@ -0,0 +1,54 @@
+use super::TerminalBufferEntity;
+use crate::domain::model::semantic_prompt_value::{PromptMark, PromptMarkKind};
+use crate::domain::model::terminal_grid_entity::TerminalGridEntity;
+use crate::domain::model::terminal_line_entity::TerminalLine;
+
+/// OSC 133 のプロンプトのマークの記録と、コマンド履歴の対象となる行の列挙
+impl TerminalBufferEntity {
+    /// カーソル位置の行に OSC 133 のマークを記録する。
+    /// 代替画面はコマンド履歴の対象外のため記録しない。
+    pub fn add_prompt_mark(&mut self, kind: PromptMarkKind) {
+        if self.is_alternate_screen {
+            return;
+        }
+        let column = self.cursor.x;
+        if let Some(line) = self.grid.lines_mut().get_mut(self.cursor.y) {
+            line.add_prompt_mark(PromptMark {
+                kind,
+                column,
+                timestamp: std::time::Instant::now(),
+            });
+        }
+    }
+
+    /// 主画面のスクロールバックとグリッドを古い順に列挙する。
+    /// 添字はスクロールバック先頭を 0 とする通し番号になる。
+    pub fn primary_lines(&self) -> impl Iterator<Item = &TerminalLine> {
+        self.scrollback
+            .history()
+            .iter()
+            .chain(self.primary_grid().lines().iter())
+    }
+
+    /// `primary_lines` の通し番号で主画面の行を取得する
+    pub fn get_primary_line(&self, row: usize) -> Option<&TerminalLine> {
+        let history = self.scrollback.history();
+        match row.checked_sub(history.len()) {
+            Some(row) => self.primary_grid().lines().get(row),
+            None => history.get(row),
+        }
+    }
+
+    /// `primary_lines` の行数
+    pub fn get_primary_line_count(&self) -> usize {
+        self.scrollback.history().len() + self.primary_grid().lines().len()
+    }
+
+    fn primary_grid(&self) -> &TerminalGridEntity {
+        if self.is_alternate_screen {
+            &self.inactive_grid
+        } else {
+            &self.grid
+        }
+    }
+}
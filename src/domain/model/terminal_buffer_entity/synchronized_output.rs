@@ -0,0 +1,22 @@
+use super::{SynchronizedFrameEvents, TerminalBufferEntity};
+
+/// 同期出力 (DECSET 2026) の状態と、フレームの区切りの記録
+impl TerminalBufferEntity {
+    pub fn is_synchronized_output_enabled(&self) -> bool {
+        self.is_synchronized_output
+    }
+
+    pub fn set_synchronized_output(&mut self, enabled: bool) {
+        if enabled {
+            self.synchronized_frame_events.is_started = true;
+        } else if self.is_synchronized_output {
+            self.synchronized_frame_events.is_ended = true;
+        }
+        self.is_synchronized_output = enabled;
+    }
+
+    /// 前回の呼び出し以降に発生した同期出力のフレームの区切りを取り出す
+    pub fn take_synchronized_frame_events(&mut self) -> SynchronizedFrameEvents {
+        std::mem::take(&mut self.synchronized_frame_events)
+    }
+}
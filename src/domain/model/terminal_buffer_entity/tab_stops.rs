@@ -0,0 +1,34 @@
+use super::TerminalBufferEntity;
+
+/// タブ停止位置の設定と移動 (HT/CHT/CBT/HTS/TBC)
+impl TerminalBufferEntity {
+    /// HT / CHT: 次のタブ停止位置へ n 回移動する。セルの内容は変更しない。
+    pub fn forward_tab(&mut self, n: usize) {
+        self.is_pending_wrap = false;
+        for _ in 0..n {
+            self.cursor.x = self.tab_stops.next(self.cursor.x);
+        }
+    }
+
+    /// CBT: 前のタブ停止位置へ n 回移動する
+    pub fn backward_tab(&mut self, n: usize) {
+        self.is_pending_wrap = false;
+        for _ in 0..n {
+            self.cursor.x = self.tab_stops.previous(self.cursor.x);
+        }
+    }
+
+    /// HTS: カーソル位置にタブ停止位置を設定する
+    pub fn set_tab_stop(&mut self) {
+        self.tab_stops.set(self.cursor.x);
+    }
+
+    /// TBC: 0 はカーソル位置、3 はすべてのタブ停止位置を解除する
+    pub fn clear_tab_stop(&mut self, mode: u16) {
+        match mode {
+            0 => self.tab_stops.clear(self.cursor.x),
+            3 => self.tab_stops.clear_all(),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use super::TerminalBufferEntity;
+use crate::domain::model::terminal_title_value::{TerminalTitle, TitleTarget};
+
+/// XTWINOPS のタイトルスタックに積める最大数 (xterm と同じ)
+const TITLE_STACK_LIMIT: usize = 10;
+
+/// OSC 0/1/2 のタイトルと XTWINOPS のタイトルスタック
+impl TerminalBufferEntity {
+    pub fn get_title(&self) -> &TerminalTitle {
+        &self.title
+    }
+
+    pub fn set_icon_name(&mut self, name: String) {
+        self.title.icon_name = name;
+    }
+
+    pub fn set_window_title(&mut self, title: String) {
+        self.title.window_title = title;
+    }
+
+    /// 現在のタイトルをスタックに退避する (CSI 22 t)
+    /// 退避は常にアイコン名とウィンドウタイトルの両方を対象とし、復元時に対象を選択する。
+    pub fn push_title(&mut self) {
+        if self.title_stack.len() >= TITLE_STACK_LIMIT {
+            self.title_stack.remove(0);
+        }
+        self.title_stack.push(self.title.clone());
+    }
+
+    /// スタックからタイトルを復元する (CSI 23 t)
+    pub fn pop_title(&mut self, target: TitleTarget) {
+        if let Some(entry) = self.title_stack.pop() {
+            if target.includes_icon_name() {
+                self.title.icon_name = entry.icon_name;
+            }
+            if target.includes_window_title() {
+                self.title.window_title = entry.window_title;
+            }
+        }
+    }
+}
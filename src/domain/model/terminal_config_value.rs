@@ -51,6 +51,50 @@ impl ThemeType {
     }
 }
 
+/// OSC 52 によるクリップボードアクセスの許可ポリシー
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClipboardPolicy {
+    /// 読み書きとも拒否する
+    Disabled,
+    /// 書き込みのみ許可する（読み取りは拒否）
+    #[default]
+    WriteOnly,
+    /// 読み書きとも許可する
+    ReadWrite,
+}
+
+impl ClipboardPolicy {
+    const POLICIES: &[(Self, i32)] = &[
+        (Self::Disabled, 0),
+        (Self::WriteOnly, 1),
+        (Self::ReadWrite, 2),
+    ];
+
+    pub fn from_index(index: i32) -> Self {
+        Self::POLICIES
+            .iter()
+            .find(|(_, i)| *i == index)
+            .map(|(p, _)| *p)
+            .unwrap_or_default()
+    }
+
+    pub fn to_index(self) -> i32 {
+        Self::POLICIES
+            .iter()
+            .find(|(p, _)| *p == self)
+            .map(|(_, i)| *i)
+            .expect("ClipboardPolicy is missing from ClipboardPolicy::POLICIES mapping")
+    }
+
+    pub fn allows_write(self) -> bool {
+        matches!(self, Self::WriteOnly | Self::ReadWrite)
+    }
+
+    pub fn allows_read(self) -> bool {
+        self == Self::ReadWrite
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TerminalConfig {
     pub theme_type: ThemeType,
@@ -59,6 +103,11 @@ pub struct TerminalConfig {
     pub font_weight: i32,
     pub font_italic: bool,
     pub shell_path: String,
+    pub clipboard_policy: ClipboardPolicy,
+    /// ブラケットペーストが無効な状態で複数行を貼り付ける際に確認する
+    pub confirm_multiline_paste: bool,
+    /// 代替スクロール (DECSET 1007) でホイール 1 ノッチあたりに送るカーソルキーの数
+    pub alternate_scroll_lines: i32,
 }
 
 impl Default for TerminalConfig {
@@ -77,11 +126,17 @@ impl Default for TerminalConfig {
             font_weight: 400, // FW_NORMAL
             font_italic: false,
             shell_path,
+            clipboard_policy: ClipboardPolicy::default(),
+            confirm_multiline_paste: true,
+            alternate_scroll_lines: 3,
         }
     }
 }
 
 impl TerminalConfig {
+    /// `alternate_scroll_lines` の上限。1 ノッチで画面を大きく越える量のキーを送らないようにする。
+    pub const MAX_ALTERNATE_SCROLL_LINES: i32 = 100;
+
     pub fn get_color_theme(&self, is_dark: bool) -> ColorTheme {
         match self.theme_type {
             ThemeType::SystemDefault => {
@@ -185,4 +240,19 @@ mod tests {
             assert_eq!(ThemeType::from_index(idx_i32), *theme);
         }
     }
+
+    #[test]
+    fn test_clipboard_policy_mapping() {
+        assert_eq!(ClipboardPolicy::default(), ClipboardPolicy::WriteOnly);
+        assert_eq!(ClipboardPolicy::from_index(0), ClipboardPolicy::Disabled);
+        assert_eq!(ClipboardPolicy::from_index(2), ClipboardPolicy::ReadWrite);
+        assert_eq!(ClipboardPolicy::from_index(99), ClipboardPolicy::WriteOnly);
+        assert_eq!(ClipboardPolicy::ReadWrite.to_index(), 2);
+
+        assert!(!ClipboardPolicy::Disabled.allows_write());
+        assert!(!ClipboardPolicy::Disabled.allows_read());
+        assert!(ClipboardPolicy::WriteOnly.allows_write());
+        assert!(!ClipboardPolicy::WriteOnly.allows_read());
+        assert!(ClipboardPolicy::ReadWrite.allows_read());
+    }
 }
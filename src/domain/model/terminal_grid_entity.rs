@@ -1,9 +1,10 @@
+use super::terminal_line_entity::TerminalLine;
 use super::terminal_types_entity::{Cell, TerminalAttribute, TerminalColor};
 use std::collections::VecDeque;
 use unicode_width::UnicodeWidthStr;
 
 pub struct TerminalGridEntity {
-    lines: VecDeque<Vec<Cell>>,
+    lines: VecDeque<TerminalLine>,
     width: usize,
     height: usize,
 }
@@ -12,7 +13,7 @@ impl TerminalGridEntity {
     pub fn new(width: usize, height: usize) -> Self {
         let mut lines = VecDeque::with_capacity(height);
         for _ in 0..height {
-            lines.push_back(vec![Cell::default(); width]);
+            lines.push_back(TerminalLine::new(width));
         }
         Self {
             lines,
@@ -21,10 +22,35 @@ impl TerminalGridEntity {
         }
     }
 
-    pub fn lines(&self) -> &VecDeque<Vec<Cell>> {
+    /// 既存の行から生成する。行数が足りない場合は下に空行を補う。
+    pub fn from_lines(
+        lines: impl IntoIterator<Item = TerminalLine>,
+        width: usize,
+        height: usize,
+    ) -> Self {
+        let mut lines: VecDeque<TerminalLine> = lines.into_iter().take(height).collect();
+        while lines.len() < height {
+            lines.push_back(TerminalLine::new(width));
+        }
+        Self {
+            lines,
+            width,
+            height,
+        }
+    }
+
+    /// すべての行を取り出し、空の画面にする
+    pub fn take_lines(&mut self) -> VecDeque<TerminalLine> {
+        let empty = (0..self.height)
+            .map(|_| TerminalLine::new(self.width))
+            .collect();
+        std::mem::replace(&mut self.lines, empty)
+    }
+
+    pub fn lines(&self) -> &VecDeque<TerminalLine> {
         &self.lines
     }
-    pub fn lines_mut(&mut self) -> &mut VecDeque<Vec<Cell>> {
+    pub fn lines_mut(&mut self) -> &mut VecDeque<TerminalLine> {
         &mut self.lines
     }
 
@@ -40,6 +66,7 @@ impl TerminalGridEntity {
                 ..TerminalAttribute::default()
             },
             is_wide_continuation: false,
+            image: None,
         };
         if self.lines[y][x].is_wide_continuation {
             if x > 0 {
@@ -76,6 +103,7 @@ impl TerminalGridEntity {
                     text: " ".to_string(),
                     attribute: cell.attribute,
                     is_wide_continuation: true,
+                    image: None,
                 };
             }
         }
@@ -87,7 +115,8 @@ impl TerminalGridEntity {
                 self.lines.remove(bottom);
             }
             if row < self.lines.len() {
-                self.lines.insert(row, empty_line.clone());
+                self.lines
+                    .insert(row, TerminalLine::from(empty_line.clone()));
             }
         }
     }
@@ -98,7 +127,8 @@ impl TerminalGridEntity {
                 self.lines.remove(row);
             }
             if bottom < self.lines.len() {
-                self.lines.insert(bottom, empty_line.clone());
+                self.lines
+                    .insert(bottom, TerminalLine::from(empty_line.clone()));
             }
         }
     }
@@ -118,7 +148,7 @@ impl TerminalGridEntity {
         }
         if new_height > self.height {
             for _ in 0..(new_height - self.height) {
-                self.lines.push_back(vec![Cell::default(); new_width]);
+                self.lines.push_back(TerminalLine::new(new_width));
             }
         } else {
             self.lines.truncate(new_height);
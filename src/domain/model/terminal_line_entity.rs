@@ -0,0 +1,64 @@
+use super::semantic_prompt_value::PromptMark;
+use super::terminal_types_entity::Cell;
+use std::ops::{Deref, DerefMut};
+
+/// グリッドおよびスクロールバックの 1 行
+/// セルの並びに加え、行単位のメタデータ（プロンプトマーク等）を保持する。
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalLine {
+    cells: Vec<Cell>,
+    /// OSC 133 で記録されたマーク（記録順）
+    prompt_marks: Vec<PromptMark>,
+    /// 自動折り返しによって次の行へ続いているか
+    is_wrapped: bool,
+}
+
+impl TerminalLine {
+    pub fn new(width: usize) -> Self {
+        Self::from(vec![Cell::default(); width])
+    }
+
+    pub fn prompt_marks(&self) -> &[PromptMark] {
+        &self.prompt_marks
+    }
+
+    pub fn add_prompt_mark(&mut self, mark: PromptMark) {
+        self.prompt_marks.push(mark);
+    }
+
+    pub fn clear_prompt_marks(&mut self) {
+        self.prompt_marks.clear();
+    }
+
+    pub fn is_wrapped(&self) -> bool {
+        self.is_wrapped
+    }
+
+    pub fn set_wrapped(&mut self, wrapped: bool) {
+        self.is_wrapped = wrapped;
+    }
+}
+
+impl From<Vec<Cell>> for TerminalLine {
+    fn from(cells: Vec<Cell>) -> Self {
+        Self {
+            cells,
+            prompt_marks: Vec::new(),
+            is_wrapped: false,
+        }
+    }
+}
+
+impl Deref for TerminalLine {
+    type Target = Vec<Cell>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.cells
+    }
+}
+
+impl DerefMut for TerminalLine {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.cells
+    }
+}
@@ -0,0 +1,221 @@
+use super::semantic_prompt_value::PromptMark;
+use super::terminal_line_entity::TerminalLine;
+use super::terminal_types_entity::Cell;
+
+/// 自動折り返しでつながった行を論理行として連結し、新しい幅で折り返し直した結果
+/// 行番号はスクロールバックと画面を通した通し番号で扱う。
+#[derive(Debug)]
+pub struct ReflowedLines {
+    pub lines: Vec<TerminalLine>,
+    /// 折り返し後のカーソル位置 (行, 列)
+    pub cursor: Option<(usize, usize)>,
+}
+
+impl ReflowedLines {
+    /// `lines` を幅 `new_width` で折り返し直す。
+    /// カーソル (行, 列) より後ろにある空行は取り除き、カーソルは論理行内の位置を保つ。
+    pub fn new(
+        lines: impl IntoIterator<Item = TerminalLine>,
+        new_width: usize,
+        cursor: Option<(usize, usize)>,
+    ) -> Self {
+        let new_width = new_width.max(1);
+        let mut lines: Vec<TerminalLine> = lines.into_iter().collect();
+        let last_content = lines.iter().rposition(|line| !is_blank_line(line));
+        let keep = match (last_content, cursor) {
+            (Some(content), Some((row, _))) => content.max(row) + 1,
+            (Some(content), None) => content + 1,
+            (None, Some((row, _))) => row + 1,
+            (None, None) => 0,
+        };
+        lines.truncate(keep);
+
+        let mut result = Self {
+            lines: Vec::with_capacity(lines.len()),
+            cursor: None,
+        };
+        let mut logical = LogicalLine::default();
+        for (row, line) in lines.into_iter().enumerate() {
+            let base = logical.cells.len();
+            if let Some((cursor_row, cursor_col)) = cursor
+                && cursor_row == row
+            {
+                logical.cursor_offset = Some(base + cursor_col);
+            }
+            let is_wrapped = line.is_wrapped();
+            logical.marks.extend(
+                line.prompt_marks()
+                    .iter()
+                    .map(|mark| (base + mark.column, *mark)),
+            );
+            logical.cells.extend(line.iter().cloned());
+            if !is_wrapped {
+                result.push_logical_line(std::mem::take(&mut logical), new_width);
+            }
+        }
+        if !logical.cells.is_empty() || logical.cursor_offset.is_some() {
+            result.push_logical_line(logical, new_width);
+        }
+        result
+    }
+
+    /// 論理行を新しい幅の行に分割して追加する
+    fn push_logical_line(&mut self, mut logical: LogicalLine, width: usize) {
+        while logical
+            .cells
+            .last()
+            .is_some_and(|cell| *cell == Cell::default())
+        {
+            logical.cells.pop();
+        }
+
+        let first_row = self.lines.len();
+        let mut rows: Vec<Vec<Cell>> = Vec::new();
+        let mut current: Vec<Cell> = Vec::with_capacity(width);
+        // 論理行内の各セルが配置された (行, 列)
+        let mut positions = Vec::with_capacity(logical.cells.len());
+        let cell_count = logical.cells.len();
+        let mut cells = logical.cells.into_iter().peekable();
+        while let Some(cell) = cells.next() {
+            let is_wide = cells.peek().is_some_and(|next| next.is_wide_continuation);
+            // 全角文字を行末で分断しないよう、収まらない場合は空白で埋めて次の行へ送る
+            let needed = if is_wide && width >= 2 { 2 } else { 1 };
+            if current.len() + needed > width && !cell.is_wide_continuation {
+                current.resize(width, Cell::default());
+                rows.push(std::mem::take(&mut current));
+            }
+            positions.push((rows.len(), current.len()));
+            current.push(cell);
+        }
+
+        let end = (rows.len(), current.len());
+        let position_of = |offset: usize| -> (usize, usize) {
+            match positions.get(offset) {
+                Some(&pos) => pos,
+                None => {
+                    let col = end.1 + (offset - cell_count);
+                    (end.0 + col / width, col % width)
+                }
+            }
+        };
+
+        if let Some(offset) = logical.cursor_offset {
+            let (row, col) = position_of(offset);
+            // 内容より後ろにあるカーソル位置まで行を確保する
+            while rows.len() < row {
+                current.resize(width, Cell::default());
+                rows.push(std::mem::take(&mut current));
+            }
+            self.cursor = Some((first_row + row, col));
+        }
+        current.resize(width, Cell::default());
+        rows.push(current);
+
+        let row_count = rows.len();
+        for (i, cells) in rows.into_iter().enumerate() {
+            let mut line = TerminalLine::from(cells);
+            line.set_wrapped(i + 1 < row_count);
+            self.lines.push(line);
+        }
+        for (offset, mut mark) in logical.marks {
+            let (row, col) = position_of(offset);
+            let row = row.min(row_count - 1);
+            mark.column = col;
+            self.lines[first_row + row].add_prompt_mark(mark);
+        }
+    }
+}
+
+/// 折り返し前の論理行
+#[derive(Default)]
+struct LogicalLine {
+    cells: Vec<Cell>,
+    /// 論理行の先頭からのセル数で表したプロンプトマークの位置
+    marks: Vec<(usize, PromptMark)>,
+    /// 論理行の先頭からのセル数で表したカーソル位置
+    cursor_offset: Option<usize>,
+}
+
+fn is_blank_line(line: &TerminalLine) -> bool {
+    !line.is_wrapped()
+        && line.prompt_marks().is_empty()
+        && line.iter().all(|cell| *cell == Cell::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str, width: usize, is_wrapped: bool) -> TerminalLine {
+        let mut cells = vec![Cell::default(); width];
+        for (cell, c) in cells.iter_mut().zip(text.chars()) {
+            cell.text = c.to_string();
+        }
+        let mut line = TerminalLine::from(cells);
+        line.set_wrapped(is_wrapped);
+        line
+    }
+
+    fn texts(reflowed: &ReflowedLines) -> Vec<String> {
+        reflowed
+            .lines
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .filter(|c| !c.is_wide_continuation)
+                    .map(|c| c.text.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_narrow_rewraps_long_line() {
+        let lines = vec![line("abcdefgh", 8, false), line("xy", 8, false)];
+        let reflowed = ReflowedLines::new(lines, 3, Some((1, 2)));
+        assert_eq!(texts(&reflowed), vec!["abc", "def", "gh ", "xy "]);
+        assert!(reflowed.lines[0].is_wrapped());
+        assert!(reflowed.lines[1].is_wrapped());
+        assert!(!reflowed.lines[2].is_wrapped());
+        assert_eq!(reflowed.cursor, Some((3, 2)));
+    }
+
+    #[test]
+    fn test_widen_joins_soft_wrapped_rows() {
+        let lines = vec![
+            line("abc", 3, true),
+            line("def", 3, true),
+            line("g", 3, false),
+            line("", 3, false),
+        ];
+        // カーソルは "g" の直後
+        let reflowed = ReflowedLines::new(lines, 8, Some((2, 1)));
+        assert_eq!(texts(&reflowed), vec!["abcdefg "]);
+        assert!(!reflowed.lines[0].is_wrapped());
+        assert_eq!(reflowed.cursor, Some((0, 7)));
+    }
+
+    #[test]
+    fn test_trailing_blank_rows_are_dropped() {
+        let lines = vec![line("$ ", 4, false), line("", 4, false), line("", 4, false)];
+        let reflowed = ReflowedLines::new(lines.clone(), 4, Some((0, 2)));
+        assert_eq!(texts(&reflowed), vec!["$   "]);
+        assert_eq!(reflowed.cursor, Some((0, 2)));
+
+        // カーソルより上の空行は保持する
+        let reflowed = ReflowedLines::new(lines, 4, Some((2, 0)));
+        assert_eq!(reflowed.lines.len(), 3);
+        assert_eq!(reflowed.cursor, Some((2, 0)));
+    }
+
+    #[test]
+    fn test_wide_char_is_not_split() {
+        let mut cells = vec![Cell::default(); 4];
+        cells[0].text = "a".to_string();
+        cells[1].text = "あ".to_string();
+        cells[2].is_wide_continuation = true;
+        let reflowed = ReflowedLines::new(vec![TerminalLine::from(cells)], 2, None);
+        assert_eq!(texts(&reflowed), vec!["a ", "あ"]);
+        assert!(reflowed.lines[1][1].is_wide_continuation);
+    }
+}
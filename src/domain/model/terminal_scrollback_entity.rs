@@ -1,8 +1,8 @@
-use super::terminal_types_entity::Cell;
+use super::terminal_line_entity::TerminalLine;
 use std::collections::VecDeque;
 
 pub struct TerminalScrollbackEntity {
-    history: VecDeque<Vec<Cell>>,
+    history: VecDeque<TerminalLine>,
     viewport_offset: usize,
     scrollback_limit: usize,
 }
@@ -16,14 +16,17 @@ impl TerminalScrollbackEntity {
         }
     }
 
-    pub fn history(&self) -> &VecDeque<Vec<Cell>> {
+    pub fn history(&self) -> &VecDeque<TerminalLine> {
         &self.history
     }
+    pub fn history_mut(&mut self) -> &mut VecDeque<TerminalLine> {
+        &mut self.history
+    }
     pub fn viewport_offset(&self) -> usize {
         self.viewport_offset
     }
 
-    pub fn push(&mut self, line: Vec<Cell>) {
+    pub fn push(&mut self, line: TerminalLine) {
         if self.scrollback_limit == 0 {
             return;
         }
@@ -36,6 +39,12 @@ impl TerminalScrollbackEntity {
         }
     }
 
+    /// 履歴をすべて取り出す（リフローで並べ直すため）
+    pub fn take_history(&mut self) -> VecDeque<TerminalLine> {
+        self.viewport_offset = 0;
+        std::mem::take(&mut self.history)
+    }
+
     pub fn scroll_to(&mut self, offset: usize) {
         self.viewport_offset = offset.min(self.history.len());
     }
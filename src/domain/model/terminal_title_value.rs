@@ -0,0 +1,33 @@
+/// OSC 0/1/2 で設定されるアイコン名とウィンドウタイトル
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TerminalTitle {
+    pub icon_name: String,
+    pub window_title: String,
+}
+
+/// XTWINOPS (CSI 22/23 t) の操作対象
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleTarget {
+    Both,
+    IconName,
+    WindowTitle,
+}
+
+impl TitleTarget {
+    /// XTWINOPS の第2パラメータから操作対象を決定する
+    pub fn from_param(param: u16) -> Self {
+        match param {
+            1 => Self::IconName,
+            2 => Self::WindowTitle,
+            _ => Self::Both,
+        }
+    }
+
+    pub fn includes_icon_name(self) -> bool {
+        matches!(self, Self::Both | Self::IconName)
+    }
+
+    pub fn includes_window_title(self) -> bool {
+        matches!(self, Self::Both | Self::WindowTitle)
+    }
+}
@@ -1,3 +1,6 @@
+use super::hyperlink_registry_entity::HyperlinkId;
+use super::sixel_image_value::ImageFragment;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TerminalColor {
     Default,
@@ -6,6 +9,34 @@ pub enum TerminalColor {
     Rgb(u8, u8, u8),
 }
 
+/// SGR 4 / 4:n / 21 で指定される下線の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnderlineStyle {
+    #[default]
+    None,
+    Single,
+    Double,
+    /// 波線 (スペルチェックや LSP の診断表示で使用される)
+    Curly,
+    Dotted,
+    Dashed,
+}
+
+impl UnderlineStyle {
+    /// SGR 4:n のサブパラメータから下線の種類を判定する
+    pub fn from_subparam(n: u16) -> Option<Self> {
+        match n {
+            0 => Some(Self::None),
+            1 => Some(Self::Single),
+            2 => Some(Self::Double),
+            3 => Some(Self::Curly),
+            4 => Some(Self::Dotted),
+            5 => Some(Self::Dashed),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct TerminalAttribute {
     pub fg: TerminalColor,
@@ -13,9 +44,17 @@ pub struct TerminalAttribute {
     pub is_bold: bool,
     pub is_dim: bool,
     pub is_italic: bool,
-    pub is_underline: bool,
+    pub underline: UnderlineStyle,
+    /// SGR 58 で指定された下線色。Default の場合は前景色で描画する
+    pub underline_color: TerminalColor,
+    pub is_blink: bool,
     pub is_inverse: bool,
+    /// SGR 8: 文字を背景色で描画して隠す
+    pub is_concealed: bool,
     pub is_strikethrough: bool,
+    pub is_overline: bool,
+    /// OSC 8 で設定されたハイパーリンク。SGR のリセットでは解除されない
+    pub hyperlink_id: Option<HyperlinkId>,
 }
 
 impl Default for TerminalAttribute {
@@ -26,9 +65,14 @@ impl Default for TerminalAttribute {
             is_bold: false,
             is_dim: false,
             is_italic: false,
-            is_underline: false,
+            underline: UnderlineStyle::None,
+            underline_color: TerminalColor::Default,
+            is_blink: false,
             is_inverse: false,
+            is_concealed: false,
             is_strikethrough: false,
+            is_overline: false,
+            hyperlink_id: None,
         }
     }
 }
@@ -38,6 +82,8 @@ pub struct Cell {
     pub text: String,
     pub attribute: TerminalAttribute,
     pub is_wide_continuation: bool,
+    /// Sixel で配置された画像のうち、このセルに表示する区画
+    pub image: Option<ImageFragment>,
 }
 
 impl Default for Cell {
@@ -46,6 +92,7 @@ impl Default for Cell {
             text: " ".to_string(),
             attribute: TerminalAttribute::default(),
             is_wide_continuation: false,
+            image: None,
         }
     }
 }
@@ -65,11 +112,32 @@ pub enum CursorStyle {
 pub enum MouseTrackingMode {
     #[default]
     None,
+    X10,         // 9: ボタンの押し下げのみを修飾キー無しで送信
     Default,     // 1000: ボタンの押し下げ/離上を送信
     ButtonEvent, // 1002: ボタンの押し下げ/離上 + ドラッグを送信
     AnyEvent,    // 1003: 全ての移動イベントを送信
 }
 
+/// マウスイベントの送信形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MouseEncoding {
+    /// `CSI M Cb Cx Cy`。各値に 32 を加えた 1 バイトで送るため、座標は 223 までしか表せない
+    #[default]
+    Default,
+    Utf8,  // 1005: Default の各値を UTF-8 で送信 (座標は 2015 まで)
+    Sgr,   // 1006: `CSI < Pb ; Px ; Py M/m`
+    Urxvt, // 1015: `CSI Cb ; Px ; Py M`
+}
+
+/// 前回取り出してから発生した同期出力 (DECSET 2026) のフレームの区切り
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SynchronizedFrameEvents {
+    /// `CSI ? 2026 h` を受け取った (フレームの開始)
+    pub is_started: bool,
+    /// 同期出力中に `CSI ? 2026 l` を受け取った (フレームの完了)
+    pub is_ended: bool,
+}
+
 pub struct Cursor {
     pub x: usize,
     pub y: usize,
@@ -87,3 +155,12 @@ impl Default for Cursor {
         }
     }
 }
+
+/// DECSC で保存するカーソルの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SavedCursor {
+    pub x: usize,
+    pub y: usize,
+    /// 保存時に折り返し待ちだったか (xterm の DECSC は "last column flag" も保存する)
+    pub is_pending_wrap: bool,
+}
@@ -0,0 +1,63 @@
+/// WM_MOUSEWHEEL の 1 ノッチあたりの回転量
+pub const WHEEL_DELTA: i32 = 120;
+
+/// ホイールの回転量をスクロール行数へ変換する。
+/// 高精度タッチパッド等は 1 ノッチに満たない回転量を細かく送るため、1 行に満たない端数を次の回転へ持ち越す。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WheelScroll {
+    /// 行数に換算しきれなかった回転量 (`WHEEL_DELTA` が 1 行)
+    remainder: i32,
+}
+
+impl WheelScroll {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 回転量 `delta` (正が上方向) を 1 ノッチ `lines_per_notch` 行として行数に変換する
+    pub fn take_lines(&mut self, delta: i16, lines_per_notch: i32) -> i32 {
+        // 逆方向に回した場合は持ち越した端数を捨てる
+        if self.remainder.signum() * (delta as i32).signum() < 0 {
+            self.remainder = 0;
+        }
+        let total = (delta as i32)
+            .saturating_mul(lines_per_notch)
+            .saturating_add(self.remainder);
+        self.remainder = total % WHEEL_DELTA;
+        total / WHEEL_DELTA
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whole_notches() {
+        let mut wheel = WheelScroll::new();
+        assert_eq!(wheel.take_lines(120, 3), 3);
+        assert_eq!(wheel.take_lines(-240, 3), -6);
+    }
+
+    #[test]
+    fn test_carries_remainder() {
+        let mut wheel = WheelScroll::new();
+        // 3 行/ノッチでは 40 ごとに 1 行
+        assert_eq!(wheel.take_lines(30, 3), 0);
+        assert_eq!(wheel.take_lines(30, 3), 1);
+        assert_eq!(wheel.take_lines(30, 3), 1);
+        assert_eq!(wheel.take_lines(30, 3), 1);
+        assert_eq!(wheel.take_lines(-30, 3), 0);
+        // 方向を変えた場合は逆方向の端数を持ち越さない
+        assert_eq!(wheel.take_lines(30, 3), 0);
+        assert_eq!(wheel.take_lines(10, 3), 1);
+    }
+
+    #[test]
+    fn test_large_lines_per_notch_saturates() {
+        let mut wheel = WheelScroll::new();
+        assert_eq!(wheel.take_lines(i16::MAX, i32::MAX), i32::MAX / WHEEL_DELTA);
+        let mut wheel = WheelScroll::new();
+        assert_eq!(wheel.take_lines(i16::MIN, i32::MAX), i32::MIN / WHEEL_DELTA);
+    }
+}
@@ -0,0 +1,175 @@
+use super::hyperlink_value::percent_decode;
+
+/// OSC 7 / OSC 9;9 で通知されたシェルのカレントディレクトリ
+/// Windows で解決できるパスは Windows 形式に正規化して保持する。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkingDirectory {
+    path: String,
+}
+
+impl WorkingDirectory {
+    /// OSC 7 の `file://host/path` 形式の URI から生成する
+    pub fn from_file_uri(uri: &str) -> Option<Self> {
+        let rest = uri.strip_prefix("file://")?;
+        // ホスト名はシェルが動作しているマシン名であり、パスの解決には使用しない
+        let path = &rest[rest.find('/')?..];
+        Self::from_path(&percent_decode(path))
+    }
+
+    /// OSC 9;9 等で通知されたパス文字列から生成する
+    pub fn from_path(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        let raw = raw
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .unwrap_or(raw);
+        if raw.is_empty() {
+            return None;
+        }
+        Some(Self {
+            path: normalize_path(raw),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    /// Windows で解決できるパス（ドライブまたは UNC）であるかを判定する
+    pub fn is_windows_path(&self) -> bool {
+        match self.path.as_bytes() {
+            [drive, b':', ..] => drive.is_ascii_alphabetic(),
+            [b'\\', b'\\', ..] => true,
+            _ => false,
+        }
+    }
+
+    /// このディレクトリを基準に相対パスを解決する。
+    /// Windows で解決できないディレクトリ（WSL 内部等）の場合は None を返す。
+    pub fn join(&self, relative: &str) -> Option<String> {
+        if !self.is_windows_path() {
+            return None;
+        }
+        let relative = relative.replace('/', "\\");
+        let relative = relative.trim_start_matches(".\\");
+        if self.path.ends_with('\\') {
+            Some(format!("{}{}", self.path, relative))
+        } else {
+            Some(format!("{}\\{}", self.path, relative))
+        }
+    }
+}
+
+/// パスを Windows 形式に変換する。
+/// Windows で解決できない POSIX パス（WSL 内部やリモートホスト）はそのまま返す。
+fn normalize_path(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let windows_path = match bytes {
+        // /C:/Users -> C:/Users
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => raw[1..].to_string(),
+        [drive, b':', ..] if drive.is_ascii_alphabetic() => raw.to_string(),
+        // /mnt/c/Users -> C:/Users (WSL のドライブマウント)
+        [b'/', b'm', b'n', b't', b'/', drive, rest @ ..]
+            if drive.is_ascii_alphabetic() && (rest.is_empty() || rest[0] == b'/') =>
+        {
+            format!(
+                "{}:{}",
+                drive.to_ascii_uppercase() as char,
+                std::str::from_utf8(rest).unwrap_or("")
+            )
+        }
+        // //server/share や \\server\share は UNC パス
+        [b'/' | b'\\', b'/' | b'\\', ..] => raw.to_string(),
+        _ => return raw.to_string(),
+    };
+
+    let mut path = windows_path.replace('/', "\\");
+    // ルート以外の末尾の区切り文字を取り除き、ドライブ直下は "C:\" に揃える
+    while path.len() > 3 && path.ends_with('\\') {
+        path.pop();
+    }
+    if path.len() == 2 && path.ends_with(':') {
+        path.push('\\');
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_uri(uri: &str) -> Option<String> {
+        WorkingDirectory::from_file_uri(uri).map(|d| d.as_str().to_string())
+    }
+
+    fn from_path(path: &str) -> Option<String> {
+        WorkingDirectory::from_path(path).map(|d| d.as_str().to_string())
+    }
+
+    #[test]
+    fn test_from_file_uri() {
+        assert_eq!(
+            from_uri("file://DESKTOP-01/C:/Users/me/My%20Docs/").as_deref(),
+            Some(r"C:\Users\me\My Docs")
+        );
+        assert_eq!(from_uri("file:///c:/work").as_deref(), Some(r"c:\work"));
+        assert_eq!(from_uri("file://localhost/D:/").as_deref(), Some(r"D:\"));
+        assert_eq!(
+            from_uri("file:///C:/%E4%BD%9C%E6%A5%AD").as_deref(),
+            Some(r"C:\作業")
+        );
+        assert_eq!(from_uri("https://example.com/"), None);
+        assert_eq!(from_uri("file://host-only"), None);
+    }
+
+    #[test]
+    fn test_wsl_paths() {
+        assert_eq!(
+            from_uri("file://ubuntu/mnt/c/Users/me").as_deref(),
+            Some(r"C:\Users\me")
+        );
+        assert_eq!(from_path("/mnt/d").as_deref(), Some(r"D:\"));
+        assert_eq!(from_path("/mnt/d/").as_deref(), Some(r"D:\"));
+        // /mnt 以下でもドライブ名でないものは変換しない
+        assert_eq!(from_path("/mnt/wsl/x").as_deref(), Some("/mnt/wsl/x"));
+        // WSL 内部のパスは Windows で解決できないためそのまま保持する
+        assert_eq!(
+            from_uri("file://ubuntu/home/me/src").as_deref(),
+            Some("/home/me/src")
+        );
+    }
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(
+            from_path(r#""C:\Program Files\""#).as_deref(),
+            Some(r"C:\Program Files")
+        );
+        assert_eq!(from_path("C:/tmp").as_deref(), Some(r"C:\tmp"));
+        assert_eq!(
+            from_path(r"\\wsl.localhost\Ubuntu\home").as_deref(),
+            Some(r"\\wsl.localhost\Ubuntu\home")
+        );
+        assert_eq!(
+            from_path("//server/share/dir/").as_deref(),
+            Some(r"\\server\share\dir")
+        );
+        assert_eq!(from_path(""), None);
+        assert_eq!(from_path(r#""""#), None);
+    }
+
+    #[test]
+    fn test_join() {
+        let join = |dir: &str, rel: &str| WorkingDirectory::from_path(dir).unwrap().join(rel);
+        assert_eq!(
+            join(r"C:\work", "src/a.rs").as_deref(),
+            Some(r"C:\work\src\a.rs")
+        );
+        assert_eq!(join("D:/", "./a.rs").as_deref(), Some(r"D:\a.rs"));
+        assert_eq!(
+            join(r"\\server\share", "a.rs").as_deref(),
+            Some(r"\\server\share\a.rs")
+        );
+        assert_eq!(join("/home/me", "a.rs"), None);
+    }
+}
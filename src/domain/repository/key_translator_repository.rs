@@ -1,10 +1,11 @@
-pub use crate::domain::model::input_value::{InputKey, MouseEvent};
+pub use crate::domain::model::input_value::{InputKey, InputModes, MouseEvent};
+pub use crate::domain::model::terminal_types_entity::MouseEncoding;
 
 /// キーボードおよびマウス入力の翻訳を行うトレイト
 pub trait KeyTranslatorRepository: Send + Sync {
-    /// キーイベントを、ターミナルに送信するバイトシーケンスに翻訳する
-    fn translate(&self, key: InputKey) -> Option<Vec<u8>>;
+    /// キーイベントを、現在の入力モードに従ってターミナルに送信するバイトシーケンスに翻訳する
+    fn translate(&self, key: InputKey, modes: InputModes) -> Option<Vec<u8>>;
 
-    /// マウスイベントを、ターミナルに送信するバイトシーケンスに翻訳する
-    fn translate_mouse(&self, event: MouseEvent) -> Option<Vec<u8>>;
+    /// マウスイベントを、指定した送信形式のバイトシーケンスに翻訳する
+    fn translate_mouse(&self, event: MouseEvent, encoding: MouseEncoding) -> Option<Vec<u8>>;
 }
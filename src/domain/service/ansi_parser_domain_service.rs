@@ -1,3 +1,4 @@
+use super::sixel_decoder_domain_service::SixelDecoderDomainService;
 use super::terminal_protocol_handler::TerminalProtocolHandler;
 use crate::domain::model::terminal_buffer_entity::TerminalBufferEntity;
 use vte::Parser;
@@ -5,31 +6,45 @@ use vte::Parser;
 /// ANSI エスケープシーケンスのパースを担うドメインサービス
 pub(crate) struct AnsiParserDomainService {
     parser: Parser,
+    /// DCS q で受信中の Sixel データの復号状態
+    sixel_decoder: Option<SixelDecoderDomainService>,
 }
 
 impl AnsiParserDomainService {
     pub(crate) fn new() -> Self {
         Self {
             parser: Parser::new(),
+            sixel_decoder: None,
         }
     }
 
-    pub(crate) fn parse(&mut self, bytes: &[u8], buffer: &mut TerminalBufferEntity) {
-        {
+    /// 出力を解釈してバッファに反映する。
+    /// 問い合わせシーケンスに対してアプリケーションへ返すべき応答を、受信順に返す。
+    pub(crate) fn parse(
+        &mut self,
+        bytes: &[u8],
+        buffer: &mut TerminalBufferEntity,
+    ) -> Vec<Vec<u8>> {
+        let replies = {
             // Protocol Handler を介してバッファを操作する
-            let mut handler = TerminalProtocolHandler::new(buffer);
+            let mut handler = TerminalProtocolHandler::new(buffer, &mut self.sixel_decoder);
             // vte 0.15 の advance は &[u8] を受け取るため、入力全体をまとめて渡す
             self.parser.advance(&mut handler, bytes);
-        }
+            handler.take_replies()
+        };
         // 各データ受信パケットの処理後に強制的にフラッシュを行い、表示遅延を解消する
         buffer.flush_pending_cluster();
+        replies
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::model::terminal_buffer_entity::{Cell, TerminalBufferEntity, TerminalColor};
+    use crate::domain::model::input_value::InputModes;
+    use crate::domain::model::terminal_buffer_entity::{
+        Cell, MouseEncoding, MouseTrackingMode, TerminalBufferEntity, TerminalColor,
+    };
 
     fn line_to_string(line: &[Cell]) -> String {
         line.iter()
@@ -98,7 +113,6 @@ mod tests {
 
     #[test]
     fn test_mouse_mode_parsing() {
-        use crate::domain::model::terminal_types_entity::MouseTrackingMode;
         let mut buffer = TerminalBufferEntity::new(80, 25);
         let mut parser = AnsiParserDomainService::new();
 
@@ -125,11 +139,870 @@ mod tests {
         assert_eq!(buffer.get_mouse_tracking_mode(), MouseTrackingMode::None);
 
         // SGR Encoding (1006)
-        assert!(!buffer.is_sgr_mouse_encoding_enabled());
+        assert_eq!(buffer.get_mouse_encoding(), MouseEncoding::Default);
         parser.parse(b"\x1b[?1006h", &mut buffer);
-        assert!(buffer.is_sgr_mouse_encoding_enabled());
+        assert_eq!(buffer.get_mouse_encoding(), MouseEncoding::Sgr);
 
         parser.parse(b"\x1b[?1006l", &mut buffer);
-        assert!(!buffer.is_sgr_mouse_encoding_enabled());
+        assert_eq!(buffer.get_mouse_encoding(), MouseEncoding::Default);
+    }
+
+    #[test]
+    fn test_legacy_mouse_modes() {
+        let mut buffer = TerminalBufferEntity::new(80, 25);
+        replies_for(&mut buffer, b"\x1b[?9h");
+        assert_eq!(buffer.get_mouse_tracking_mode(), MouseTrackingMode::X10);
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?9$p"), "\x1b[?9;1$y");
+        replies_for(&mut buffer, b"\x1b[?9l");
+        assert_eq!(buffer.get_mouse_tracking_mode(), MouseTrackingMode::None);
+
+        replies_for(&mut buffer, b"\x1b[?1005h");
+        assert_eq!(buffer.get_mouse_encoding(), MouseEncoding::Utf8);
+        // 後から有効にした形式が優先される
+        replies_for(&mut buffer, b"\x1b[?1015h");
+        assert_eq!(buffer.get_mouse_encoding(), MouseEncoding::Urxvt);
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?1005$p"), "\x1b[?1005;2$y");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?1015$p"), "\x1b[?1015;1$y");
+        // 有効でない形式の解除は無視する
+        replies_for(&mut buffer, b"\x1b[?1005l");
+        assert_eq!(buffer.get_mouse_encoding(), MouseEncoding::Urxvt);
+        replies_for(&mut buffer, b"\x1b[?1015l");
+        assert_eq!(buffer.get_mouse_encoding(), MouseEncoding::Default);
+    }
+
+    #[test]
+    fn test_alternate_screen_preserves_primary() {
+        let mut buffer = TerminalBufferEntity::new(10, 3);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(b"primary\x1b[2;3H", &mut buffer);
+
+        parser.parse(b"\x1b[?1049h", &mut buffer);
+        assert!(buffer.is_alternate_screen());
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(0).unwrap()),
+            "          "
+        );
+        parser.parse(b"\x1b[Halt", &mut buffer);
+        assert_eq!(buffer.get_line_at_visual_row(0).unwrap()[0].text, "a");
+
+        parser.parse(b"\x1b[?1049l", &mut buffer);
+        assert!(!buffer.is_alternate_screen());
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(0).unwrap()),
+            "primary   "
+        );
+        // 1049 の解除でカーソル位置が復元される
+        assert_eq!(buffer.get_cursor_pos(), (2, 1));
+    }
+
+    #[test]
+    fn test_alternate_screen_does_not_touch_scrollback() {
+        let mut buffer = TerminalBufferEntity::new(10, 3);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(b"1\n2\n3\n4", &mut buffer);
+        assert_eq!(buffer.get_history_len(), 1);
+
+        parser.parse(b"\x1b[?1049h", &mut buffer);
+        parser.parse(b"a\nb\nc\nd\ne\nf", &mut buffer);
+        assert_eq!(buffer.get_history_len(), 0);
+
+        parser.parse(b"\x1b[?1049l", &mut buffer);
+        assert_eq!(buffer.get_history_len(), 1);
+        assert_eq!(buffer.get_line_at_visual_row(2).unwrap()[0].text, "4");
+    }
+
+    #[test]
+    fn test_alternate_screen_1047_and_1048() {
+        let mut buffer = TerminalBufferEntity::new(10, 3);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(b"main\x1b[3;5H\x1b[?1048h", &mut buffer);
+
+        parser.parse(b"\x1b[?1047h\x1b[Halt", &mut buffer);
+        assert_eq!(buffer.get_line_at_visual_row(0).unwrap()[0].text, "a");
+
+        // 1047 の解除では代替画面を消去してから主画面へ戻る
+        parser.parse(b"\x1b[?1047l", &mut buffer);
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(0).unwrap()),
+            "main      "
+        );
+        parser.parse(b"\x1b[?47h", &mut buffer);
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(0).unwrap()),
+            "          "
+        );
+        parser.parse(b"\x1b[?47l\x1b[?1048l", &mut buffer);
+        assert_eq!(buffer.get_cursor_pos(), (4, 2));
+    }
+
+    #[test]
+    fn test_osc_window_title() {
+        let mut buffer = TerminalBufferEntity::new(80, 25);
+        let mut parser = AnsiParserDomainService::new();
+
+        parser.parse(b"\x1b]0;pwsh\x07", &mut buffer);
+        assert_eq!(buffer.get_title().icon_name, "pwsh");
+        assert_eq!(buffer.get_title().window_title, "pwsh");
+
+        parser.parse(b"\x1b]2;vim; main.rs\x1b\\", &mut buffer);
+        assert_eq!(buffer.get_title().window_title, "vim; main.rs");
+        assert_eq!(buffer.get_title().icon_name, "pwsh");
+
+        parser.parse("\x1b]1;アイコン\x07".as_bytes(), &mut buffer);
+        assert_eq!(buffer.get_title().icon_name, "アイコン");
+        assert_eq!(buffer.get_title().window_title, "vim; main.rs");
+    }
+
+    #[test]
+    fn test_title_stack() {
+        let mut buffer = TerminalBufferEntity::new(80, 25);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(b"\x1b]0;shell\x07", &mut buffer);
+
+        // 両方を退避して変更し、ウィンドウタイトルだけを復元する
+        parser.parse(b"\x1b[22;0t\x1b]0;less\x07", &mut buffer);
+        parser.parse(b"\x1b[23;2t", &mut buffer);
+        assert_eq!(buffer.get_title().window_title, "shell");
+        assert_eq!(buffer.get_title().icon_name, "less");
+
+        parser.parse(b"\x1b[22t\x1b]0;htop\x07\x1b[23t", &mut buffer);
+        assert_eq!(buffer.get_title().window_title, "shell");
+        assert_eq!(buffer.get_title().icon_name, "less");
+
+        // 空のスタックからの復元は無視される
+        parser.parse(b"\x1b[23t", &mut buffer);
+        assert_eq!(buffer.get_title().window_title, "shell");
+    }
+
+    #[test]
+    fn test_osc8_hyperlink() {
+        let mut buffer = TerminalBufferEntity::new(20, 3);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(
+            b"see \x1b]8;id=doc;https://example.com/a;b\x1b\\link\x1b[0m\x1b]8;;\x1b\\ end",
+            &mut buffer,
+        );
+
+        assert!(buffer.get_hyperlink_at(3, 0).is_none());
+        let link = buffer.get_hyperlink_at(4, 0).unwrap();
+        assert_eq!(link.uri, "https://example.com/a;b");
+        assert_eq!(link.id.as_deref(), Some("doc"));
+        // SGR 0 ではリンクは解除されない
+        assert!(buffer.get_hyperlink_at(7, 0).is_some());
+        assert!(buffer.get_hyperlink_at(8, 0).is_none());
+
+        // 同じリンクは同じ ID に正規化される
+        let line = buffer.get_line_at_visual_row(0).unwrap();
+        assert_eq!(
+            line[4].attribute.hyperlink_id,
+            line[7].attribute.hyperlink_id
+        );
+    }
+
+    #[test]
+    fn test_osc8_hyperlink_survives_scrollback() {
+        let mut buffer = TerminalBufferEntity::new(20, 2);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(
+            b"\x1b]8;;file:///C:/src/main.rs\x07main.rs\x1b]8;;\x07\r\n2\r\n3",
+            &mut buffer,
+        );
+        assert_eq!(buffer.get_history_len(), 1);
+
+        buffer.scroll_lines(1);
+        let link = buffer.get_hyperlink_at(0, 0).unwrap();
+        assert_eq!(
+            link.to_file_path(None, None).as_deref(),
+            Some(r"C:\src\main.rs")
+        );
+    }
+
+    #[test]
+    fn test_osc52_clipboard_requests() {
+        use crate::domain::model::clipboard_value::{ClipboardRequest, ClipboardSelection};
+
+        let mut buffer = TerminalBufferEntity::new(20, 3);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(
+            "\x1b]52;c;44GT44KT44Gr44Gh44Gv\x07\x1b]52;;?\x1b\\\x1b]52;c;!!\x07".as_bytes(),
+            &mut buffer,
+        );
+
+        assert_eq!(
+            buffer.take_clipboard_requests(),
+            vec![
+                ClipboardRequest::Write {
+                    selection: ClipboardSelection::Clipboard,
+                    text: "こんにちは".to_string(),
+                },
+                ClipboardRequest::Read {
+                    selection: ClipboardSelection::Clipboard,
+                },
+            ]
+        );
+        // 不正な base64 は無視され、画面にも出力されない
+        assert!(buffer.take_clipboard_requests().is_empty());
+        assert_eq!(buffer.get_cursor_pos(), (0, 0));
+    }
+
+    #[test]
+    fn test_osc7_and_osc9_9_current_directory() {
+        let mut buffer = TerminalBufferEntity::new(20, 3);
+        let mut parser = AnsiParserDomainService::new();
+        assert!(buffer.get_current_directory().is_none());
+
+        parser.parse(b"\x1b]7;file://host/C:/My%20Work/\x1b\\", &mut buffer);
+        assert_eq!(
+            buffer.get_current_directory().unwrap().as_str(),
+            r"C:\My Work"
+        );
+
+        // パスに含まれる ';' も失われない
+        parser.parse(b"\x1b]9;9;\"D:\\a;b\"\x07", &mut buffer);
+        assert_eq!(buffer.get_current_directory().unwrap().as_str(), r"D:\a;b");
+
+        // OSC 9;4 (進捗表示) はカレントディレクトリに影響しない
+        parser.parse(b"\x1b]9;4;1;50\x07", &mut buffer);
+        assert_eq!(buffer.get_current_directory().unwrap().as_str(), r"D:\a;b");
+        assert_eq!(buffer.get_cursor_pos(), (0, 0));
+    }
+
+    #[test]
+    fn test_osc133_prompt_marks_move_to_scrollback() {
+        use crate::domain::model::semantic_prompt_value::PromptMarkKind;
+
+        let mut buffer = TerminalBufferEntity::new(20, 2);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07out\r\n\x1b]133;D;2\x07",
+            &mut buffer,
+        );
+
+        let kinds: Vec<Vec<(PromptMarkKind, usize)>> = buffer
+            .primary_lines()
+            .map(|line| {
+                line.prompt_marks()
+                    .iter()
+                    .map(|m| (m.kind, m.column))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(buffer.get_history_len(), 1);
+        assert_eq!(
+            kinds,
+            vec![
+                vec![
+                    (PromptMarkKind::PromptStart, 0),
+                    (PromptMarkKind::CommandStart, 2)
+                ],
+                vec![(PromptMarkKind::OutputStart, 0)],
+                vec![(PromptMarkKind::CommandFinished { exit_code: Some(2) }, 0)],
+            ]
+        );
+
+        // 画面消去でグリッド上のマークは消えるが、スクロールバックのマークは残る
+        parser.parse(b"\x1b[2J", &mut buffer);
+        let marked_rows = buffer
+            .primary_lines()
+            .filter(|line| !line.prompt_marks().is_empty())
+            .count();
+        assert_eq!(marked_rows, 1);
+    }
+
+    /// 入力に対する応答を連結した文字列を返す
+    fn replies_for(buffer: &mut TerminalBufferEntity, input: &[u8]) -> String {
+        let mut parser = AnsiParserDomainService::new();
+        let replies = parser.parse(input, buffer);
+        String::from_utf8(replies.concat()).unwrap()
+    }
+
+    #[test]
+    fn test_reply_dsr() {
+        let mut buffer = TerminalBufferEntity::new(80, 24);
+        assert_eq!(replies_for(&mut buffer, b"\x1b[5n"), "\x1b[0n");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[6n"), "\x1b[1;1R");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[5;10H\x1b[6n"), "\x1b[5;10R");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?6n"), "\x1b[?5;10;1R");
+        // 原点モードではスクロール領域からの相対位置を報告する
+        assert_eq!(
+            replies_for(&mut buffer, b"\x1b[3;20r\x1b[?6h\x1b[2;4H\x1b[6n"),
+            "\x1b[2;4R"
+        );
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?5n"), "");
+    }
+
+    #[test]
+    fn test_reply_device_attributes() {
+        let mut buffer = TerminalBufferEntity::new(80, 24);
+        assert_eq!(replies_for(&mut buffer, b"\x1b[c"), "\x1b[?62;4;22c");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[0c"), "\x1b[?62;4;22c");
+
+        let version: Vec<u32> = env!("CARGO_PKG_VERSION")
+            .split('.')
+            .map(|p| p.parse().unwrap())
+            .collect();
+        let version_number = version[0] * 10000 + version[1] * 100 + version[2];
+        assert_eq!(
+            replies_for(&mut buffer, b"\x1b[>c"),
+            format!("\x1b[>0;{};0c", version_number)
+        );
+        assert_eq!(
+            replies_for(&mut buffer, b"\x1b[>q"),
+            format!(
+                "\x1bP>|EmEditorTerminal({})\x1b\\",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+        // 問い合わせ以外のパラメータには応答しない
+        assert_eq!(replies_for(&mut buffer, b"\x1b[1c\x1b[>1q"), "");
+    }
+
+    #[test]
+    fn test_reply_decrqm() {
+        let mut buffer = TerminalBufferEntity::new(80, 24);
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?25$p"), "\x1b[?25;1$y");
+        assert_eq!(
+            replies_for(&mut buffer, b"\x1b[?25l\x1b[?25$p"),
+            "\x1b[?25;2$y"
+        );
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?6$p"), "\x1b[?6;2$y");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?7$p"), "\x1b[?7;1$y");
+        assert_eq!(
+            replies_for(&mut buffer, b"\x1b[?1049h\x1b[?1049$p\x1b[?47$p"),
+            "\x1b[?1049;1$y\x1b[?47;1$y"
+        );
+        assert_eq!(
+            replies_for(
+                &mut buffer,
+                b"\x1b[?1002h\x1b[?1000$p\x1b[?1002$p\x1b[?1006$p"
+            ),
+            "\x1b[?1000;2$y\x1b[?1002;1$y\x1b[?1006;2$y"
+        );
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?9999$p"), "\x1b[?9999;0$y");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[20$p"), "\x1b[20;4$y");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[2$p"), "\x1b[2;0$y");
+    }
+
+    #[test]
+    fn test_input_modes() {
+        let mut buffer = TerminalBufferEntity::new(80, 24);
+        let mut parser = AnsiParserDomainService::new();
+        assert_eq!(buffer.get_input_modes(), InputModes::default());
+
+        parser.parse(b"\x1b[?1h\x1b=", &mut buffer);
+        assert!(buffer.get_input_modes().is_application_cursor_keys);
+        assert!(buffer.get_input_modes().is_application_keypad);
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?1$p"), "\x1b[?1;1$y");
+
+        parser.parse(b"\x1b[?1l\x1b>", &mut buffer);
+        assert_eq!(buffer.get_input_modes(), InputModes::default());
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?1$p"), "\x1b[?1;2$y");
+    }
+
+    #[test]
+    fn test_deferred_autowrap() {
+        let mut buffer = TerminalBufferEntity::new(5, 4);
+        let mut parser = AnsiParserDomainService::new();
+
+        // ちょうど行幅の文字の後に CR LF しても空行は生じない
+        parser.parse(b"abcde\r\nxy", &mut buffer);
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(0).unwrap()),
+            "abcde"
+        );
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(1).unwrap()),
+            "xy   "
+        );
+        assert!(!buffer.get_line_at_visual_row(0).unwrap().is_wrapped());
+
+        // 最終桁に書いた直後はカーソルが行末に留まり、次の文字で折り返す
+        parser.parse(b"\x1b[3;1H12345", &mut buffer);
+        assert_eq!(replies_for(&mut buffer, b"\x1b[6n"), "\x1b[3;5R");
+        parser.parse(b"6\r", &mut buffer);
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(2).unwrap()),
+            "12345"
+        );
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(3).unwrap()),
+            "6    "
+        );
+        assert!(buffer.get_line_at_visual_row(2).unwrap().is_wrapped());
+        assert!(!buffer.get_line_at_visual_row(3).unwrap().is_wrapped());
+    }
+
+    #[test]
+    fn test_decsc_saves_pending_wrap() {
+        let mut buffer = TerminalBufferEntity::new(5, 3);
+        let mut parser = AnsiParserDomainService::new();
+
+        // 折り返し待ちの状態で DECSC し、移動後に DECRC すると次の文字で折り返す
+        parser.parse(b"abcde\x1b7\x1b[3;1H\x1b8f", &mut buffer);
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(0).unwrap()),
+            "abcde"
+        );
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(1).unwrap()),
+            "f    "
+        );
+        assert!(buffer.get_line_at_visual_row(0).unwrap().is_wrapped());
+
+        // 折り返し待ちでない位置を保存した場合は最終桁を上書きする
+        parser.parse(b"\x1b[3;5H\x1b7\x1b[1;1H\x1b8g", &mut buffer);
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(2).unwrap()),
+            "    g"
+        );
+        assert_eq!(replies_for(&mut buffer, b"\x1b[6n"), "\x1b[3;5R");
+    }
+
+    #[test]
+    fn test_deferred_autowrap_wide_char() {
+        let mut buffer = TerminalBufferEntity::new(5, 3);
+        let mut parser = AnsiParserDomainService::new();
+        // 全角文字が残り 1 桁に収まらない場合は次の行へ送る
+        parser.parse("abcdあ\r".as_bytes(), &mut buffer);
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(0).unwrap()),
+            "abcd "
+        );
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(1).unwrap()),
+            "あ   "
+        );
+        assert!(buffer.get_line_at_visual_row(0).unwrap().is_wrapped());
+    }
+
+    #[test]
+    fn test_decawm_off_overwrites_last_column() {
+        let mut buffer = TerminalBufferEntity::new(5, 3);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(b"\x1b[?7labcdefg\r", &mut buffer);
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(0).unwrap()),
+            "abcdg"
+        );
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(1).unwrap()),
+            "     "
+        );
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?7$p"), "\x1b[?7;2$y");
+
+        parser.parse(b"\x1b[?7h\x1b[2;1Habcdefg\r", &mut buffer);
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(2).unwrap()),
+            "fg   "
+        );
+    }
+
+    #[test]
+    fn test_soft_wrap_flag_cleared_by_erase() {
+        let mut buffer = TerminalBufferEntity::new(5, 3);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(b"abcdefg\x1b[1;3H\x1b[K", &mut buffer);
+        assert!(!buffer.get_line_at_visual_row(0).unwrap().is_wrapped());
+    }
+
+    #[test]
+    fn test_resize_reflows_wrapped_lines() {
+        let mut buffer = TerminalBufferEntity::new(10, 4);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(b"0123456789abc\r\n$ ", &mut buffer);
+        assert_eq!(buffer.get_cursor_pos(), (2, 2));
+
+        // 幅を狭めると論理行ごとに折り返し直される
+        buffer.resize(5, 4);
+        let rows: Vec<String> = (0..4)
+            .map(|row| line_to_string(buffer.get_line_at_visual_row(row).unwrap()))
+            .collect();
+        assert_eq!(rows, vec!["01234", "56789", "abc  ", "$    "]);
+        assert_eq!(buffer.get_cursor_pos(), (2, 3));
+
+        // 元の幅に戻すと切り詰められずに復元される
+        buffer.resize(10, 4);
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(0).unwrap()),
+            "0123456789"
+        );
+        assert_eq!(
+            line_to_string(buffer.get_line_at_visual_row(1).unwrap()),
+            "abc       "
+        );
+        assert_eq!(buffer.get_cursor_pos(), (2, 2));
+    }
+
+    #[test]
+    fn test_resize_height_shrink_pushes_rows_to_scrollback() {
+        let mut buffer = TerminalBufferEntity::new(10, 5);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(b"1\r\n2\r\n3\r\n4", &mut buffer);
+
+        buffer.resize(10, 2);
+        assert_eq!(buffer.get_history_len(), 2);
+        assert_eq!(buffer.get_line_at_visual_row(0).unwrap()[0].text, "3");
+        assert_eq!(buffer.get_line_at_visual_row(1).unwrap()[0].text, "4");
+        assert_eq!(buffer.get_cursor_pos(), (1, 1));
+
+        // カーソルより下の空行だけなら履歴へは送らない
+        let mut buffer = TerminalBufferEntity::new(10, 5);
+        parser.parse(b"1", &mut buffer);
+        buffer.resize(10, 2);
+        assert_eq!(buffer.get_history_len(), 0);
+        assert_eq!(buffer.get_line_at_visual_row(0).unwrap()[0].text, "1");
+    }
+
+    #[test]
+    fn test_resize_on_alternate_screen_keeps_primary() {
+        let mut buffer = TerminalBufferEntity::new(10, 3);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(b"0123456789ab\x1b[?1049h", &mut buffer);
+        buffer.resize(6, 3);
+        parser.parse(b"\x1b[?1049l", &mut buffer);
+        let rows: Vec<String> = (0..3)
+            .map(|row| line_to_string(buffer.get_line_at_visual_row(row).unwrap()))
+            .collect();
+        assert_eq!(rows, vec!["012345", "6789ab", "      "]);
+        // 保存されていたカーソル位置も論理行内の位置を保って移動する
+        assert_eq!(buffer.get_cursor_pos(), (0, 2));
+    }
+
+    fn screen_rows(buffer: &TerminalBufferEntity, rows: usize) -> Vec<String> {
+        (0..rows)
+            .map(|row| {
+                line_to_string(buffer.get_line_at_visual_row(row).unwrap())
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tab_stops_column_output() {
+        let mut buffer = TerminalBufferEntity::new(24, 4);
+        let mut parser = AnsiParserDomainService::new();
+        // column -t のように既定の 8 桁間隔で揃う
+        parser.parse(b"NAME\tSIZE\tTYPE\r\nfoo.rs\t12\tfile\r\n", &mut buffer);
+        assert_eq!(
+            screen_rows(&buffer, 2),
+            vec!["NAME    SIZE    TYPE", "foo.rs  12      file"]
+        );
+
+        // タブはセルを上書きせずにカーソルだけを移動する
+        parser.parse(b"\x1b[1;1H\tX", &mut buffer);
+        assert_eq!(screen_rows(&buffer, 1), vec!["NAME    XIZE    TYPE"]);
+    }
+
+    #[test]
+    fn test_tab_stops_set_clear_and_move() {
+        let mut buffer = TerminalBufferEntity::new(24, 4);
+        let mut parser = AnsiParserDomainService::new();
+        // すべて解除して 4 桁目と 10 桁目に設定する
+        parser.parse(b"\x1b[3g\x1b[1;5H\x1bH\x1b[1;11H\x1bH\r", &mut buffer);
+        parser.parse(b"a\tb\tc\r\n\tx", &mut buffer);
+        // 5 桁目の停止位置だけを解除する
+        parser.parse(b"\x1b[1;5H\x1b[g\x1b[3;1H\ty", &mut buffer);
+        assert_eq!(
+            screen_rows(&buffer, 3),
+            vec!["a   b     c", "    x", "          y"]
+        );
+
+        // CHT / CBT は N 個先（前）の停止位置へ移動する
+        parser.parse(b"\x1b[3g\x1b[4;7H\x1bH\x1b[4;13H\x1bH", &mut buffer);
+        parser.parse(b"\x1b[4;1H\x1b[2I1\x1b[2Z2\x1b[2Z3", &mut buffer);
+        assert_eq!(screen_rows(&buffer, 4)[3], "3     2     1");
+        assert_eq!(buffer.get_cursor_pos(), (1, 3));
+        // 右端を越えて移動しない
+        parser.parse(b"\x1b[4;1H\x1b[9I", &mut buffer);
+        assert_eq!(buffer.get_cursor_pos(), (23, 3));
+    }
+
+    #[test]
+    fn test_tab_stops_reset_on_resize_and_ris() {
+        let mut buffer = TerminalBufferEntity::new(24, 4);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(b"\x1b[3g", &mut buffer);
+        buffer.resize(30, 4);
+        parser.parse(b"\r\t", &mut buffer);
+        assert_eq!(buffer.get_cursor_pos().0, 8);
+
+        parser.parse(b"\x1b[3g\x1bc\t", &mut buffer);
+        assert_eq!(buffer.get_cursor_pos(), (8, 0));
+    }
+
+    #[test]
+    fn test_ris_resets_modes_and_palette() {
+        let mut buffer = TerminalBufferEntity::new(10, 3);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(
+            b"\x1b]0;title\x07\x1b]4;1;#123456\x07\x1b[?1h\x1b[?2004h\x1b[?7l\x1b[31mabc",
+            &mut buffer,
+        );
+        parser.parse(b"\x1bc", &mut buffer);
+        assert_eq!(screen_rows(&buffer, 3), vec!["", "", ""]);
+        assert_eq!(buffer.get_cursor_pos(), (0, 0));
+        assert_eq!(buffer.get_input_modes(), InputModes::default());
+        assert!(!buffer.is_bracketed_paste_enabled());
+        assert!(buffer.is_autowrap_enabled());
+        assert_eq!(buffer.get_current_attribute().fg, TerminalColor::Default);
+        assert_eq!(
+            replies_for(&mut buffer, b"\x1b]4;1;?\x07"),
+            replies_for(&mut TerminalBufferEntity::new(10, 3), b"\x1b]4;1;?\x07")
+        );
+        // タイトルは維持する
+        assert_eq!(buffer.get_title().window_title, "title");
+    }
+
+    #[test]
+    fn test_dec_special_graphics_line_drawing() {
+        let mut buffer = TerminalBufferEntity::new(10, 4);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(b"\x1b(0lqqk\r\nx  x\r\nmqqj\x1b(B\r\nlqqk", &mut buffer);
+        assert_eq!(
+            screen_rows(&buffer, 4),
+            vec!["┌──┐", "│  │", "└──┘", "lqqk"]
+        );
+    }
+
+    #[test]
+    fn test_shift_out_uses_g1() {
+        let mut buffer = TerminalBufferEntity::new(10, 2);
+        let mut parser = AnsiParserDomainService::new();
+        // ncurses の典型: G1 に罫線を指定し、SO/SI で切り替える
+        parser.parse(b"\x1b)0a\x0eqx\x0fq\x1b(A#", &mut buffer);
+        assert_eq!(screen_rows(&buffer, 1), vec!["a─│q£"]);
+
+        // RIS で ASCII に戻る
+        parser.parse(b"\x1b)0\x0e\x1bcq", &mut buffer);
+        assert_eq!(screen_rows(&buffer, 1), vec!["q"]);
+    }
+
+    #[test]
+    fn test_insert_mode() {
+        let mut buffer = TerminalBufferEntity::new(8, 2);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(b"abcdef\x1b[1;3H\x1b[4hXY\x1b[4lZ\r", &mut buffer);
+        assert_eq!(screen_rows(&buffer, 1), vec!["abXYZdef"]);
+        assert_eq!(replies_for(&mut buffer, b"\x1b[4$p"), "\x1b[4;2$y");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[4h\x1b[4$p"), "\x1b[4;1$y");
+    }
+
+    #[test]
+    fn test_insert_mode_keeps_wide_char_boundary() {
+        let mut buffer = TerminalBufferEntity::new(6, 2);
+        let mut parser = AnsiParserDomainService::new();
+        // 右端へ押し出される全角文字は半分だけ残さずに空白にする
+        parser.parse("abcdあ\x1b[1;1H\x1b[4hX\r".as_bytes(), &mut buffer);
+        assert_eq!(screen_rows(&buffer, 1), vec!["Xabcd"]);
+        // 全角文字の途中への挿入は分断された文字を空白にする
+        parser.parse("\x1b[2;1Hあい\x1b[2;2HZ\r".as_bytes(), &mut buffer);
+        let row = buffer.get_line_at_visual_row(1).unwrap();
+        assert!(row.iter().all(|cell| cell.text != "あ"));
+        assert_eq!(row[1].text, "Z");
+        assert_eq!(row[3].text, "い");
+    }
+
+    #[test]
+    fn test_repeat_last_graphic() {
+        let mut buffer = TerminalBufferEntity::new(12, 3);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse("-\x1b[4b|あ\x1b[2b\r\n".as_bytes(), &mut buffer);
+        assert_eq!(screen_rows(&buffer, 1), vec!["-----|あああ"]);
+
+        // 折り返しは通常の出力と同じく行われる
+        parser.parse(b"=\x1b[13b\r", &mut buffer);
+        assert_eq!(screen_rows(&buffer, 3)[1..], ["============", "=="]);
+    }
+
+    #[test]
+    fn test_sgr_underline_styles() {
+        use crate::domain::model::terminal_types_entity::UnderlineStyle;
+        let mut buffer = TerminalBufferEntity::new(10, 2);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(
+            b"\x1b[4ma\x1b[4:3mb\x1b[21mc\x1b[4:5md\x1b[4:0me\x1b[4;24mf",
+            &mut buffer,
+        );
+        let line = buffer.get_line_at_visual_row(0).unwrap();
+        let styles: Vec<_> = (0..6).map(|i| line[i].attribute.underline).collect();
+        assert_eq!(
+            styles,
+            vec![
+                UnderlineStyle::Single,
+                UnderlineStyle::Curly,
+                UnderlineStyle::Double,
+                UnderlineStyle::Dashed,
+                UnderlineStyle::None,
+                UnderlineStyle::None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sgr_extended_colors() {
+        let mut buffer = TerminalBufferEntity::new(10, 2);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(
+            b"\x1b[58:2::1:2:3ma\x1b[58;5;9mb\x1b[58:2:4:5:6mc\x1b[59md\x1b[38:2::7:8:9;48;2;1;1;1me",
+            &mut buffer,
+        );
+        let line = buffer.get_line_at_visual_row(0).unwrap();
+        assert_eq!(
+            line[0].attribute.underline_color,
+            TerminalColor::Rgb(1, 2, 3)
+        );
+        assert_eq!(line[1].attribute.underline_color, TerminalColor::Xterm(9));
+        assert_eq!(
+            line[2].attribute.underline_color,
+            TerminalColor::Rgb(4, 5, 6)
+        );
+        assert_eq!(line[3].attribute.underline_color, TerminalColor::Default);
+        assert_eq!(line[4].attribute.fg, TerminalColor::Rgb(7, 8, 9));
+        assert_eq!(line[4].attribute.bg, TerminalColor::Rgb(1, 1, 1));
+    }
+
+    #[test]
+    fn test_sgr_blink_conceal_overline() {
+        let mut buffer = TerminalBufferEntity::new(10, 2);
+        let mut parser = AnsiParserDomainService::new();
+        parser.parse(b"\x1b[5;8;53ma\x1b[25mb\x1b[28mc\x1b[55md", &mut buffer);
+        let line = buffer.get_line_at_visual_row(0).unwrap();
+        let flags = |i: usize| {
+            let attr = &line[i].attribute;
+            (attr.is_blink, attr.is_concealed, attr.is_overline)
+        };
+        assert_eq!(flags(0), (true, true, true));
+        assert_eq!(flags(1), (false, true, true));
+        assert_eq!(flags(2), (false, false, true));
+        assert_eq!(flags(3), (false, false, false));
+
+        // SGR 6 (高速点滅) も点滅として扱い、SGR 0 ですべて解除する
+        parser.parse(b"\x1b[6me\x1b[0mf", &mut buffer);
+        let line = buffer.get_line_at_visual_row(0).unwrap();
+        assert!(line[4].attribute.is_blink);
+        assert_eq!(line[5].attribute, Default::default());
+    }
+
+    #[test]
+    fn test_sixel_image_is_anchored_to_cells() {
+        let mut buffer = TerminalBufferEntity::new(10, 5);
+        let mut parser = AnsiParserDomainService::new();
+        buffer.set_cell_pixel_size(10, 20);
+        // 25x30 ピクセルの画像は 3 列 x 2 行のセルを占める
+        parser.parse(b"ab\x1bP0;1;0q\"1;1;25;30#1~\x1b\\", &mut buffer);
+        let fragment_at = |buffer: &TerminalBufferEntity, x: usize, y: usize| {
+            let line = buffer.get_line_at_visual_row(y).unwrap();
+            line[x].image.as_ref().map(|f| (f.column, f.row))
+        };
+        assert_eq!(fragment_at(&buffer, 1, 0), None);
+        assert_eq!(fragment_at(&buffer, 2, 0), Some((0, 0)));
+        assert_eq!(fragment_at(&buffer, 4, 1), Some((2, 1)));
+        assert_eq!(fragment_at(&buffer, 5, 1), None);
+        // カーソルは画像の直下、配置を始めた列へ移動する
+        assert_eq!(buffer.get_cursor_pos(), (2, 2));
+
+        // 文字で上書きすると画像の区画は消える
+        parser.parse(b"\x1b[1;3HX", &mut buffer);
+        assert_eq!(fragment_at(&buffer, 2, 0), None);
+        assert_eq!(fragment_at(&buffer, 3, 0), Some((1, 0)));
+    }
+
+    #[test]
+    fn test_sixel_image_scrolls_into_scrollback() {
+        let mut buffer = TerminalBufferEntity::new(10, 3);
+        let mut parser = AnsiParserDomainService::new();
+        buffer.set_cell_pixel_size(10, 20);
+        // 受信が分割されても復号を継続する
+        parser.parse(b"\n\n\x1bPq#1!20", &mut buffer);
+        parser.parse(b"~-!20~\x1b\\", &mut buffer);
+        assert_eq!(buffer.get_history_len(), 1);
+        let rows: Vec<_> = buffer
+            .primary_lines()
+            .map(|line| line[0].image.as_ref().map(|f| f.row))
+            .collect();
+        assert_eq!(rows, vec![None, None, Some(0), None]);
+        assert_eq!(buffer.get_cursor_pos(), (0, 2));
+    }
+
+    #[test]
+    fn test_sixel_image_memory_limit() {
+        use crate::domain::model::sixel_image_registry_entity::MAX_IMAGE_MEMORY;
+        use crate::domain::model::sixel_image_value::SixelImage;
+
+        let mut buffer = TerminalBufferEntity::new(10, 3);
+        buffer.set_cell_pixel_size(1000, 1000);
+        let size = 1000 * 1000 * 4;
+        let count = MAX_IMAGE_MEMORY / size + 1;
+        for _ in 0..count {
+            buffer.place_image(SixelImage::new(1000, 1000, vec![0; size]).unwrap());
+        }
+        // 上限を超えた分の古い画像は、スクロールバックのセルからも取り除かれる
+        let images: Vec<_> = buffer
+            .primary_lines()
+            .map(|line| line[0].image.is_some())
+            .collect();
+        assert_eq!(images.len(), count + 1);
+        assert!(!images[0]);
+        assert!(images[1..count].iter().all(|&is_image| is_image));
+    }
+
+    #[test]
+    fn test_win32_input_mode() {
+        let mut buffer = TerminalBufferEntity::new(10, 2);
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?9001$p"), "\x1b[?9001;2$y");
+        replies_for(&mut buffer, b"\x1b[?9001h");
+        assert!(buffer.get_input_modes().is_win32_input_mode);
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?9001$p"), "\x1b[?9001;1$y");
+        replies_for(&mut buffer, b"\x1b[?9001l");
+        assert!(!buffer.get_input_modes().is_win32_input_mode);
+    }
+
+    #[test]
+    fn test_kitty_keyboard_flags() {
+        let mut buffer = TerminalBufferEntity::new(10, 2);
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?u"), "\x1b[?0u");
+        replies_for(&mut buffer, b"\x1b[>1u\x1b[>11u");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?u"), "\x1b[?11u");
+        replies_for(&mut buffer, b"\x1b[<u");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?u"), "\x1b[?1u");
+        replies_for(&mut buffer, b"\x1b[=4;2u");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?u"), "\x1b[?5u");
+        replies_for(&mut buffer, b"\x1b[=1;3u");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?u"), "\x1b[?4u");
+        replies_for(&mut buffer, b"\x1b[<5u");
+        assert!(buffer.get_input_modes().kitty_keyboard_flags.is_empty());
+    }
+
+    #[test]
+    fn test_kitty_keyboard_flags_per_screen() {
+        let mut buffer = TerminalBufferEntity::new(10, 2);
+        replies_for(&mut buffer, b"\x1b[>1u");
+        // 代替画面は独立したスタックを持つ
+        replies_for(&mut buffer, b"\x1b[?1049h");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?u"), "\x1b[?0u");
+        replies_for(&mut buffer, b"\x1b[>15u");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?u"), "\x1b[?15u");
+        replies_for(&mut buffer, b"\x1b[?1049l");
+        assert_eq!(replies_for(&mut buffer, b"\x1b[?u"), "\x1b[?1u");
+    }
+
+    #[test]
+    fn test_osc8_hyperlinks_are_pruned() {
+        let mut buffer = TerminalBufferEntity::new(20, 3);
+        let mut parser = AnsiParserDomainService::new();
+        // 代替画面はスクロールバックへ送らないため、画面から消えたリンクは参照されなくなる
+        parser.parse(b"\x1b[?1049h", &mut buffer);
+        for i in 0..2000 {
+            let line = format!("\x1b]8;;https://example.com/{}\x07x\x1b]8;;\x07\r\n", i);
+            parser.parse(line.as_bytes(), &mut buffer);
+        }
+        assert!(buffer.get_hyperlink_count() <= 256);
+        let link = buffer.get_hyperlink_at(0, 1).unwrap();
+        assert_eq!(link.uri, "https://example.com/1999");
+
+        // RIS で画面から消えたリンクも登録を外す
+        parser.parse(b"\x1b[?1049l\x1bc", &mut buffer);
+        assert_eq!(buffer.get_hyperlink_count(), 0);
     }
 }
@@ -0,0 +1,91 @@
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// OSC 52 等で使用される Base64 の符号化・復号を担うドメインサービス
+pub(crate) struct Base64CodecDomainService;
+
+impl Base64CodecDomainService {
+    pub(crate) fn encode(input_bytes: &[u8]) -> String {
+        let mut encoded = String::with_capacity(input_bytes.len().div_ceil(3) * 4);
+        for chunk in input_bytes.chunks(3) {
+            let b = [
+                chunk[0],
+                chunk.get(1).copied().unwrap_or(0),
+                chunk.get(2).copied().unwrap_or(0),
+            ];
+            let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    encoded.push(ALPHABET[((n >> (18 - i * 6)) & 0x3F) as usize] as char);
+                } else {
+                    encoded.push('=');
+                }
+            }
+        }
+        encoded
+    }
+
+    /// Base64 文字列を復号する。不正な文字が含まれる場合は None を返す。
+    pub(crate) fn decode(input: &[u8]) -> Option<Vec<u8>> {
+        let mut decoded = Vec::with_capacity(input.len() / 4 * 3);
+        let mut acc: u32 = 0;
+        let mut bits = 0;
+        for &b in input {
+            let value = match b {
+                b'A'..=b'Z' => b - b'A',
+                b'a'..=b'z' => b - b'a' + 26,
+                b'0'..=b'9' => b - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                b'=' => break,
+                b'\r' | b'\n' => continue,
+                _ => return None,
+            };
+            acc = (acc << 6) | value as u32;
+            bits += 6;
+            if bits >= 8 {
+                bits -= 8;
+                decoded.push((acc >> bits) as u8);
+                acc &= (1 << bits) - 1;
+            }
+        }
+        Some(decoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(Base64CodecDomainService::encode(b""), "");
+        assert_eq!(Base64CodecDomainService::encode(b"f"), "Zg==");
+        assert_eq!(Base64CodecDomainService::encode(b"fo"), "Zm8=");
+        assert_eq!(Base64CodecDomainService::encode(b"foo"), "Zm9v");
+        assert_eq!(
+            Base64CodecDomainService::encode("こんにちは".as_bytes()),
+            "44GT44KT44Gr44Gh44Gv"
+        );
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            Base64CodecDomainService::decode(b"Zg=="),
+            Some(b"f".to_vec())
+        );
+        assert_eq!(
+            Base64CodecDomainService::decode(b"Zm8="),
+            Some(b"fo".to_vec())
+        );
+        assert_eq!(
+            Base64CodecDomainService::decode(b"Zm9vYmFy"),
+            Some(b"foobar".to_vec())
+        );
+        assert_eq!(
+            Base64CodecDomainService::decode(b"44GT44KT44Gr44Gh44Gv"),
+            Some("こんにちは".as_bytes().to_vec())
+        );
+        assert_eq!(Base64CodecDomainService::decode(b"Zm9v!"), None);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::domain::model::command_block_value::CommandBlock;
+use crate::domain::model::semantic_prompt_value::PromptMarkKind;
+use crate::domain::model::terminal_line_entity::TerminalLine;
+
+/// 行に記録された OSC 133 のマークからコマンド区間を組み立てるドメインサービス
+pub(crate) struct CommandBlockDomainService;
+
+impl CommandBlockDomainService {
+    /// 古い順に並んだ行からコマンド区間の一覧を作成する。
+    /// プロンプト開始 (A) を区切りとし、A を伴わない B は新しい区間として扱う。
+    pub(crate) fn collect(lines: &[&TerminalLine]) -> Vec<CommandBlock> {
+        let mut blocks: Vec<CommandBlock> = Vec::new();
+        for (row, line) in lines.iter().enumerate() {
+            for mark in line.prompt_marks() {
+                let pos = (row, mark.column);
+                match mark.kind {
+                    PromptMarkKind::PromptStart => blocks.push(CommandBlock::new(row)),
+                    PromptMarkKind::CommandStart => {
+                        if blocks.last().is_none_or(|b| b.input_start.is_some()) {
+                            blocks.push(CommandBlock::new(row));
+                        }
+                        if let Some(block) = blocks.last_mut() {
+                            block.input_start = Some(pos);
+                        }
+                    }
+                    PromptMarkKind::OutputStart => {
+                        if let Some(block) = blocks.last_mut()
+                            && block.output_start.is_none()
+                        {
+                            block.output_start = Some(pos);
+                            block.started_at = Some(mark.timestamp);
+                            if let Some(input_start) = block.input_start {
+                                block.command = Self::extract_text(lines, input_start, Some(pos))
+                                    .trim()
+                                    .to_string();
+                            }
+                        }
+                    }
+                    PromptMarkKind::CommandFinished { exit_code } => {
+                        if let Some(block) = blocks.last_mut()
+                            && !block.is_finished()
+                        {
+                            block.output_end = Some(pos);
+                            block.exit_code = exit_code;
+                            block.finished_at = Some(mark.timestamp);
+                        }
+                    }
+                }
+            }
+        }
+        blocks
+    }
+
+    /// `rows` の順に行を調べ、最初に見つかったコマンド区間の先頭行 (`collect` の `prompt_row`) を返す。
+    /// 全行から区間を組み立て直さず、起点の前後だけを走査する。
+    pub(crate) fn find_prompt_row<'a>(
+        line_at: impl Fn(usize) -> Option<&'a TerminalLine>,
+        mut rows: impl Iterator<Item = usize>,
+    ) -> Option<usize> {
+        rows.find(|&row| Self::is_prompt_row(&line_at, row))
+    }
+
+    /// 行がコマンド区間を開始するかを判定する。
+    /// B は直前の A/B マークが A でない場合にだけ区間を開始するため、直前のマークまで遡る。
+    fn is_prompt_row<'a>(line_at: &impl Fn(usize) -> Option<&'a TerminalLine>, row: usize) -> bool {
+        let Some(line) = line_at(row) else {
+            return false;
+        };
+        let marks = line.prompt_marks();
+        if !marks.iter().any(|m| Self::is_prompt_mark(&m.kind)) {
+            return false;
+        }
+        let mut previous = (0..row)
+            .rev()
+            .filter_map(line_at)
+            .find_map(|l| {
+                l.prompt_marks()
+                    .iter()
+                    .rev()
+                    .find(|m| Self::is_prompt_mark(&m.kind))
+            })
+            .map(|m| m.kind);
+        for mark in marks {
+            match mark.kind {
+                PromptMarkKind::PromptStart => return true,
+                PromptMarkKind::CommandStart => {
+                    if previous != Some(PromptMarkKind::PromptStart) {
+                        return true;
+                    }
+                    previous = Some(mark.kind);
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// 区間の開始に関わるマーク (A または B) か
+    fn is_prompt_mark(kind: &PromptMarkKind) -> bool {
+        matches!(
+            kind,
+            PromptMarkKind::PromptStart | PromptMarkKind::CommandStart
+        )
+    }
+
+    /// コマンドの出力部分の文字列を取得する。実行中のコマンドは末尾までを対象とする。
+    pub(crate) fn extract_output(lines: &[&TerminalLine], block: &CommandBlock) -> Option<String> {
+        let start = block.output_start?;
+        Some(Self::extract_text(lines, start, block.output_end))
+    }
+
+    /// start から end（含まない）までの文字列を取り出す。
+    /// 自動折り返しで分割された行は改行を挟まずに連結し、それ以外は行末の空白を取り除く。
+    fn extract_text(
+        lines: &[&TerminalLine],
+        start: (usize, usize),
+        end: Option<(usize, usize)>,
+    ) -> String {
+        let end = end.unwrap_or((lines.len(), 0));
+        let mut text = String::new();
+        for (row, line) in lines.iter().enumerate().take(end.0 + 1).skip(start.0) {
+            let from = if row == start.0 { start.1 } else { 0 };
+            let to = if row == end.0 { end.1 } else { line.len() };
+            if row == end.0 && to == 0 && row != start.0 {
+                break;
+            }
+            let row_text: String = line
+                .iter()
+                .take(to)
+                .skip(from)
+                .filter(|c| !c.is_wide_continuation)
+                .map(|c| c.text.as_str())
+                .collect();
+            if line.is_wrapped() && row != end.0 {
+                text.push_str(&row_text);
+            } else {
+                text.push_str(row_text.trim_end());
+                text.push('\n');
+            }
+        }
+        text.trim_end_matches('\n').to_string()
+    }
+}
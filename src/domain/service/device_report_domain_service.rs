@@ -0,0 +1,79 @@
+/// XTVERSION で報告する端末名
+const TERMINAL_NAME: &str = "EmEditorTerminal";
+
+/// DA1 で報告する機能
+/// 62: VT220 互換, 4: Sixel, 22: ANSI カラー
+const PRIMARY_DEVICE_ATTRIBUTES: &[u16] = &[62, 4, 22];
+
+/// DECRQM で報告するモードの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ModeState {
+    NotRecognized = 0,
+    Set = 1,
+    Reset = 2,
+    PermanentlyReset = 4,
+}
+
+impl ModeState {
+    pub(crate) fn from_flag(is_set: bool) -> Self {
+        if is_set { Self::Set } else { Self::Reset }
+    }
+}
+
+/// アプリケーションからの問い合わせに対する応答シーケンスを組み立てるドメインサービス
+pub(crate) struct DeviceReportDomainService;
+
+impl DeviceReportDomainService {
+    /// DSR 5 (動作状態): 常に正常 (CSI 0 n)
+    pub(crate) fn operating_status() -> Vec<u8> {
+        b"\x1b[0n".to_vec()
+    }
+
+    /// DSR 6 (CPR) / DECXCPR。行と列は 1 始まり
+    pub(crate) fn cursor_position(row: usize, col: usize, is_extended: bool) -> Vec<u8> {
+        if is_extended {
+            format!("\x1b[?{};{};1R", row, col).into_bytes()
+        } else {
+            format!("\x1b[{};{}R", row, col).into_bytes()
+        }
+    }
+
+    /// DA1 (CSI c)
+    pub(crate) fn primary_device_attributes() -> Vec<u8> {
+        let attrs: Vec<String> = PRIMARY_DEVICE_ATTRIBUTES
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        format!("\x1b[?{}c", attrs.join(";")).into_bytes()
+    }
+
+    /// DA2 (CSI > c): 端末種別 0 (VT100), ファームウェアバージョン, ROM 番号 0
+    pub(crate) fn secondary_device_attributes() -> Vec<u8> {
+        format!("\x1b[>0;{};0c", Self::version_number()).into_bytes()
+    }
+
+    /// XTVERSION (CSI > q): DCS > | name(version) ST
+    pub(crate) fn terminal_version() -> Vec<u8> {
+        format!(
+            "\x1bP>|{}({})\x1b\\",
+            TERMINAL_NAME,
+            env!("CARGO_PKG_VERSION")
+        )
+        .into_bytes()
+    }
+
+    /// DECRQM の応答 (DECRPM)
+    pub(crate) fn mode_report(mode: u16, is_private: bool, state: ModeState) -> Vec<u8> {
+        let marker = if is_private { "?" } else { "" };
+        format!("\x1b[{}{};{}$y", marker, mode, state as u8).into_bytes()
+    }
+
+    /// パッケージのバージョン (major.minor.patch) を DA2 用の数値に変換する
+    fn version_number() -> u32 {
+        env!("CARGO_PKG_VERSION")
+            .split('.')
+            .take(3)
+            .map(|part| part.parse::<u32>().unwrap_or(0))
+            .fold(0, |acc, part| acc * 100 + part)
+    }
+}
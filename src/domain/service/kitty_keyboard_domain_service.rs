@@ -0,0 +1,256 @@
+use crate::domain::model::input_value::InputKey;
+use crate::domain::model::kitty_keyboard_value::{KittyKey, KittyKeyboardFlags};
+
+/// kitty キーボードプロトコル (`CSI code ; mods u`) のキーイベントを組み立てるドメインサービス
+/// 有効なフラグに応じて CSI u で送るキーを選び、それ以外は従来の符号化に任せる。
+pub(crate) struct KittyKeyboardDomainService;
+
+impl KittyKeyboardDomainService {
+    /// キーイベントを有効なフラグに従って符号化する。
+    /// 従来の VT シーケンス (または WM_CHAR による文字入力) で送るべきキーの場合は None を返す。
+    pub(crate) fn encode(key: InputKey, flags: KittyKeyboardFlags) -> Option<Vec<u8>> {
+        let is_event_types = flags.contains(KittyKeyboardFlags::REPORT_EVENT_TYPES);
+        if key.is_key_up && !is_event_types {
+            return None;
+        }
+        // イベント種別 (2: リピート, 3: 解放)。押下 (1) は省略する
+        let event_type = if key.is_key_up {
+            Some(3)
+        } else if key.is_repeat && is_event_types {
+            Some(2)
+        } else {
+            None
+        };
+        let is_disambiguate = flags.contains(KittyKeyboardFlags::DISAMBIGUATE);
+        let is_all_keys = flags.contains(KittyKeyboardFlags::REPORT_ALL_KEYS);
+        let modifier = key.modifiers.xterm_parameter().unwrap_or(1);
+        let is_chord = key.modifiers.is_ctrl_pressed || key.modifiers.is_alt_pressed;
+
+        let kitty_key = KittyKey::classify(key)?;
+        let is_kitty_form = is_all_keys
+            || match kitty_key {
+                KittyKey::Text { .. } => key.is_key_up || (is_disambiguate && is_chord),
+                // Enter 等の解放は、すべてのキーを報告する場合のみ送る
+                KittyKey::Editing(_) => !key.is_key_up && is_disambiguate && modifier != 1,
+                KittyKey::Escape | KittyKey::Keypad(_) => key.is_key_up || is_disambiguate,
+                // F3 の `CSI R` はカーソル位置報告と区別できないため常に `CSI 13 ~` で送る
+                KittyKey::Tilde(13) => true,
+                KittyKey::Letter(_) | KittyKey::Tilde(_) => event_type.is_some(),
+                KittyKey::Modifier(_) => false,
+            };
+        if !is_kitty_form {
+            return None;
+        }
+
+        // 修飾キーが無い押下イベントではパラメータを省略する
+        let parameters = match (modifier, event_type) {
+            (1, None) => String::new(),
+            (m, None) => format!(";{}", m),
+            (m, Some(event_type)) => format!(";{}:{}", m, event_type),
+        };
+        let sequence = match kitty_key {
+            KittyKey::Text { code, shifted_code } => {
+                let alternate = match shifted_code {
+                    Some(shifted)
+                        if key.modifiers.is_shift_pressed
+                            && flags.contains(KittyKeyboardFlags::REPORT_ALTERNATE_KEYS) =>
+                    {
+                        format!(":{}", shifted)
+                    }
+                    _ => String::new(),
+                };
+                format!("\x1b[{}{}{}u", code, alternate, parameters)
+            }
+            KittyKey::Editing(code) | KittyKey::Keypad(code) | KittyKey::Modifier(code) => {
+                format!("\x1b[{}{}u", code, parameters)
+            }
+            KittyKey::Escape => format!("\x1b[27{}u", parameters),
+            KittyKey::Letter(final_byte) if parameters.is_empty() => {
+                format!("\x1b[{}", final_byte as char)
+            }
+            KittyKey::Letter(final_byte) => format!("\x1b[1{}{}", parameters, final_byte as char),
+            KittyKey::Tilde(number) => format!("\x1b[{}{}~", number, parameters),
+        };
+        Some(sequence.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::input_value::Modifiers;
+    use crate::domain::model::kitty_keyboard_value::*;
+
+    fn encode(key: InputKey, flags: u16) -> Option<String> {
+        KittyKeyboardDomainService::encode(key, KittyKeyboardFlags::from_bits(flags))
+            .map(|seq| String::from_utf8(seq).unwrap())
+    }
+
+    fn release(vk_code: u16, spec: &str) -> InputKey {
+        InputKey::from_spec(vk_code, spec).with_key_state(0, false, true)
+    }
+
+    fn repeat(vk_code: u16, spec: &str) -> InputKey {
+        InputKey::from_spec(vk_code, spec).with_repeat(true)
+    }
+
+    #[test]
+    fn test_disambiguate() {
+        let cases: &[(u16, &str, Option<&str>)] = &[
+            // 文字入力は従来通り WM_CHAR に任せる
+            (0x41, "", None),
+            (0x41, "S", None),
+            (0x41, "C", Some("\x1b[97;5u")),
+            (0x49, "C", Some("\x1b[105;5u")),
+            (0x31, "A", Some("\x1b[49;3u")),
+            (VK_SPACE, "C", Some("\x1b[32;5u")),
+            (VK_ESCAPE, "", Some("\x1b[27u")),
+            (VK_RETURN, "", None),
+            (VK_RETURN, "S", Some("\x1b[13;2u")),
+            (VK_TAB, "S", Some("\x1b[9;2u")),
+            (VK_BACK, "C", Some("\x1b[127;5u")),
+            // 機能キーは従来の符号化と同じ
+            (VK_UP, "", None),
+            (VK_UP, "C", None),
+            (VK_F1 + 2, "", Some("\x1b[13~")),
+            (VK_F1 + 2, "S", Some("\x1b[13;2~")),
+            (VK_NUMPAD0 + 5, "", Some("\x1b[57404u")),
+            (VK_ADD, "", Some("\x1b[57413u")),
+            (VK_SHIFT, "S", None),
+        ];
+        for &(vk_code, keys, expected) in cases {
+            assert_eq!(
+                encode(InputKey::from_spec(vk_code, keys), 0b1).as_deref(),
+                expected,
+                "vk=0x{:02X} mods={}",
+                vk_code,
+                keys
+            );
+        }
+    }
+
+    #[test]
+    fn test_report_all_keys() {
+        let cases: &[(u16, &str, &str)] = &[
+            (0x41, "", "\x1b[97u"),
+            (0x41, "S", "\x1b[97;2u"),
+            (0x30, "", "\x1b[48u"),
+            (VK_RETURN, "", "\x1b[13u"),
+            (VK_BACK, "", "\x1b[127u"),
+            (VK_UP, "", "\x1b[A"),
+            (VK_UP, "CS", "\x1b[1;6A"),
+            (VK_F1, "", "\x1b[P"),
+            (VK_F1 + 4, "A", "\x1b[15;3~"),
+            (VK_DELETE, "", "\x1b[3~"),
+            (VK_SHIFT, "S", "\x1b[57441;2u"),
+            (VK_RMENU, "A", "\x1b[57449;3u"),
+        ];
+        for &(vk_code, keys, expected) in cases {
+            assert_eq!(
+                encode(InputKey::from_spec(vk_code, keys), 0b1001).as_deref(),
+                Some(expected),
+                "vk=0x{:02X} mods={}",
+                vk_code,
+                keys
+            );
+        }
+    }
+
+    #[test]
+    fn test_left_and_right_modifier_keys() {
+        let encode_modifier = |vk_code, scan_code, is_extended| {
+            let key = InputKey::new(vk_code, Modifiers::none()).with_key_state(
+                scan_code,
+                is_extended,
+                false,
+            );
+            encode(key, KittyKeyboardFlags::REPORT_ALL_KEYS).unwrap()
+        };
+        assert_eq!(encode_modifier(VK_SHIFT, 0x2A, false), "\x1b[57441u");
+        assert_eq!(encode_modifier(VK_SHIFT, 0x36, false), "\x1b[57447u");
+        assert_eq!(encode_modifier(VK_CONTROL, 0x1D, false), "\x1b[57442u");
+        assert_eq!(encode_modifier(VK_CONTROL, 0x1D, true), "\x1b[57448u");
+        assert_eq!(encode_modifier(VK_MENU, 0x38, true), "\x1b[57449u");
+    }
+
+    #[test]
+    fn test_report_event_types() {
+        // 解放はフラグが無ければ送らない
+        assert_eq!(encode(release(0x41, ""), 0b1), None);
+        assert_eq!(
+            encode(release(0x41, ""), 0b11).as_deref(),
+            Some("\x1b[97;1:3u")
+        );
+        assert_eq!(
+            encode(release(0x41, "C"), 0b11).as_deref(),
+            Some("\x1b[97;5:3u")
+        );
+        assert_eq!(
+            encode(release(VK_UP, ""), 0b11).as_deref(),
+            Some("\x1b[1;1:3A")
+        );
+        assert_eq!(
+            encode(release(VK_PRIOR, ""), 0b11).as_deref(),
+            Some("\x1b[5;1:3~")
+        );
+        assert_eq!(
+            encode(release(VK_ESCAPE, ""), 0b11).as_deref(),
+            Some("\x1b[27;1:3u")
+        );
+        // Enter 等の解放は、すべてのキーを報告する場合のみ送る
+        assert_eq!(encode(release(VK_RETURN, ""), 0b11), None);
+        assert_eq!(
+            encode(release(VK_RETURN, ""), 0b1011).as_deref(),
+            Some("\x1b[13;1:3u")
+        );
+    }
+
+    #[test]
+    fn test_report_repeat_events() {
+        // リピートはフラグが無ければ押下として送る
+        assert_eq!(encode(repeat(VK_UP, ""), 0b1), None);
+        assert_eq!(
+            encode(repeat(0x41, "C"), 0b1).as_deref(),
+            Some("\x1b[97;5u")
+        );
+        assert_eq!(
+            encode(repeat(VK_UP, ""), 0b11).as_deref(),
+            Some("\x1b[1;1:2A")
+        );
+        assert_eq!(
+            encode(repeat(VK_DELETE, "S"), 0b11).as_deref(),
+            Some("\x1b[3;2:2~")
+        );
+        assert_eq!(
+            encode(repeat(0x41, "C"), 0b11).as_deref(),
+            Some("\x1b[97;5:2u")
+        );
+        // 修飾キーの無い文字入力のリピートは従来通り WM_CHAR に任せる
+        assert_eq!(encode(repeat(0x41, ""), 0b11), None);
+        assert_eq!(
+            encode(repeat(0x41, ""), 0b1011).as_deref(),
+            Some("\x1b[97;1:2u")
+        );
+    }
+
+    #[test]
+    fn test_report_alternate_keys() {
+        assert_eq!(
+            encode(InputKey::from_spec(0x41, "CS"), 0b101).as_deref(),
+            Some("\x1b[97:65;6u")
+        );
+        assert_eq!(
+            encode(InputKey::from_spec(0x41, "C"), 0b101).as_deref(),
+            Some("\x1b[97;5u")
+        );
+        assert_eq!(
+            encode(InputKey::from_spec(0x41, "S"), 0b1101).as_deref(),
+            Some("\x1b[97:65;2u")
+        );
+        // 数字の Shift 時の記号は配列に依存するため通知しない
+        assert_eq!(
+            encode(InputKey::from_spec(0x31, "S"), 0b1101).as_deref(),
+            Some("\x1b[49;2u")
+        );
+    }
+}
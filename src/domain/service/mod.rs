@@ -1,3 +1,11 @@
 pub(crate) mod ansi_parser_domain_service;
+pub(crate) mod base64_codec_domain_service;
+pub(crate) mod command_block_domain_service;
+pub(crate) mod device_report_domain_service;
+pub(crate) mod kitty_keyboard_domain_service;
+pub(crate) mod paste_domain_service;
+pub(crate) mod sixel_decoder_domain_service;
 pub(crate) mod terminal_protocol_handler;
+pub(crate) mod utf16_decoder_domain_service;
 pub(crate) mod vt_sequence_translator_domain_service;
+pub(crate) mod win32_input_domain_service;
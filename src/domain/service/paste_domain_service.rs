@@ -0,0 +1,126 @@
+const BRACKETED_PASTE_START: &str = "\x1b[200~";
+const BRACKETED_PASTE_END: &str = "\x1b[201~";
+
+/// 1 回の書き込みで送信する貼り付けデータの最大バイト数
+pub(crate) const PASTE_CHUNK_SIZE: usize = 4096;
+
+/// 貼り付けるテキストを端末へ送信できる形に整えるドメインサービス
+pub(crate) struct PasteDomainService;
+
+impl PasteDomainService {
+    /// 貼り付け用に正規化する。
+    /// 改行は CRLF を CR に揃え、ブラケットペーストの開始・終了マーカーは取り除く。
+    /// マーカーを除去した結果として新たなマーカーが現れる場合に備え、無くなるまで繰り返す。
+    pub(crate) fn sanitize(text: &str) -> String {
+        let mut sanitized = text.replace("\r\n", "\r");
+        loop {
+            let before = sanitized.len();
+            sanitized = sanitized
+                .replace(BRACKETED_PASTE_END, "")
+                .replace(BRACKETED_PASTE_START, "");
+            if sanitized.len() == before {
+                return sanitized;
+            }
+        }
+    }
+
+    /// 送信するデータを組み立てる。ブラケットペーストが有効な場合はマーカーで囲む
+    pub(crate) fn build_payload(text: &str, is_bracketed: bool) -> String {
+        let sanitized = Self::sanitize(text);
+        if sanitized.is_empty() {
+            return sanitized;
+        }
+        if is_bracketed {
+            format!(
+                "{}{}{}",
+                BRACKETED_PASTE_START, sanitized, BRACKETED_PASTE_END
+            )
+        } else {
+            sanitized
+        }
+    }
+
+    /// 複数行（改行を含む）テキストであるかを判定する
+    pub(crate) fn is_multiline(text: &str) -> bool {
+        text.contains(['\r', '\n'])
+    }
+
+    /// 文字の途中で分割しないよう、文字境界で max_bytes 以下のチャンクに分割する
+    pub(crate) fn split_chunks(payload: &str, max_bytes: usize) -> Vec<Vec<u8>> {
+        let mut chunks = Vec::new();
+        let mut rest = payload;
+        while !rest.is_empty() {
+            let mut end = rest.len().min(max_bytes.max(1));
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            if end == 0 {
+                // max_bytes が 1 文字より小さい場合でも先へ進める
+                end = rest.chars().next().map_or(rest.len(), char::len_utf8);
+            }
+            let (chunk, tail) = rest.split_at(end);
+            chunks.push(chunk.as_bytes().to_vec());
+            rest = tail;
+        }
+        chunks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(PasteDomainService::sanitize("a\r\nb\nc\r"), "a\rb\nc\r");
+        assert_eq!(
+            PasteDomainService::sanitize("x\x1b[201~; rm -rf /\x1b[200~"),
+            "x; rm -rf /"
+        );
+        // 除去後に再びマーカーが現れるケース
+        assert_eq!(
+            PasteDomainService::sanitize("\x1b[20\x1b[201~1~echo"),
+            "echo"
+        );
+    }
+
+    #[test]
+    fn test_build_payload() {
+        assert_eq!(
+            PasteDomainService::build_payload("ls\r\n", true),
+            "\x1b[200~ls\r\x1b[201~"
+        );
+        assert_eq!(PasteDomainService::build_payload("ls\r\n", false), "ls\r");
+        assert_eq!(PasteDomainService::build_payload("\x1b[201~", true), "");
+    }
+
+    #[test]
+    fn test_is_multiline() {
+        assert!(!PasteDomainService::is_multiline("echo hi"));
+        assert!(PasteDomainService::is_multiline("echo hi\r\n"));
+        assert!(PasteDomainService::is_multiline("a\nb"));
+    }
+
+    #[test]
+    fn test_split_chunks_on_char_boundary() {
+        let chunks = PasteDomainService::split_chunks("aあいう", 4);
+        assert_eq!(
+            chunks,
+            vec![
+                "aあ".as_bytes().to_vec(),
+                "い".as_bytes().to_vec(),
+                "う".as_bytes().to_vec()
+            ]
+        );
+        assert_eq!(
+            PasteDomainService::split_chunks("あ", 1),
+            vec!["あ".as_bytes().to_vec()]
+        );
+        assert!(PasteDomainService::split_chunks("", 4).is_empty());
+
+        let large = "x".repeat(PASTE_CHUNK_SIZE * 2 + 1);
+        let chunks = PasteDomainService::split_chunks(&large, PASTE_CHUNK_SIZE);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].len(), 1);
+    }
+}
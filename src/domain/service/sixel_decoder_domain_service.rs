@@ -0,0 +1,290 @@
+use crate::domain::model::sixel_image_value::SixelImage;
+use crate::domain::model::sixel_palette_value::SixelPalette;
+
+/// 受け付ける画像の最大の幅・高さ (ピクセル)。xterm の maxGraphicSize の既定値に合わせる
+const MAX_IMAGE_WIDTH: usize = 1000;
+const MAX_IMAGE_HEIGHT: usize = 1000;
+/// 1 つの sixel 文字が表す縦方向のピクセル数
+const SIXEL_HEIGHT: usize = 6;
+
+/// 数値パラメータを伴う制御文字
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    /// `"Pan;Pad;Ph;Pv`: ラスタ属性
+    Raster,
+    /// `#Pc` / `#Pc;Pu;Px;Py;Pz`: 色の選択・定義
+    Color,
+    /// `!Pn`: 次の sixel 文字の繰り返し
+    Repeat,
+}
+
+/// DCS q ... ST で送られる Sixel データを RGBA 画像へ復号するドメインサービス
+/// DCS のデータは複数回の受信に分かれて届くため、1 バイトずつ受け取って状態を保持する。
+/// 縦横比 (P1, Pan/Pad) は無視し、1:1 の画素として扱う。
+pub(crate) struct SixelDecoderDomainService {
+    /// P2 = 1: 描画されなかった画素を透明のままにする
+    is_transparent_background: bool,
+    palette: SixelPalette,
+    current_color: usize,
+    /// 確保済みの画素領域 (RGBA)。未描画の画素はアルファ値 0
+    pixels: Vec<u8>,
+    allocated_width: usize,
+    allocated_height: usize,
+    /// ラスタ属性で宣言された大きさ
+    declared_size: (usize, usize),
+    /// 描画された範囲の右端・下端
+    painted_size: (usize, usize),
+    x: usize,
+    y: usize,
+    repeat: usize,
+    command: Option<Command>,
+    params: Vec<u16>,
+    current_param: Option<u16>,
+}
+
+impl SixelDecoderDomainService {
+    pub(crate) fn new(is_transparent_background: bool) -> Self {
+        Self {
+            is_transparent_background,
+            palette: SixelPalette::new(),
+            current_color: 0,
+            pixels: Vec::new(),
+            allocated_width: 0,
+            allocated_height: 0,
+            declared_size: (0, 0),
+            painted_size: (0, 0),
+            x: 0,
+            y: 0,
+            repeat: 1,
+            command: None,
+            params: Vec::new(),
+            current_param: None,
+        }
+    }
+
+    /// DCS のデータを 1 バイト処理する
+    pub(crate) fn put(&mut self, byte: u8) {
+        if self.command.is_some() {
+            match byte {
+                b'0'..=b'9' => {
+                    let digit = (byte - b'0') as u16;
+                    let value = self.current_param.unwrap_or(0);
+                    self.current_param = Some(value.saturating_mul(10).saturating_add(digit));
+                    return;
+                }
+                b';' => {
+                    let value = self.current_param.take().unwrap_or(0);
+                    self.params.push(value);
+                    return;
+                }
+                _ => self.finish_command(),
+            }
+        }
+        match byte {
+            b'?'..=b'~' => self.draw_sixel(byte - b'?'),
+            b'$' => self.x = 0,
+            b'-' => {
+                self.x = 0;
+                self.y += SIXEL_HEIGHT;
+            }
+            b'"' => self.command = Some(Command::Raster),
+            b'#' => self.command = Some(Command::Color),
+            b'!' => self.command = Some(Command::Repeat),
+            _ => {}
+        }
+    }
+
+    /// 復号を終了して画像を返す。何も描画されていない場合は None を返す。
+    /// ラスタ属性で宣言された大きさは、画素が描画された場合のみ反映する。
+    pub(crate) fn finish(mut self) -> Option<SixelImage> {
+        if self.command.is_some() {
+            self.finish_command();
+        }
+        if self.painted_size.0 == 0 || self.painted_size.1 == 0 {
+            return None;
+        }
+        let width = self.declared_size.0.max(self.painted_size.0);
+        let height = self.declared_size.1.max(self.painted_size.1);
+        self.ensure_allocated(width, height);
+        let background = if self.is_transparent_background {
+            [0, 0, 0, 0]
+        } else {
+            self.palette.get(0)
+        };
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for row in self
+            .pixels
+            .chunks_exact(self.allocated_width * 4)
+            .take(height)
+        {
+            for pixel in row[..width * 4].chunks_exact(4) {
+                if pixel[3] == 0 {
+                    pixels.extend_from_slice(&background);
+                } else {
+                    pixels.extend_from_slice(pixel);
+                }
+            }
+        }
+        SixelImage::new(width, height, pixels)
+    }
+
+    fn finish_command(&mut self) {
+        if let Some(value) = self.current_param.take() {
+            self.params.push(value);
+        }
+        let params = std::mem::take(&mut self.params);
+        let param = |i: usize| params.get(i).copied().unwrap_or(0) as usize;
+        match self.command.take() {
+            Some(Command::Raster) => {
+                // 宣言だけで画素は確保しない (描画されないまま巨大な領域を確保させないため)
+                self.declared_size = (
+                    param(2).min(MAX_IMAGE_WIDTH),
+                    param(3).min(MAX_IMAGE_HEIGHT),
+                );
+            }
+            Some(Command::Color) => {
+                let register = SixelPalette::register_index(param(0));
+                if params.len() >= 5 {
+                    self.palette
+                        .define(register, params[1], params[2], params[3], params[4]);
+                }
+                self.current_color = register;
+            }
+            Some(Command::Repeat) => self.repeat = param(0).max(1),
+            None => {}
+        }
+    }
+
+    /// 6 ビットのパターンを現在の色で描画し、繰り返し回数分だけ右へ進む
+    fn draw_sixel(&mut self, bits: u8) {
+        let repeat = std::mem::replace(&mut self.repeat, 1);
+        let right = (self.x + repeat).min(MAX_IMAGE_WIDTH);
+        if bits != 0 && self.x < right && self.y < MAX_IMAGE_HEIGHT {
+            let bottom = (self.y + SIXEL_HEIGHT).min(MAX_IMAGE_HEIGHT);
+            self.ensure_allocated(right, bottom);
+            let color = self.palette.get(self.current_color);
+            for dy in 0..bottom - self.y {
+                if bits & (1 << dy) == 0 {
+                    continue;
+                }
+                let row_start = ((self.y + dy) * self.allocated_width + self.x) * 4;
+                let row_end = row_start + (right - self.x) * 4;
+                for pixel in self.pixels[row_start..row_end].chunks_exact_mut(4) {
+                    pixel.copy_from_slice(&color);
+                }
+                self.painted_size.1 = self.painted_size.1.max(self.y + dy + 1);
+            }
+            self.painted_size.0 = self.painted_size.0.max(right);
+        }
+        self.x = right;
+    }
+
+    /// 少なくとも width x height の画素領域を確保する。拡張時は既存の画素を保持する。
+    fn ensure_allocated(&mut self, width: usize, height: usize) {
+        if width <= self.allocated_width && height <= self.allocated_height {
+            return;
+        }
+        let new_width = width
+            .max(self.allocated_width.saturating_mul(2))
+            .min(MAX_IMAGE_WIDTH)
+            .max(self.allocated_width);
+        let new_height = height
+            .max(self.allocated_height.saturating_mul(2))
+            .min(MAX_IMAGE_HEIGHT)
+            .max(self.allocated_height);
+        let mut pixels = vec![0; new_width * new_height * 4];
+        if self.allocated_width > 0 {
+            for (old_row, new_row) in self
+                .pixels
+                .chunks_exact(self.allocated_width * 4)
+                .zip(pixels.chunks_exact_mut(new_width * 4))
+            {
+                new_row[..old_row.len()].copy_from_slice(old_row);
+            }
+        }
+        self.pixels = pixels;
+        self.allocated_width = new_width;
+        self.allocated_height = new_height;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(data: &[u8], is_transparent_background: bool) -> Option<SixelImage> {
+        let mut decoder = SixelDecoderDomainService::new(is_transparent_background);
+        data.iter().for_each(|&b| decoder.put(b));
+        decoder.finish()
+    }
+
+    fn pixel(image: &SixelImage, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * image.get_width() + x) * 4;
+        image.get_pixels()[i..i + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn test_single_column() {
+        // '~' (0x3F + 0b111111) は縦 6 ピクセルすべてを描画する
+        let image = decode(b"#0;2;100;0;0~", false).unwrap();
+        assert_eq!((image.get_width(), image.get_height()), (1, 6));
+        assert_eq!(pixel(&image, 0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 0, 5), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_repeat_and_bands() {
+        // 1 段目: 赤を 3 列、2 段目: 緑で最上段のピクセルのみ 2 列
+        let image = decode(b"#1;2;100;0;0#2;2;0;100;0#1!3~-#2@@", true).unwrap();
+        assert_eq!((image.get_width(), image.get_height()), (3, 7));
+        assert_eq!(pixel(&image, 2, 5), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 1, 6), [0, 255, 0, 255]);
+        // 描画されていない画素は透明
+        assert_eq!(pixel(&image, 2, 6), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_carriage_return_overlays_colors() {
+        // '$' で同じ段の先頭に戻り、別の色で下半分を重ねる
+        let image = decode(b"#1;2;100;0;0E$#2;2;0;0;100w", true).unwrap();
+        assert_eq!((image.get_width(), image.get_height()), (1, 6));
+        assert_eq!(pixel(&image, 0, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(&image, 0, 1), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 0, 5), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_raster_attributes_define_size() {
+        // 宣言された大きさが描画範囲より大きい場合は宣言に従い、背景は色レジスタ 0 で塗る
+        let image = decode(b"\"1;1;4;12#0;2;0;0;100#1;2;100;100;100@", false).unwrap();
+        assert_eq!((image.get_width(), image.get_height()), (4, 12));
+        assert_eq!(pixel(&image, 0, 0), [255, 255, 255, 255]);
+        assert_eq!(pixel(&image, 3, 11), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_empty_data() {
+        assert!(decode(b"", false).is_none());
+        assert!(decode(b"#1;2;100;0;0", false).is_none());
+        // ラスタ属性だけでは画像にしない
+        assert!(decode(b"\"1;1;4096;4096", false).is_none());
+        assert!(decode(b"\"1;1;4096;4096!5000?-?", false).is_none());
+    }
+
+    #[test]
+    fn test_size_limit() {
+        // 宣言・描画のいずれも上限で切り詰める
+        let image = decode(b"\"1;1;4096;4096#1!5000~", true).unwrap();
+        assert_eq!(
+            (image.get_width(), image.get_height()),
+            (MAX_IMAGE_WIDTH, MAX_IMAGE_HEIGHT)
+        );
+        // 下端を越えた段は切り詰める (6 ピクセル x 166 段 = 996)
+        let data = [b"~".as_slice(), &b"-".repeat(166), b"~-~"].concat();
+        let image = decode(&data, true).unwrap();
+        assert_eq!(
+            (image.get_width(), image.get_height()),
+            (1, MAX_IMAGE_HEIGHT)
+        );
+    }
+}
@@ -1,17 +1,117 @@
+use crate::domain::model::character_set_value::CharacterSet;
+use crate::domain::model::clipboard_value::{ClipboardRequest, ClipboardSelection};
+use crate::domain::model::color_palette_entity::DynamicColor;
+use crate::domain::model::color_theme_value::RgbColor;
+use crate::domain::model::hyperlink_value::Hyperlink;
+use crate::domain::model::kitty_keyboard_value::KittyKeyboardFlags;
+use crate::domain::model::semantic_prompt_value::PromptMarkKind;
 use crate::domain::model::terminal_buffer_entity::TerminalBufferEntity;
+use crate::domain::model::terminal_title_value::TitleTarget;
 use crate::domain::model::terminal_types_entity::{
-    CursorStyle, MouseTrackingMode, TerminalAttribute, TerminalColor,
+    CursorStyle, MouseEncoding, MouseTrackingMode, TerminalAttribute, TerminalColor, UnderlineStyle,
 };
+use crate::domain::model::working_directory_value::WorkingDirectory;
+use crate::domain::service::base64_codec_domain_service::Base64CodecDomainService;
+use crate::domain::service::device_report_domain_service::{DeviceReportDomainService, ModeState};
+use crate::domain::service::sixel_decoder_domain_service::SixelDecoderDomainService;
 use vte::{Params, Perform};
 
 /// ターミナルプロトコル（ANSI/VT100等）の解釈と実行を担うドメインサービス
 pub(crate) struct TerminalProtocolHandler<'a> {
     buffer: &'a mut TerminalBufferEntity,
+    /// 受信中の Sixel データの復号状態。DCS は複数回の受信にまたがるため呼び出し元が保持する
+    sixel_decoder: &'a mut Option<SixelDecoderDomainService>,
+    /// アプリケーションへ返す応答シーケンス
+    replies: Vec<Vec<u8>>,
 }
 
 impl<'a> TerminalProtocolHandler<'a> {
-    pub fn new(buffer: &'a mut TerminalBufferEntity) -> Self {
-        Self { buffer }
+    pub fn new(
+        buffer: &'a mut TerminalBufferEntity,
+        sixel_decoder: &'a mut Option<SixelDecoderDomainService>,
+    ) -> Self {
+        Self {
+            buffer,
+            sixel_decoder,
+            replies: Vec::new(),
+        }
+    }
+
+    pub fn take_replies(&mut self) -> Vec<Vec<u8>> {
+        std::mem::take(&mut self.replies)
+    }
+
+    fn reply(&mut self, bytes: Vec<u8>) {
+        self.replies.push(bytes);
+    }
+
+    /// DSR (CSI n / CSI ? n)
+    fn handle_dsr(&mut self, params: &Params, is_private: bool) {
+        match self.get_param(params, 0, 0) {
+            5 if !is_private => self.reply(DeviceReportDomainService::operating_status()),
+            6 => {
+                let (row, col) = self.buffer.get_cursor_report_pos();
+                self.reply(DeviceReportDomainService::cursor_position(
+                    row, col, is_private,
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    /// DECRQM (CSI Ps $ p / CSI ? Ps $ p)
+    fn handle_decrqm(&mut self, params: &Params, is_private: bool) {
+        let mode = self.get_param(params, 0, 0);
+        let state = if is_private {
+            self.private_mode_state(mode)
+        } else {
+            self.ansi_mode_state(mode)
+        };
+        self.reply(DeviceReportDomainService::mode_report(
+            mode, is_private, state,
+        ));
+    }
+
+    /// DEC 専用モードの現在の状態
+    fn private_mode_state(&self, mode: u16) -> ModeState {
+        let mouse_mode = self.buffer.get_mouse_tracking_mode();
+        let mouse_encoding = self.buffer.get_mouse_encoding();
+        match mode {
+            1 => ModeState::from_flag(self.buffer.get_input_modes().is_application_cursor_keys),
+            6 => ModeState::from_flag(self.buffer.is_origin_mode()),
+            7 => ModeState::from_flag(self.buffer.is_autowrap_enabled()),
+            25 => ModeState::from_flag(self.buffer.is_cursor_visible()),
+            9 => ModeState::from_flag(mouse_mode == MouseTrackingMode::X10),
+            47 | 1047 | 1049 => ModeState::from_flag(self.buffer.is_alternate_screen()),
+            1000 => ModeState::from_flag(mouse_mode == MouseTrackingMode::Default),
+            1002 => ModeState::from_flag(mouse_mode == MouseTrackingMode::ButtonEvent),
+            1003 => ModeState::from_flag(mouse_mode == MouseTrackingMode::AnyEvent),
+            1004 => ModeState::from_flag(self.buffer.is_focus_reporting_enabled()),
+            1005 => ModeState::from_flag(mouse_encoding == MouseEncoding::Utf8),
+            1006 => ModeState::from_flag(mouse_encoding == MouseEncoding::Sgr),
+            1007 => ModeState::from_flag(self.buffer.is_alternate_scroll_enabled()),
+            1015 => ModeState::from_flag(mouse_encoding == MouseEncoding::Urxvt),
+            2004 => ModeState::from_flag(self.buffer.is_bracketed_paste_enabled()),
+            2026 => ModeState::from_flag(self.buffer.is_synchronized_output_enabled()),
+            9001 => ModeState::from_flag(self.buffer.get_input_modes().is_win32_input_mode),
+            _ => ModeState::NotRecognized,
+        }
+    }
+
+    /// ANSI モードの現在の状態
+    fn ansi_mode_state(&self, mode: u16) -> ModeState {
+        match mode {
+            4 => ModeState::from_flag(self.buffer.is_insert_mode()),
+            // LNM (改行モード) は未対応のため常にリセット
+            20 => ModeState::PermanentlyReset,
+            _ => ModeState::NotRecognized,
+        }
+    }
+
+    /// OSC の応答を問い合わせと同じ終端（BEL または ST）で組み立てる
+    fn reply_osc(&mut self, body: String, bell_terminated: bool) {
+        let terminator = if bell_terminated { "\x07" } else { "\x1b\\" };
+        self.reply(format!("\x1b]{}{}", body, terminator).into_bytes());
     }
 
     fn get_param(&self, params: &Params, index: usize, default: u16) -> u16 {
@@ -42,9 +142,162 @@ impl<'a> TerminalProtocolHandler<'a> {
         self.buffer.set_cursor_style(style);
     }
 
+    /// OSC のテキスト引数を復元する。vte は ';' で引数を分割するため、連結して元に戻す。
+    fn osc_text(params: &[&[u8]]) -> String {
+        let joined = params.join(&b';');
+        String::from_utf8_lossy(&joined)
+            .chars()
+            .filter(|c| !c.is_control())
+            .collect()
+    }
+
+    fn handle_xtwinops(&mut self, params: &Params) {
+        let target = TitleTarget::from_param(self.get_param(params, 1, 0));
+        match self.get_param(params, 0, 0) {
+            22 => self.buffer.push_title(),
+            23 => self.buffer.pop_title(target),
+            _ => {}
+        }
+    }
+
+    /// OSC 8 ; params ; URI を処理する。URI が空の場合はリンクを終了する。
+    fn handle_hyperlink(&mut self, args: &[&[u8]]) {
+        let Some((link_params, uri)) = args.split_first() else {
+            return;
+        };
+        let uri = Self::osc_text(uri);
+        if uri.is_empty() {
+            self.buffer.set_hyperlink(None);
+            return;
+        }
+        // params は key=value を ':' で区切った形式
+        let id = String::from_utf8_lossy(link_params)
+            .split(':')
+            .find_map(|kv| kv.strip_prefix("id="))
+            .filter(|id| !id.is_empty())
+            .map(str::to_string);
+        self.buffer.set_hyperlink(Some(Hyperlink::new(uri, id)));
+    }
+
+    /// OSC 52 ; Pc ; Pd
+    /// Pd が "?" なら読み取り要求、それ以外は base64 でエンコードされた書き込み内容として扱う。
+    /// 許可ポリシーの判定はワークフロー側で行う。
+    fn handle_clipboard(&mut self, args: &[&[u8]]) {
+        let [targets, data] = args else {
+            return;
+        };
+        let selection = ClipboardSelection::parse_list(targets)
+            .into_iter()
+            .next()
+            .unwrap_or(ClipboardSelection::Clipboard);
+        let request = if *data == b"?" {
+            ClipboardRequest::Read { selection }
+        } else {
+            let Some(decoded) = Base64CodecDomainService::decode(data) else {
+                log::warn!("OSC 52: invalid base64 payload ignored");
+                return;
+            };
+            ClipboardRequest::Write {
+                selection,
+                text: String::from_utf8_lossy(&decoded).into_owned(),
+            }
+        };
+        self.buffer.push_clipboard_request(request);
+    }
+
+    /// OSC 7 ; file://host/path
+    fn handle_osc7_directory(&mut self, args: &[&[u8]]) {
+        match WorkingDirectory::from_file_uri(&Self::osc_text(args)) {
+            Some(directory) => {
+                log::debug!("OSC 7: current directory = {}", directory.as_str());
+                self.buffer.set_current_directory(directory);
+            }
+            None => log::debug!("OSC 7: unsupported URI ignored"),
+        }
+    }
+
+    /// OSC 9 ; 9 ; path (Windows Terminal 形式のカレントディレクトリ通知)
+    /// OSC 9 のその他のサブコマンド（通知・進捗表示）は未対応のため無視する。
+    fn handle_osc9(&mut self, args: &[&[u8]]) {
+        if let Some((&subcommand, path)) = args.split_first()
+            && subcommand == b"9"
+            && let Some(directory) = WorkingDirectory::from_path(&Self::osc_text(path))
+        {
+            log::debug!("OSC 9;9: current directory = {}", directory.as_str());
+            self.buffer.set_current_directory(directory);
+        }
+    }
+
+    /// OSC 4 ; c ; spec [; c ; spec ...]
+    fn handle_osc4_palette(&mut self, args: &[&[u8]], bell_terminated: bool) {
+        for pair in args.chunks_exact(2) {
+            let Some(index) = Self::parse_osc_number(pair[0]).and_then(|n| u8::try_from(n).ok())
+            else {
+                continue;
+            };
+            let spec = String::from_utf8_lossy(pair[1]);
+            if spec == "?" {
+                let color = self.buffer.get_palette().get_indexed(index);
+                self.reply_osc(
+                    format!("4;{};{}", index, color.to_x11_spec()),
+                    bell_terminated,
+                );
+            } else if let Some(color) = RgbColor::parse_x11_spec(&spec) {
+                self.buffer.get_palette_mut().set_indexed(index, color);
+            }
+        }
+    }
+
+    /// OSC 10/11/12 ; spec [; spec ...]
+    /// 複数指定された場合は後続の番号（10 の次は 11）の色として扱う。
+    fn handle_dynamic_color(&mut self, first: DynamicColor, args: &[&[u8]], bell_terminated: bool) {
+        let mut target = Some(first);
+        for spec in args {
+            let Some(current) = target else {
+                break;
+            };
+            let spec = String::from_utf8_lossy(spec);
+            if spec == "?" {
+                let color = self.buffer.get_palette().get_dynamic(current);
+                self.reply_osc(
+                    format!("{};{}", current.to_osc(), color.to_x11_spec()),
+                    bell_terminated,
+                );
+            } else if let Some(color) = RgbColor::parse_x11_spec(&spec) {
+                self.buffer
+                    .get_palette_mut()
+                    .set_dynamic(current, Some(color));
+            }
+            target = current.next();
+        }
+    }
+
+    /// OSC 104 [; c ...]
+    fn handle_reset_palette(&mut self, args: &[&[u8]]) {
+        let indexes: Vec<u8> = args
+            .iter()
+            .filter_map(|a| Self::parse_osc_number(a).and_then(|n| u8::try_from(n).ok()))
+            .collect();
+        let palette = self.buffer.get_palette_mut();
+        if indexes.is_empty() {
+            palette.reset_indexed(None);
+        }
+        for index in indexes {
+            palette.reset_indexed(Some(index));
+        }
+    }
+
+    fn parse_osc_number(param: &[u8]) -> Option<u16> {
+        std::str::from_utf8(param).ok()?.parse().ok()
+    }
+
     fn handle_sgr(&mut self, params: &Params) {
+        let hyperlink_id = self.buffer.get_current_attribute().hyperlink_id;
         if params.is_empty() {
-            self.buffer.set_attribute(TerminalAttribute::default());
+            self.buffer.set_attribute(TerminalAttribute {
+                hyperlink_id,
+                ..TerminalAttribute::default()
+            });
             return;
         }
         let mut attr = self.buffer.get_current_attribute().clone();
@@ -56,106 +309,113 @@ impl<'a> TerminalProtocolHandler<'a> {
                 1 => attr.is_bold = true,
                 2 => attr.is_dim = true,
                 3 => attr.is_italic = true,
-                4 => attr.is_underline = true,
+                4 => match subparams.get(1) {
+                    Some(&style) => {
+                        if let Some(style) = UnderlineStyle::from_subparam(style) {
+                            attr.underline = style;
+                        }
+                    }
+                    None => attr.underline = UnderlineStyle::Single,
+                },
+                5 | 6 => attr.is_blink = true,
                 7 => attr.is_inverse = true,
-                27 => attr.is_inverse = false,
+                8 => attr.is_concealed = true,
                 9 => attr.is_strikethrough = true,
+                21 => attr.underline = UnderlineStyle::Double,
                 22 => {
                     attr.is_bold = false;
                     attr.is_dim = false;
                 }
                 23 => attr.is_italic = false,
-                24 => attr.is_underline = false,
+                24 => attr.underline = UnderlineStyle::None,
+                25 => attr.is_blink = false,
+                27 => attr.is_inverse = false,
+                28 => attr.is_concealed = false,
                 29 => attr.is_strikethrough = false,
                 30..=37 => attr.fg = TerminalColor::Ansi((p - 30) as u8),
                 38 => {
-                    if let Some(type_p) = subparams.get(1).copied() {
-                        match type_p {
-                            5 => {
-                                if let Some(color_idx) = subparams.get(2).copied() {
-                                    attr.fg = TerminalColor::Xterm(color_idx as u8);
-                                }
-                            }
-                            2 if subparams.len() >= 5 => {
-                                attr.fg = TerminalColor::Rgb(
-                                    subparams[2] as u8,
-                                    subparams[3] as u8,
-                                    subparams[4] as u8,
-                                );
-                            }
-                            _ => {}
-                        }
-                    } else if let Some(next_subparams) = iter.next() {
-                        let type_p = next_subparams.first().copied().unwrap_or(0);
-                        match type_p {
-                            5 => {
-                                if let Some(color_sub) = iter.next()
-                                    && let Some(color_idx) = color_sub.first().copied()
-                                {
-                                    attr.fg = TerminalColor::Xterm(color_idx as u8);
-                                }
-                            }
-                            2 => {
-                                let r = iter.next().and_then(|s| s.first()).copied();
-                                let g = iter.next().and_then(|s| s.first()).copied();
-                                let b = iter.next().and_then(|s| s.first()).copied();
-                                if let (Some(r), Some(g), Some(b)) = (r, g, b) {
-                                    attr.fg = TerminalColor::Rgb(r as u8, g as u8, b as u8);
-                                }
-                            }
-                            _ => {}
-                        }
+                    if let Some(color) = Self::parse_extended_color(subparams, &mut iter) {
+                        attr.fg = color;
                     }
                 }
                 39 => attr.fg = TerminalColor::Default,
                 40..=47 => attr.bg = TerminalColor::Ansi((p - 40) as u8),
                 48 => {
-                    if let Some(type_p) = subparams.get(1).copied() {
-                        match type_p {
-                            5 => {
-                                if let Some(color_idx) = subparams.get(2).copied() {
-                                    attr.bg = TerminalColor::Xterm(color_idx as u8);
-                                }
-                            }
-                            2 if subparams.len() >= 5 => {
-                                attr.bg = TerminalColor::Rgb(
-                                    subparams[2] as u8,
-                                    subparams[3] as u8,
-                                    subparams[4] as u8,
-                                );
-                            }
-                            _ => {}
-                        }
-                    } else if let Some(next_subparams) = iter.next() {
-                        let type_p = next_subparams.first().copied().unwrap_or(0);
-                        match type_p {
-                            5 => {
-                                if let Some(color_sub) = iter.next()
-                                    && let Some(color_idx) = color_sub.first().copied()
-                                {
-                                    attr.bg = TerminalColor::Xterm(color_idx as u8);
-                                }
-                            }
-                            2 => {
-                                let r = iter.next().and_then(|s| s.first()).copied();
-                                let g = iter.next().and_then(|s| s.first()).copied();
-                                let b = iter.next().and_then(|s| s.first()).copied();
-                                if let (Some(r), Some(g), Some(b)) = (r, g, b) {
-                                    attr.bg = TerminalColor::Rgb(r as u8, g as u8, b as u8);
-                                }
-                            }
-                            _ => {}
-                        }
+                    if let Some(color) = Self::parse_extended_color(subparams, &mut iter) {
+                        attr.bg = color;
                     }
                 }
                 49 => attr.bg = TerminalColor::Default,
+                53 => attr.is_overline = true,
+                55 => attr.is_overline = false,
+                58 => {
+                    if let Some(color) = Self::parse_extended_color(subparams, &mut iter) {
+                        attr.underline_color = color;
+                    }
+                }
+                59 => attr.underline_color = TerminalColor::Default,
                 90..=97 => attr.fg = TerminalColor::Ansi((p - 90 + 8) as u8),
                 100..=107 => attr.bg = TerminalColor::Ansi((p - 100 + 8) as u8),
                 _ => {}
             }
         }
+        // ハイパーリンクは SGR の管理外であり、SGR 0 でも維持する
+        attr.hyperlink_id = hyperlink_id;
         self.buffer.set_attribute(attr);
     }
+
+    /// kitty キーボードプロトコルのフラグ操作 (CSI > u / CSI < u / CSI ? u / CSI = u)
+    fn handle_kitty_keyboard(&mut self, params: &Params, intermediates: &[u8]) {
+        let flags = KittyKeyboardFlags::from_bits(self.get_param(params, 0, 0));
+        match intermediates {
+            b">" => self.buffer.push_kitty_keyboard_flags(flags),
+            b"<" => self
+                .buffer
+                .pop_kitty_keyboard_flags(self.get_param(params, 0, 1).max(1) as usize),
+            b"=" => self
+                .buffer
+                .set_kitty_keyboard_flags(flags, self.get_param(params, 1, 1).max(1)),
+            b"?" => {
+                let current = self.buffer.get_input_modes().kitty_keyboard_flags;
+                self.reply(format!("\x1b[?{}u", current.bits()).into_bytes());
+            }
+            _ => {}
+        }
+    }
+
+    /// SGR 38 / 48 / 58 の色指定を解釈する。
+    /// コロン区切り (`38:5:n`, `38:2:r:g:b`, 色空間 ID 付きの `38:2::r:g:b`) と
+    /// セミコロン区切り (`38;5;n`, `38;2;r;g;b`) の両方に対応する。
+    fn parse_extended_color<'p>(
+        subparams: &[u16],
+        iter: &mut impl Iterator<Item = &'p [u16]>,
+    ) -> Option<TerminalColor> {
+        if let Some(&kind) = subparams.get(1) {
+            return match (kind, subparams.len()) {
+                (5, _) => subparams.get(2).map(|&idx| TerminalColor::Xterm(idx as u8)),
+                (2, 6..) => Some(TerminalColor::Rgb(
+                    subparams[3] as u8,
+                    subparams[4] as u8,
+                    subparams[5] as u8,
+                )),
+                (2, 5) => Some(TerminalColor::Rgb(
+                    subparams[2] as u8,
+                    subparams[3] as u8,
+                    subparams[4] as u8,
+                )),
+                _ => None,
+            };
+        }
+        let mut next = || iter.next().and_then(|s| s.first()).copied();
+        match next()? {
+            5 => next().map(|idx| TerminalColor::Xterm(idx as u8)),
+            2 => {
+                let (r, g, b) = (next()?, next()?, next()?);
+                Some(TerminalColor::Rgb(r as u8, g as u8, b as u8))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl<'a> Perform for TerminalProtocolHandler<'a> {
@@ -166,26 +426,87 @@ impl<'a> Perform for TerminalProtocolHandler<'a> {
         self.buffer.flush_pending_cluster();
         match byte {
             0x08 => self.buffer.move_cursor_backward(1),
-            0x09 => self.buffer.handle_tab(),
+            0x09 => self.buffer.forward_tab(1),
             0x0A..=0x0C => {
                 self.buffer.move_cursor_to_col(0);
                 self.buffer.index();
             }
             0x0D => self.buffer.move_cursor_to_col(0),
+            // SO / SI
+            0x0E => self.buffer.invoke_charset(1),
+            0x0F => self.buffer.invoke_charset(0),
             _ => {}
         }
     }
-    fn hook(&mut self, _params: &Params, _intermediates: &[u8], _ignore: bool, _action: char) {
+    fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
         self.buffer.flush_pending_cluster();
+        // DCS P1;P2;P3 q: Sixel。P2 = 1 の場合は描画されない画素を透明にする
+        *self.sixel_decoder = if action == 'q' && intermediates.is_empty() && !ignore {
+            let is_transparent_background = self.get_param(params, 1, 0) == 1;
+            Some(SixelDecoderDomainService::new(is_transparent_background))
+        } else {
+            None
+        };
     }
-    fn put(&mut self, _byte: u8) {
+    fn put(&mut self, byte: u8) {
         self.buffer.flush_pending_cluster();
+        if let Some(decoder) = self.sixel_decoder.as_mut() {
+            decoder.put(byte);
+        }
     }
     fn unhook(&mut self) {
         self.buffer.flush_pending_cluster();
+        if let Some(image) = self.sixel_decoder.take().and_then(|d| d.finish()) {
+            self.buffer.place_image(image);
+        }
     }
-    fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
         self.buffer.flush_pending_cluster();
+        let Some((command, args)) = params.split_first() else {
+            return;
+        };
+        match *command {
+            b"0" => {
+                let text = Self::osc_text(args);
+                self.buffer.set_icon_name(text.clone());
+                self.buffer.set_window_title(text);
+            }
+            b"1" => self.buffer.set_icon_name(Self::osc_text(args)),
+            b"2" => self.buffer.set_window_title(Self::osc_text(args)),
+            b"4" => self.handle_osc4_palette(args, bell_terminated),
+            b"7" => self.handle_osc7_directory(args),
+            b"8" => self.handle_hyperlink(args),
+            b"9" => self.handle_osc9(args),
+            b"10" | b"11" | b"12" => {
+                if let Some(target) =
+                    Self::parse_osc_number(command).and_then(DynamicColor::from_osc)
+                {
+                    self.handle_dynamic_color(target, args, bell_terminated);
+                }
+            }
+            b"52" => self.handle_clipboard(args),
+            b"104" => self.handle_reset_palette(args),
+            b"110" => self
+                .buffer
+                .get_palette_mut()
+                .set_dynamic(DynamicColor::Foreground, None),
+            b"111" => self
+                .buffer
+                .get_palette_mut()
+                .set_dynamic(DynamicColor::Background, None),
+            b"112" => self
+                .buffer
+                .get_palette_mut()
+                .set_dynamic(DynamicColor::Cursor, None),
+            b"133" => {
+                if let Some((kind, rest)) = args.split_first()
+                    && let Some(kind) = PromptMarkKind::from_params(kind, rest)
+                {
+                    self.buffer.add_prompt_mark(kind);
+                }
+            }
+            _ => {}
+        }
     }
     fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], _ignore: bool, action: char) {
         self.buffer.flush_pending_cluster();
@@ -222,6 +543,13 @@ impl<'a> Perform for TerminalProtocolHandler<'a> {
             'J' => self
                 .buffer
                 .erase_in_display(self.get_param(params, 0, 0) as u8),
+            'I' => self
+                .buffer
+                .forward_tab(self.get_param(params, 0, 1) as usize),
+            'Z' => self
+                .buffer
+                .backward_tab(self.get_param(params, 0, 1) as usize),
+            'g' => self.buffer.clear_tab_stop(self.get_param(params, 0, 0)),
             'G' => self
                 .buffer
                 .move_cursor_to_col(self.get_param(params, 0, 1).saturating_sub(1) as usize),
@@ -238,12 +566,38 @@ impl<'a> Perform for TerminalProtocolHandler<'a> {
                 self.buffer.move_cursor_up(n);
                 self.buffer.move_cursor_to_col(0);
             }
+            'h' | 'l' if intermediates.is_empty() => {
+                let is_set = action == 'h';
+                for subparams in params.iter() {
+                    for mode in subparams.iter() {
+                        if *mode == 4 {
+                            self.buffer.set_insert_mode(is_set);
+                        }
+                    }
+                }
+            }
+            'b' => self
+                .buffer
+                .repeat_last_graphic(self.get_param(params, 0, 1) as usize),
             'h' if intermediates.first() == Some(&b'?') => {
                 for subparams in params.iter() {
                     for mode in subparams.iter() {
                         match mode {
+                            1 => self.buffer.set_application_cursor_keys(true),
                             6 => self.buffer.set_origin_mode(true),
+                            7 => self.buffer.set_autowrap(true),
+                            9 => {
+                                log::debug!("Enabling X10 Mouse Tracking (9)");
+                                self.buffer.set_mouse_tracking_mode(MouseTrackingMode::X10);
+                            }
                             25 => self.buffer.set_cursor_visible(true),
+                            47 | 1047 => self.buffer.switch_to_alternate_screen(),
+                            1048 => self.buffer.save_cursor(),
+                            1049 => {
+                                self.buffer.save_cursor();
+                                self.buffer.switch_to_alternate_screen();
+                                self.buffer.erase_in_display(2);
+                            }
                             1000 => {
                                 log::debug!("Enabling Default Mouse Tracking (1000)");
                                 self.buffer
@@ -259,10 +613,23 @@ impl<'a> Perform for TerminalProtocolHandler<'a> {
                                 self.buffer
                                     .set_mouse_tracking_mode(MouseTrackingMode::AnyEvent);
                             }
+                            1004 => self.buffer.set_focus_reporting(true),
+                            1005 => {
+                                log::debug!("Enabling UTF-8 Mouse Encoding (1005)");
+                                self.buffer.set_mouse_encoding(MouseEncoding::Utf8);
+                            }
                             1006 => {
                                 log::debug!("Enabling SGR Mouse Encoding (1006)");
-                                self.buffer.set_sgr_mouse_encoding(true);
+                                self.buffer.set_mouse_encoding(MouseEncoding::Sgr);
+                            }
+                            1007 => self.buffer.set_alternate_scroll(true),
+                            1015 => {
+                                log::debug!("Enabling urxvt Mouse Encoding (1015)");
+                                self.buffer.set_mouse_encoding(MouseEncoding::Urxvt);
                             }
+                            2004 => self.buffer.set_bracketed_paste(true),
+                            2026 => self.buffer.set_synchronized_output(true),
+                            9001 => self.buffer.set_win32_input_mode(true),
                             _ => {}
                         }
                     }
@@ -272,16 +639,37 @@ impl<'a> Perform for TerminalProtocolHandler<'a> {
                 for subparams in params.iter() {
                     for mode in subparams.iter() {
                         match mode {
+                            1 => self.buffer.set_application_cursor_keys(false),
                             6 => self.buffer.set_origin_mode(false),
+                            7 => self.buffer.set_autowrap(false),
                             25 => self.buffer.set_cursor_visible(false),
-                            1000 | 1002 | 1003 => {
+                            47 => self.buffer.switch_to_primary_screen(),
+                            1047 => {
+                                if self.buffer.is_alternate_screen() {
+                                    self.buffer.erase_in_display(2);
+                                }
+                                self.buffer.switch_to_primary_screen();
+                            }
+                            1048 => self.buffer.restore_cursor(),
+                            1049 => {
+                                self.buffer.switch_to_primary_screen();
+                                self.buffer.restore_cursor();
+                            }
+                            9 | 1000 | 1002 | 1003 => {
                                 log::debug!("Disabling Mouse Tracking");
                                 self.buffer.set_mouse_tracking_mode(MouseTrackingMode::None)
                             }
+                            1004 => self.buffer.set_focus_reporting(false),
+                            1005 => self.buffer.reset_mouse_encoding(MouseEncoding::Utf8),
                             1006 => {
                                 log::debug!("Disabling SGR Mouse Encoding");
-                                self.buffer.set_sgr_mouse_encoding(false);
+                                self.buffer.reset_mouse_encoding(MouseEncoding::Sgr);
                             }
+                            1007 => self.buffer.set_alternate_scroll(false),
+                            1015 => self.buffer.reset_mouse_encoding(MouseEncoding::Urxvt),
+                            2004 => self.buffer.set_bracketed_paste(false),
+                            2026 => self.buffer.set_synchronized_output(false),
+                            9001 => self.buffer.set_win32_input_mode(false),
                             _ => {}
                         }
                     }
@@ -310,16 +698,55 @@ impl<'a> Perform for TerminalProtocolHandler<'a> {
             'q' if intermediates.first() == Some(&b' ') => {
                 self.handle_decscusr(params);
             }
+            't' if intermediates.is_empty() => self.handle_xtwinops(params),
+            'n' => self.handle_dsr(params, intermediates.first() == Some(&b'?')),
+            'c' if intermediates.is_empty() && self.get_param(params, 0, 0) == 0 => {
+                self.reply(DeviceReportDomainService::primary_device_attributes());
+            }
+            'c' if intermediates == b">" && self.get_param(params, 0, 0) == 0 => {
+                self.reply(DeviceReportDomainService::secondary_device_attributes());
+            }
+            'q' if intermediates == b">" && self.get_param(params, 0, 0) == 0 => {
+                self.reply(DeviceReportDomainService::terminal_version());
+            }
+            'p' if intermediates.last() == Some(&b'$') => {
+                self.handle_decrqm(params, intermediates.first() == Some(&b'?'));
+            }
+            'u' if !intermediates.is_empty() => self.handle_kitty_keyboard(params, intermediates),
             _ => {}
         }
     }
-    fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, byte: u8) {
+    fn esc_dispatch(&mut self, intermediates: &[u8], _ignore: bool, byte: u8) {
         self.buffer.flush_pending_cluster();
+        if !intermediates.is_empty() {
+            // SCS: ESC ( ) * + で G0〜G3 に文字集合を指定する
+            let slot = match intermediates {
+                b"(" => Some(0),
+                b")" => Some(1),
+                b"*" => Some(2),
+                b"+" => Some(3),
+                _ => None,
+            };
+            if let (Some(slot), Some(charset)) = (slot, CharacterSet::from_designator(byte)) {
+                self.buffer.designate_charset(slot, charset);
+            }
+            return;
+        }
         match byte as char {
             '7' => self.buffer.save_cursor(),
             '8' => self.buffer.restore_cursor(),
             'M' => self.buffer.reverse_index(),
             'D' => self.buffer.index(),
+            'H' => self.buffer.set_tab_stop(),
+            'c' => self.buffer.reset(),
+            // SS2 / SS3 / LS2 / LS3
+            'N' => self.buffer.single_shift_charset(2),
+            'O' => self.buffer.single_shift_charset(3),
+            'n' => self.buffer.invoke_charset(2),
+            'o' => self.buffer.invoke_charset(3),
+            // DECKPAM / DECKPNM
+            '=' => self.buffer.set_application_keypad(true),
+            '>' => self.buffer.set_application_keypad(false),
             _ => {}
         }
     }
@@ -0,0 +1,60 @@
+/// WM_CHAR で 1 単位ずつ届く UTF-16 を文字へ復元するドメインサービス
+/// 絵文字等の BMP 外の文字はサロゲートペアとして 2 回に分けて届くため、
+/// 上位サロゲートを保持し、下位サロゲートが届いた時点で 1 文字にする。
+#[derive(Debug, Default)]
+pub(crate) struct Utf16DecoderDomainService {
+    pending_high_surrogate: Option<u16>,
+}
+
+impl Utf16DecoderDomainService {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// UTF-16 の 1 単位を受け取り、確定した文字列を返す。上位サロゲートを保持した場合は空文字列。
+    /// 対になるサロゲートが無い単位は U+FFFD に置き換える。
+    pub(crate) fn push(&mut self, unit: u16) -> String {
+        let pending = self.pending_high_surrogate.take();
+        if (0xD800..=0xDBFF).contains(&unit) {
+            self.pending_high_surrogate = Some(unit);
+            return pending
+                .map(|_| char::REPLACEMENT_CHARACTER)
+                .into_iter()
+                .collect();
+        }
+        char::decode_utf16(pending.into_iter().chain([unit]))
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(units: &[u16]) -> Vec<String> {
+        let mut decoder = Utf16DecoderDomainService::new();
+        units.iter().map(|&unit| decoder.push(unit)).collect()
+    }
+
+    #[test]
+    fn test_surrogate_pair() {
+        // U+1F600 (😀) = D83D DE00
+        assert_eq!(decode(&[0xD83D, 0xDE00]), ["", "😀"]);
+        // U+20B9F (CJK 拡張 B) = D842 DF9F
+        assert_eq!(
+            decode(&[b'a' as u16, 0xD842, 0xDF9F, 0x3042]),
+            ["a", "", "𠮟", "あ"]
+        );
+    }
+
+    #[test]
+    fn test_unpaired_surrogates() {
+        // 下位サロゲートのみ
+        assert_eq!(decode(&[0xDE00]), ["\u{FFFD}"]);
+        // 上位サロゲートの後に通常の文字
+        assert_eq!(decode(&[0xD83D, b'x' as u16]), ["", "\u{FFFD}x"]);
+        // 上位サロゲートが続いた場合は古い方を捨てる
+        assert_eq!(decode(&[0xD83D, 0xD83D, 0xDE00]), ["", "\u{FFFD}", "😀"]);
+    }
+}
@@ -1,5 +1,8 @@
-use crate::domain::model::input_value::{InputKey, MouseButton, MouseEvent};
+use crate::domain::model::input_value::{InputKey, InputModes, MouseButton, MouseEvent};
+use crate::domain::model::terminal_types_entity::MouseEncoding;
 use crate::domain::repository::key_translator_repository::KeyTranslatorRepository;
+use crate::domain::service::kitty_keyboard_domain_service::KittyKeyboardDomainService;
+use crate::domain::service::win32_input_domain_service::Win32InputDomainService;
 
 /// VTシーケンス（ANSIエスケープシーケンス）への翻訳を行う実装
 pub struct VtSequenceTranslatorDomainService;
@@ -8,6 +11,114 @@ impl VtSequenceTranslatorDomainService {
     pub fn new() -> Self {
         Self
     }
+
+    /// 英字で終端するキー（矢印・Home・End・F1〜F4）のシーケンスを組み立てる。
+    /// 修飾キー付きの場合は `CSI 1 ; m X`、無い場合は `use_ss3` に従い `SS3 X` か `CSI X` を返す。
+    fn letter_key_sequence(final_byte: u8, modifier: Option<u8>, use_ss3: bool) -> Vec<u8> {
+        match modifier {
+            Some(m) => format!("\x1b[1;{}{}", m, final_byte as char).into_bytes(),
+            None if use_ss3 => vec![0x1B, b'O', final_byte],
+            None => vec![0x1B, b'[', final_byte],
+        }
+    }
+
+    /// `~` で終端するキー（Insert・Delete・PageUp/Down・F5〜F12）のシーケンスを組み立てる。
+    /// 修飾キー付きの場合は `CSI n ; m ~` を返す。
+    fn tilde_key_sequence(number: u8, modifier: Option<u8>) -> Vec<u8> {
+        match modifier {
+            Some(m) => format!("\x1b[{};{}~", number, m).into_bytes(),
+            None => format!("\x1b[{}~", number).into_bytes(),
+        }
+    }
+
+    /// Ctrl と記号キー・スペースの組み合わせで送る C0 制御文字
+    /// JIS 配列の @ [ ] \ ^ も同じ仮想キーコードに割り当てられている。
+    fn ctrl_symbol_byte(vk_code: u16) -> Option<u8> {
+        const VK_SPACE: u16 = 0x20;
+        const VK_2: u16 = 0x32;
+        const VK_6: u16 = 0x36;
+        const VK_OEM_MINUS: u16 = 0xBD;
+        const VK_OEM_2: u16 = 0xBF; // US: / JIS: /
+        const VK_OEM_3: u16 = 0xC0; // US: ` JIS: @
+        const VK_OEM_4: u16 = 0xDB; // [
+        const VK_OEM_5: u16 = 0xDC; // US: \ JIS: ￥
+        const VK_OEM_6: u16 = 0xDD; // ]
+        const VK_OEM_7: u16 = 0xDE; // US: ' JIS: ^
+        const VK_OEM_102: u16 = 0xE2; // JIS: \ (ろ)
+
+        match vk_code {
+            // Ctrl+Space / Ctrl+@ (Ctrl+Shift+2) -> NUL
+            VK_SPACE | VK_2 | VK_OEM_3 => Some(0x00),
+            VK_OEM_4 => Some(0x1B),
+            VK_OEM_5 | VK_OEM_102 => Some(0x1C),
+            VK_OEM_6 => Some(0x1D),
+            VK_6 | VK_OEM_7 => Some(0x1E),
+            // Ctrl+/ / Ctrl+_ -> US
+            VK_OEM_2 | VK_OEM_MINUS => Some(0x1F),
+            _ => None,
+        }
+    }
+
+    /// win32-input-mode のキーイベントを組み立てる。
+    /// 文字入力を含むすべてのキーの押下・解放を送るため、WM_CHAR による文字入力は行わない。
+    /// 解放は押下時と修飾キーの状態が異なっても (Ctrl より後に A を離す等) 必ず送る。
+    /// 入力文字 (Uc) はキーボードレイアウトから求めた文字を使い、求められない場合は
+    /// VT シーケンスが 1 文字になるキー (Ctrl+A, Enter 等) のみその文字から求める。
+    fn translate_win32_input(&self, key: InputKey, modes: InputModes) -> Option<Vec<u8>> {
+        // IME が処理するキーと、SendInput 等で文字を直接入力するキーは WM_CHAR に任せる
+        const VK_PROCESSKEY: u16 = 0xE5;
+        const VK_PACKET: u16 = 0xE7;
+        if matches!(key.vk_code, VK_PROCESSKEY | VK_PACKET) {
+            return None;
+        }
+        if key.unicode_char != 0 {
+            return Some(Win32InputDomainService::encode(key, key.unicode_char));
+        }
+        let vt_modes = InputModes {
+            is_win32_input_mode: false,
+            ..modes
+        };
+        let vt_key = InputKey {
+            is_key_up: false,
+            ..key
+        };
+        let unicode_char = match self.translate(vt_key, vt_modes).as_deref() {
+            // Backspace はコンソールでは BS として入力される
+            Some([0x7F]) => 0x08,
+            Some([byte]) => *byte as u16,
+            Some([0x1B, byte]) if key.modifiers.is_alt_pressed => *byte as u16,
+            _ => 0,
+        };
+        Some(Win32InputDomainService::encode(key, unicode_char))
+    }
+
+    /// アプリケーションキーパッドモードでのテンキーの SS3 終端文字
+    /// テンキーの Enter は通常の Enter と同じ仮想キーコードで、拡張キーとして区別される。
+    fn keypad_final_byte(key: InputKey) -> Option<u8> {
+        const VK_RETURN: u16 = 0x0D;
+        const VK_NUMPAD0: u16 = 0x60;
+        const VK_NUMPAD9: u16 = 0x69;
+        const VK_MULTIPLY: u16 = 0x6A;
+        const VK_ADD: u16 = 0x6B;
+        const VK_SEPARATOR: u16 = 0x6C;
+        const VK_SUBTRACT: u16 = 0x6D;
+        const VK_DECIMAL: u16 = 0x6E;
+        const VK_DIVIDE: u16 = 0x6F;
+
+        let vk_code = key.vk_code;
+        match vk_code {
+            VK_RETURN if key.is_extended => Some(b'M'),
+            // 0..9 -> p..y
+            VK_NUMPAD0..=VK_NUMPAD9 => Some(b'p' + (vk_code - VK_NUMPAD0) as u8),
+            VK_MULTIPLY => Some(b'j'),
+            VK_ADD => Some(b'k'),
+            VK_SEPARATOR => Some(b'l'),
+            VK_SUBTRACT => Some(b'm'),
+            VK_DECIMAL => Some(b'n'),
+            VK_DIVIDE => Some(b'o'),
+            _ => None,
+        }
+    }
 }
 
 impl Default for VtSequenceTranslatorDomainService {
@@ -17,11 +128,26 @@ impl Default for VtSequenceTranslatorDomainService {
 }
 
 impl KeyTranslatorRepository for VtSequenceTranslatorDomainService {
-    fn translate(&self, key: InputKey) -> Option<Vec<u8>> {
+    fn translate(&self, key: InputKey, modes: InputModes) -> Option<Vec<u8>> {
+        // kitty キーボードプロトコルで CSI u を送らないキーは従来の符号化に任せる
+        if !modes.kitty_keyboard_flags.is_empty()
+            && let Some(seq) = KittyKeyboardDomainService::encode(key, modes.kitty_keyboard_flags)
+        {
+            return Some(seq);
+        }
+        if modes.is_win32_input_mode {
+            return self.translate_win32_input(key, modes);
+        }
+        // VT シーケンスではキーを離したことは送信しない
+        if key.is_key_up {
+            return None;
+        }
+
         let vk_code = key.vk_code;
         let ctrl = key.modifiers.is_ctrl_pressed;
         let shift = key.modifiers.is_shift_pressed;
         let alt = key.modifiers.is_alt_pressed;
+        let modifier = key.modifiers.xterm_parameter();
 
         // Win32 Virtual-Key Codes (一部抜粋、実装に必要なもの)
         // Note: ここで定数を手動定義しているのは、Domain層が windows クレートに依存しないようにするためです。
@@ -60,6 +186,14 @@ impl KeyTranslatorRepository for VtSequenceTranslatorDomainService {
             return Some(vec![ctrl_char]);
         }
 
+        // Ctrl + 記号 / スペース。AltGr (Ctrl+Alt) による文字入力は妨げない
+        if ctrl
+            && !alt
+            && let Some(byte) = Self::ctrl_symbol_byte(vk_code)
+        {
+            return Some(vec![byte]);
+        }
+
         // Alt + Letter/Number (Meta key)
         if alt && !ctrl {
             if (0x30..=0x39).contains(&vk_code) {
@@ -76,97 +210,89 @@ impl KeyTranslatorRepository for VtSequenceTranslatorDomainService {
             }
         }
 
-        // Special keys with modifiers
-        let seq: Option<&[u8]> = match vk_code {
-            VK_UP => {
-                if ctrl {
-                    Some(b"\x1b[1;5A")
-                } else if shift {
-                    Some(b"\x1b[1;2A")
-                } else if alt {
-                    Some(b"\x1b[1;3A")
-                } else {
-                    Some(b"\x1b[A")
-                }
-            }
-            VK_DOWN => {
-                if ctrl {
-                    Some(b"\x1b[1;5B")
-                } else if shift {
-                    Some(b"\x1b[1;2B")
-                } else if alt {
-                    Some(b"\x1b[1;3B")
-                } else {
-                    Some(b"\x1b[B")
-                }
-            }
-            VK_RIGHT => {
-                if ctrl {
-                    Some(b"\x1b[1;5C")
-                } else if shift {
-                    Some(b"\x1b[1;2C")
-                } else if alt {
-                    Some(b"\x1b[1;3C")
-                } else {
-                    Some(b"\x1b[C")
-                }
-            }
-            VK_LEFT => {
-                if ctrl {
-                    Some(b"\x1b[1;5D")
-                } else if shift {
-                    Some(b"\x1b[1;2D")
-                } else if alt {
-                    Some(b"\x1b[1;3D")
-                } else {
-                    Some(b"\x1b[D")
-                }
-            }
-            VK_HOME => {
-                if ctrl {
-                    Some(b"\x1b[1;5H")
-                } else if shift {
-                    Some(b"\x1b[1;2H")
-                } else {
-                    Some(b"\x1b[H")
-                }
-            }
-            VK_END => {
-                if ctrl {
-                    Some(b"\x1b[1;5F")
-                } else if shift {
-                    Some(b"\x1b[1;2F")
-                } else {
-                    Some(b"\x1b[F")
-                }
-            }
-            VK_DELETE => Some(b"\x1b[3~"),
-            VK_INSERT => Some(b"\x1b[2~"),
-            VK_PRIOR => Some(b"\x1b[5~"), // Page Up
-            VK_NEXT => Some(b"\x1b[6~"),  // Page Down
-            VK_BACK => Some(b"\x7f"),     // Backspace (DEL)
-            VK_RETURN => Some(b"\r"),     // Enter
-            VK_TAB => Some(b"\t"),        // Tab
-            VK_ESCAPE => Some(b"\x1b"),   // Escape
-            VK_F1 => Some(b"\x1bOP"),
-            VK_F2 => Some(b"\x1bOQ"),
-            VK_F3 => Some(b"\x1bOR"),
-            VK_F4 => Some(b"\x1bOS"),
-            VK_F5 => Some(b"\x1b[15~"),
-            VK_F6 => Some(b"\x1b[17~"),
-            VK_F7 => Some(b"\x1b[18~"),
-            VK_F8 => Some(b"\x1b[19~"),
-            VK_F9 => Some(b"\x1b[20~"),
-            VK_F10 => Some(b"\x1b[21~"),
-            VK_F11 => Some(b"\x1b[23~"),
-            VK_F12 => Some(b"\x1b[24~"),
+        // Cursor keys (DECCKM)
+        let cursor_final_byte = match vk_code {
+            VK_UP => Some(b'A'),
+            VK_DOWN => Some(b'B'),
+            VK_RIGHT => Some(b'C'),
+            VK_LEFT => Some(b'D'),
+            VK_HOME => Some(b'H'),
+            VK_END => Some(b'F'),
+            _ => None,
+        };
+        if let Some(final_byte) = cursor_final_byte {
+            return Some(Self::letter_key_sequence(
+                final_byte,
+                modifier,
+                modes.is_application_cursor_keys,
+            ));
+        }
+
+        // Numeric keypad (DECKPAM)。通常モードでは WM_CHAR による文字入力に任せる
+        if modes.is_application_keypad
+            && !ctrl
+            && !alt
+            && let Some(final_byte) = Self::keypad_final_byte(key)
+        {
+            return Some(vec![0x1B, b'O', final_byte]);
+        }
+
+        // F1〜F4 は修飾キーが無い場合のみ SS3 で送る
+        let function_final_byte = match vk_code {
+            VK_F1 => Some(b'P'),
+            VK_F2 => Some(b'Q'),
+            VK_F3 => Some(b'R'),
+            VK_F4 => Some(b'S'),
             _ => None,
         };
+        if let Some(final_byte) = function_final_byte {
+            return Some(Self::letter_key_sequence(final_byte, modifier, true));
+        }
+
+        let tilde_number = match vk_code {
+            VK_INSERT => Some(2),
+            VK_DELETE => Some(3),
+            VK_PRIOR => Some(5), // Page Up
+            VK_NEXT => Some(6),  // Page Down
+            VK_F5 => Some(15),
+            VK_F6 => Some(17),
+            VK_F7 => Some(18),
+            VK_F8 => Some(19),
+            VK_F9 => Some(20),
+            VK_F10 => Some(21),
+            VK_F11 => Some(23),
+            VK_F12 => Some(24),
+            _ => None,
+        };
+        if let Some(number) = tilde_number {
+            return Some(Self::tilde_key_sequence(number, modifier));
+        }
+
+        // Shift+Tab (Back Tab) -> CSI Z。Shift 以外の修飾キーも伴う場合は CSI 1 ; m Z
+        if vk_code == VK_TAB && shift {
+            return Some(match modifier {
+                Some(2) | None => b"\x1b[Z".to_vec(),
+                Some(m) => format!("\x1b[1;{}Z", m).into_bytes(),
+            });
+        }
 
-        seq.map(|s| s.to_vec())
+        // Special keys。Alt 付きの場合は ESC を前置する
+        let seq: &[u8] = match vk_code {
+            VK_BACK if ctrl => b"\x08", // Ctrl+Backspace (BS)
+            VK_BACK => b"\x7f",         // Backspace (DEL)
+            VK_RETURN => b"\r",         // Enter
+            VK_TAB => b"\t",            // Tab
+            VK_ESCAPE => b"\x1b",       // Escape
+            _ => return None,
+        };
+        if alt {
+            Some([&[0x1B], seq].concat())
+        } else {
+            Some(seq.to_vec())
+        }
     }
 
-    fn translate_mouse(&self, event: MouseEvent) -> Option<Vec<u8>> {
+    fn translate_mouse(&self, event: MouseEvent, encoding: MouseEncoding) -> Option<Vec<u8>> {
         let mut pb = match event.button {
             MouseButton::Left => 0,
             MouseButton::Middle => 1,
@@ -178,6 +304,11 @@ impl KeyTranslatorRepository for VtSequenceTranslatorDomainService {
             MouseButton::WheelRight => 67,
         };
 
+        // SGR 以外の形式は離したボタンを区別できないため、ボタン 3 として送る
+        if event.is_release && encoding != MouseEncoding::Sgr {
+            pb = 3;
+        }
+
         // ドラッグ（移動）フラグの追加
         if event.is_drag || event.button == MouseButton::None {
             pb += 32;
@@ -194,12 +325,34 @@ impl KeyTranslatorRepository for VtSequenceTranslatorDomainService {
             pb += 16;
         }
 
-        let suffix = if event.is_release { 'm' } else { 'M' };
-
-        // SGR 1006: ESC [ < Pb ; Px ; Py M/m
         // 座標は VT 空間では 1-based
-        let seq = format!("\x1b[<{};{};{}{}", pb, event.x + 1, event.y + 1, suffix);
-        Some(seq.into_bytes())
+        let (x, y) = (event.x + 1, event.y + 1);
+        let seq = match encoding {
+            // SGR 1006: ESC [ < Pb ; Px ; Py M/m
+            MouseEncoding::Sgr => {
+                let suffix = if event.is_release { 'm' } else { 'M' };
+                format!("\x1b[<{};{};{}{}", pb, x, y, suffix).into_bytes()
+            }
+            // urxvt 1015: ESC [ Cb ; Px ; Py M
+            MouseEncoding::Urxvt => format!("\x1b[{};{};{}M", pb + 32, x, y).into_bytes(),
+            // ESC [ M Cb Cx Cy。1 バイトに収まらない座標は端に留める
+            MouseEncoding::Default => {
+                let mut seq = b"\x1b[M".to_vec();
+                seq.extend([pb, x.min(223), y.min(223)].map(|value| (value + 32) as u8));
+                seq
+            }
+            // UTF-8 1005: Default の各値を UTF-8 の 1〜2 バイトで送る
+            MouseEncoding::Utf8 => {
+                let mut seq = String::from("\x1b[M");
+                seq.extend(
+                    [pb, x.min(2015), y.min(2015)]
+                        .into_iter()
+                        .filter_map(|value| char::from_u32((value + 32) as u32)),
+                );
+                seq.into_bytes()
+            }
+        };
+        Some(seq)
     }
 }
 
@@ -207,6 +360,7 @@ impl KeyTranslatorRepository for VtSequenceTranslatorDomainService {
 mod tests {
     use super::*;
     use crate::domain::model::input_value::Modifiers;
+    use crate::domain::model::kitty_keyboard_value::KittyKeyboardFlags;
 
     #[test]
     fn test_ctrl_combinations() {
@@ -220,7 +374,10 @@ mod tests {
                 is_alt_pressed: false,
             },
         );
-        assert_eq!(translator.translate(key_a), Some(vec![1]));
+        assert_eq!(
+            translator.translate(key_a, InputModes::default()),
+            Some(vec![1])
+        );
 
         // Ctrl+C -> \x03
         let key_c = InputKey::new(
@@ -231,7 +388,10 @@ mod tests {
                 is_alt_pressed: false,
             },
         );
-        assert_eq!(translator.translate(key_c), Some(vec![3]));
+        assert_eq!(
+            translator.translate(key_c, InputModes::default()),
+            Some(vec![3])
+        );
     }
 
     #[test]
@@ -246,7 +406,10 @@ mod tests {
                 is_alt_pressed: true,
             },
         );
-        assert_eq!(translator.translate(key_ctrl_alt_a), None);
+        assert_eq!(
+            translator.translate(key_ctrl_alt_a, InputModes::default()),
+            None
+        );
     }
 
     #[test]
@@ -261,7 +424,10 @@ mod tests {
                 is_alt_pressed: true,
             },
         );
-        assert_eq!(translator.translate(key_alt_a), Some(vec![0x1B, b'a']));
+        assert_eq!(
+            translator.translate(key_alt_a, InputModes::default()),
+            Some(vec![0x1B, b'a'])
+        );
 
         // Alt+Shift+A -> ESC + A
         let key_alt_shift_a = InputKey::new(
@@ -273,7 +439,7 @@ mod tests {
             },
         );
         assert_eq!(
-            translator.translate(key_alt_shift_a),
+            translator.translate(key_alt_shift_a, InputModes::default()),
             Some(vec![0x1B, b'A'])
         );
 
@@ -286,7 +452,10 @@ mod tests {
                 is_alt_pressed: true,
             },
         );
-        assert_eq!(translator.translate(key_alt_1), Some(vec![0x1B, b'1']));
+        assert_eq!(
+            translator.translate(key_alt_1, InputModes::default()),
+            Some(vec![0x1B, b'1'])
+        );
     }
 
     #[test]
@@ -294,15 +463,24 @@ mod tests {
         let translator = VtSequenceTranslatorDomainService::new();
         // F1 -> \x1bOP
         let f1 = InputKey::new(0x70, Modifiers::none());
-        assert_eq!(translator.translate(f1), Some(b"\x1bOP".to_vec()));
+        assert_eq!(
+            translator.translate(f1, InputModes::default()),
+            Some(b"\x1bOP".to_vec())
+        );
 
         // F5 -> \x1b[15~
         let f5 = InputKey::new(0x74, Modifiers::none());
-        assert_eq!(translator.translate(f5), Some(b"\x1b[15~".to_vec()));
+        assert_eq!(
+            translator.translate(f5, InputModes::default()),
+            Some(b"\x1b[15~".to_vec())
+        );
 
         // F12 -> \x1b[24~
         let f12 = InputKey::new(0x7B, Modifiers::none());
-        assert_eq!(translator.translate(f12), Some(b"\x1b[24~".to_vec()));
+        assert_eq!(
+            translator.translate(f12, InputModes::default()),
+            Some(b"\x1b[24~".to_vec())
+        );
     }
 
     #[test]
@@ -310,7 +488,10 @@ mod tests {
         let translator = VtSequenceTranslatorDomainService::new();
         // Up Arrow
         let up = InputKey::new(0x26, Modifiers::none());
-        assert_eq!(translator.translate(up), Some(b"\x1b[A".to_vec()));
+        assert_eq!(
+            translator.translate(up, InputModes::default()),
+            Some(b"\x1b[A".to_vec())
+        );
 
         // Ctrl+Up Arrow
         let ctrl_up = InputKey::new(
@@ -321,7 +502,10 @@ mod tests {
                 is_alt_pressed: false,
             },
         );
-        assert_eq!(translator.translate(ctrl_up), Some(b"\x1b[1;5A".to_vec()));
+        assert_eq!(
+            translator.translate(ctrl_up, InputModes::default()),
+            Some(b"\x1b[1;5A".to_vec())
+        );
 
         // Alt+Up Arrow
         let alt_up = InputKey::new(
@@ -332,31 +516,49 @@ mod tests {
                 is_alt_pressed: true,
             },
         );
-        assert_eq!(translator.translate(alt_up), Some(b"\x1b[1;3A".to_vec()));
+        assert_eq!(
+            translator.translate(alt_up, InputModes::default()),
+            Some(b"\x1b[1;3A".to_vec())
+        );
 
         // Backspace
         let backspace = InputKey::new(0x08, Modifiers::none());
-        assert_eq!(translator.translate(backspace), Some(vec![0x7f]));
+        assert_eq!(
+            translator.translate(backspace, InputModes::default()),
+            Some(vec![0x7f])
+        );
 
         // Enter
         let enter = InputKey::new(0x0D, Modifiers::none());
-        assert_eq!(translator.translate(enter), Some(vec![0x0D]));
+        assert_eq!(
+            translator.translate(enter, InputModes::default()),
+            Some(vec![0x0D])
+        );
 
         // Tab
         let tab = InputKey::new(0x09, Modifiers::none());
-        assert_eq!(translator.translate(tab), Some(vec![0x09]));
+        assert_eq!(
+            translator.translate(tab, InputModes::default()),
+            Some(vec![0x09])
+        );
 
         // Escape
         let esc = InputKey::new(0x1B, Modifiers::none());
-        assert_eq!(translator.translate(esc), Some(vec![0x1B]));
+        assert_eq!(
+            translator.translate(esc, InputModes::default()),
+            Some(vec![0x1B])
+        );
 
         // Page Up
         let pgup = InputKey::new(0x21, Modifiers::none());
-        assert_eq!(translator.translate(pgup), Some(b"\x1b[5~".to_vec()));
+        assert_eq!(
+            translator.translate(pgup, InputModes::default()),
+            Some(b"\x1b[5~".to_vec())
+        );
 
         // Space
         let space = InputKey::new(0x20, Modifiers::none());
-        assert_eq!(translator.translate(space), None);
+        assert_eq!(translator.translate(space, InputModes::default()), None);
     }
 
     #[test]
@@ -371,7 +573,7 @@ mod tests {
                 is_alt_pressed: false,
             },
         );
-        assert_eq!(translator.translate(shift), None);
+        assert_eq!(translator.translate(shift, InputModes::default()), None);
     }
 
     #[test]
@@ -382,21 +584,21 @@ mod tests {
         // Left Click Press (Cell 10, 5)
         let left_down = MouseEvent::new(MouseButton::Left, 10, 5, modifiers, false, false);
         assert_eq!(
-            translator.translate_mouse(left_down),
+            translator.translate_mouse(left_down, MouseEncoding::Sgr),
             Some(b"\x1b[<0;11;6M".to_vec())
         );
 
         // Left Click Release
         let left_up = MouseEvent::new(MouseButton::Left, 10, 5, modifiers, true, false);
         assert_eq!(
-            translator.translate_mouse(left_up),
+            translator.translate_mouse(left_up, MouseEncoding::Sgr),
             Some(b"\x1b[<0;11;6m".to_vec())
         );
 
         // Right Click Press
         let right_down = MouseEvent::new(MouseButton::Right, 20, 30, modifiers, false, false);
         assert_eq!(
-            translator.translate_mouse(right_down),
+            translator.translate_mouse(right_down, MouseEncoding::Sgr),
             Some(b"\x1b[<2;21;31M".to_vec())
         );
     }
@@ -409,14 +611,14 @@ mod tests {
         // Left Drag
         let left_drag = MouseEvent::new(MouseButton::Left, 11, 5, modifiers, false, true);
         assert_eq!(
-            translator.translate_mouse(left_drag),
+            translator.translate_mouse(left_drag, MouseEncoding::Sgr),
             Some(b"\x1b[<32;12;6M".to_vec())
         );
 
         // Hover (Move without button)
         let hover = MouseEvent::new(MouseButton::None, 15, 20, modifiers, false, false);
         assert_eq!(
-            translator.translate_mouse(hover),
+            translator.translate_mouse(hover, MouseEncoding::Sgr),
             Some(b"\x1b[<35;16;21M".to_vec())
         );
     }
@@ -429,14 +631,14 @@ mod tests {
         // Wheel Up
         let wheel_up = MouseEvent::new(MouseButton::WheelUp, 0, 0, modifiers, false, false);
         assert_eq!(
-            translator.translate_mouse(wheel_up),
+            translator.translate_mouse(wheel_up, MouseEncoding::Sgr),
             Some(b"\x1b[<64;1;1M".to_vec())
         );
 
         // Wheel Down
         let wheel_down = MouseEvent::new(MouseButton::WheelDown, 0, 0, modifiers, false, false);
         assert_eq!(
-            translator.translate_mouse(wheel_down),
+            translator.translate_mouse(wheel_down, MouseEncoding::Sgr),
             Some(b"\x1b[<65;1;1M".to_vec())
         );
     }
@@ -459,7 +661,7 @@ mod tests {
             false,
         );
         assert_eq!(
-            translator.translate_mouse(ctrl_left),
+            translator.translate_mouse(ctrl_left, MouseEncoding::Sgr),
             Some(b"\x1b[<16;1;1M".to_vec())
         );
 
@@ -478,8 +680,349 @@ mod tests {
         );
         // Right(2) + Shift(4) + Alt(8) = 14
         assert_eq!(
-            translator.translate_mouse(shift_alt_right),
+            translator.translate_mouse(shift_alt_right, MouseEncoding::Sgr),
             Some(b"\x1b[<14;6;6M".to_vec())
         );
     }
+
+    #[test]
+    fn test_application_cursor_keys() {
+        let translator = VtSequenceTranslatorDomainService::new();
+        let modes = InputModes {
+            is_application_cursor_keys: true,
+            ..InputModes::default()
+        };
+
+        let up = InputKey::new(0x26, Modifiers::none());
+        assert_eq!(translator.translate(up, modes), Some(b"\x1bOA".to_vec()));
+        let left = InputKey::new(0x25, Modifiers::none());
+        assert_eq!(translator.translate(left, modes), Some(b"\x1bOD".to_vec()));
+        let home = InputKey::new(0x24, Modifiers::none());
+        assert_eq!(translator.translate(home, modes), Some(b"\x1bOH".to_vec()));
+        let end = InputKey::new(0x23, Modifiers::none());
+        assert_eq!(translator.translate(end, modes), Some(b"\x1bOF".to_vec()));
+
+        // 修飾キー付きは CSI 形式のまま
+        let ctrl_up = InputKey::new(
+            0x26,
+            Modifiers {
+                is_ctrl_pressed: true,
+                is_shift_pressed: false,
+                is_alt_pressed: false,
+            },
+        );
+        assert_eq!(
+            translator.translate(ctrl_up, modes),
+            Some(b"\x1b[1;5A".to_vec())
+        );
+
+        // カーソルキー以外は影響を受けない
+        let pgup = InputKey::new(0x21, Modifiers::none());
+        assert_eq!(translator.translate(pgup, modes), Some(b"\x1b[5~".to_vec()));
+    }
+
+    #[test]
+    fn test_application_keypad() {
+        let translator = VtSequenceTranslatorDomainService::new();
+        let modes = InputModes {
+            is_application_keypad: true,
+            ..InputModes::default()
+        };
+
+        let numpad0 = InputKey::new(0x60, Modifiers::none());
+        assert_eq!(
+            translator.translate(numpad0, modes),
+            Some(b"\x1bOp".to_vec())
+        );
+        let numpad9 = InputKey::new(0x69, Modifiers::none());
+        assert_eq!(
+            translator.translate(numpad9, modes),
+            Some(b"\x1bOy".to_vec())
+        );
+        let add = InputKey::new(0x6B, Modifiers::none());
+        assert_eq!(translator.translate(add, modes), Some(b"\x1bOk".to_vec()));
+        let divide = InputKey::new(0x6F, Modifiers::none());
+        assert_eq!(
+            translator.translate(divide, modes),
+            Some(b"\x1bOo".to_vec())
+        );
+
+        // テンキーの Enter (拡張キー) は ESC O M、メインの Enter は CR のまま
+        let keypad_enter = InputKey::new(0x0D, Modifiers::none()).with_key_state(0x1C, true, false);
+        assert_eq!(
+            translator.translate(keypad_enter, modes),
+            Some(b"\x1bOM".to_vec())
+        );
+        let enter = InputKey::new(0x0D, Modifiers::none()).with_key_state(0x1C, false, false);
+        assert_eq!(translator.translate(enter, modes), Some(b"\r".to_vec()));
+        assert_eq!(
+            translator.translate(keypad_enter, InputModes::default()),
+            Some(b"\r".to_vec())
+        );
+
+        // 通常モードでは文字入力 (WM_CHAR) に任せる
+        assert_eq!(translator.translate(numpad0, InputModes::default()), None);
+        // キーパッドモードはカーソルキーに影響しない
+        let up = InputKey::new(0x26, Modifiers::none());
+        assert_eq!(translator.translate(up, modes), Some(b"\x1b[A".to_vec()));
+    }
+
+    #[test]
+    fn test_xterm_modifier_parameter() {
+        let cases: &[(&str, Option<u8>)] = &[
+            ("", None),
+            ("S", Some(2)),
+            ("A", Some(3)),
+            ("SA", Some(4)),
+            ("C", Some(5)),
+            ("CS", Some(6)),
+            ("CA", Some(7)),
+            ("CSA", Some(8)),
+        ];
+        for &(spec, expected) in cases {
+            assert_eq!(
+                Modifiers::from_spec(spec).xterm_parameter(),
+                expected,
+                "{}",
+                spec
+            );
+        }
+    }
+
+    #[test]
+    fn test_modified_special_keys() {
+        let translator = VtSequenceTranslatorDomainService::new();
+        let app_cursor = InputModes {
+            is_application_cursor_keys: true,
+            ..InputModes::default()
+        };
+        let normal = InputModes::default();
+        let cases: &[(u16, &str, InputModes, &[u8])] = &[
+            // 矢印・Home・End
+            (0x26, "CS", normal, b"\x1b[1;6A"),
+            (0x28, "CA", normal, b"\x1b[1;7B"),
+            (0x27, "SA", normal, b"\x1b[1;4C"),
+            (0x25, "CSA", app_cursor, b"\x1b[1;8D"),
+            (0x24, "S", normal, b"\x1b[1;2H"),
+            (0x23, "C", app_cursor, b"\x1b[1;5F"),
+            (0x23, "", app_cursor, b"\x1bOF"),
+            // F1〜F4 (SS3)
+            (0x70, "", normal, b"\x1bOP"),
+            (0x70, "S", normal, b"\x1b[1;2P"),
+            (0x73, "CA", normal, b"\x1b[1;7S"),
+            // ~ で終端するキー
+            (0x2D, "S", normal, b"\x1b[2;2~"),
+            (0x2E, "C", normal, b"\x1b[3;5~"),
+            (0x2E, "", normal, b"\x1b[3~"),
+            (0x21, "A", normal, b"\x1b[5;3~"),
+            (0x22, "CS", normal, b"\x1b[6;6~"),
+            (0x74, "C", normal, b"\x1b[15;5~"),
+            (0x7B, "CSA", normal, b"\x1b[24;8~"),
+            // Tab
+            (0x09, "S", normal, b"\x1b[Z"),
+            (0x09, "CS", normal, b"\x1b[1;6Z"),
+            (0x09, "", normal, b"\t"),
+            // Backspace / Enter / Escape
+            (0x08, "C", normal, b"\x08"),
+            (0x08, "A", normal, b"\x1b\x7f"),
+            (0x0D, "A", normal, b"\x1b\r"),
+            // Ctrl + 記号 / スペース
+            (0x20, "C", normal, b"\x00"),
+            (0x32, "CS", normal, b"\x00"),
+            (0xC0, "C", normal, b"\x00"),
+            (0xDB, "C", normal, b"\x1b"),
+            (0xDC, "C", normal, b"\x1c"),
+            (0xE2, "C", normal, b"\x1c"),
+            (0xDD, "C", normal, b"\x1d"),
+            (0xBF, "C", normal, b"\x1f"),
+        ];
+        for &(vk_code, spec, modes, expected) in cases {
+            let key = InputKey::from_spec(vk_code, spec);
+            assert_eq!(
+                translator.translate(key, modes).as_deref(),
+                Some(expected),
+                "vk={:#04x} modifiers={}",
+                vk_code,
+                spec
+            );
+        }
+    }
+
+    #[test]
+    fn test_unmapped_ctrl_symbols() {
+        let translator = VtSequenceTranslatorDomainService::new();
+        // Ctrl 無しの記号や AltGr (Ctrl+Alt) は文字入力 (WM_CHAR) に任せる
+        for (vk_code, spec) in [(0xDB, ""), (0xDB, "CA"), (0x20, ""), (0xBF, "S")] {
+            let key = InputKey::from_spec(vk_code, spec);
+            assert_eq!(translator.translate(key, InputModes::default()), None);
+        }
+    }
+
+    #[test]
+    fn test_win32_input_mode() {
+        let translator = VtSequenceTranslatorDomainService::new();
+        let modes = InputModes {
+            is_win32_input_mode: true,
+            ..InputModes::default()
+        };
+        let translate = |vk_code: u16, spec: &str, scan_code: u16, is_key_up: bool| {
+            let key =
+                InputKey::from_spec(vk_code, spec).with_key_state(scan_code, false, is_key_up);
+            translator
+                .translate(key, modes)
+                .map(|seq| String::from_utf8(seq).unwrap())
+        };
+        // Ctrl+A は押下・解放の両方を送り、入力文字は 0x01
+        assert_eq!(
+            translate(0x41, "C", 0x1E, false).as_deref(),
+            Some("\x1b[65;30;1;1;8;1_")
+        );
+        assert_eq!(
+            translate(0x41, "C", 0x1E, true).as_deref(),
+            Some("\x1b[65;30;1;0;8;1_")
+        );
+        // Backspace は BS として入力する
+        assert_eq!(
+            translate(0x08, "", 0x0E, false).as_deref(),
+            Some("\x1b[8;14;8;1;0;1_")
+        );
+        // 修飾キー単独の押下も送信する
+        assert_eq!(
+            translate(0x10, "S", 0x2A, false).as_deref(),
+            Some("\x1b[16;42;0;1;16;1_")
+        );
+        // 文字入力も押下・解放の両方を送り、入力文字はキーボードレイアウトから求めた文字を使う
+        let key = InputKey::from_spec(0x41, "S")
+            .with_key_state(0x1E, false, false)
+            .with_unicode_char(b'A' as u16);
+        assert_eq!(
+            translator.translate(key, modes),
+            Some(b"\x1b[65;30;65;1;16;1_".to_vec())
+        );
+        assert_eq!(
+            translate(0x41, "", 0x1E, true).as_deref(),
+            Some("\x1b[65;30;0;0;0;1_")
+        );
+        // IME が処理するキーは WM_CHAR (WM_IME_COMPOSITION) に任せる
+        assert_eq!(translate(0xE5, "", 0x1E, false), None);
+    }
+
+    #[test]
+    fn test_win32_input_mode_release_after_modifier() {
+        let translator = VtSequenceTranslatorDomainService::new();
+        let modes = InputModes {
+            is_win32_input_mode: true,
+            ..InputModes::default()
+        };
+        let translate = |vk_code: u16, spec: &str, scan_code: u16, is_key_up: bool| {
+            let key =
+                InputKey::from_spec(vk_code, spec).with_key_state(scan_code, false, is_key_up);
+            translator
+                .translate(key, modes)
+                .map(|seq| String::from_utf8(seq).unwrap())
+        };
+        // Ctrl+A を押し、Ctrl を先に離してから A を離す
+        let events = [
+            translate(0x11, "C", 0x1D, false),
+            translate(0x41, "C", 0x1E, false),
+            translate(0x11, "C", 0x1D, true),
+            translate(0x41, "", 0x1E, true),
+        ];
+        assert_eq!(
+            events.map(|e| e.unwrap()),
+            [
+                "\x1b[17;29;0;1;8;1_",
+                "\x1b[65;30;1;1;8;1_",
+                "\x1b[17;29;0;0;8;1_",
+                "\x1b[65;30;0;0;0;1_",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_key_up_is_ignored_without_win32_input_mode() {
+        let translator = VtSequenceTranslatorDomainService::new();
+        let key = InputKey::new(0x26, Modifiers::none()).with_key_state(0x48, true, true);
+        assert_eq!(translator.translate(key, InputModes::default()), None);
+    }
+
+    #[test]
+    fn test_kitty_keyboard_falls_back_to_legacy() {
+        let translator = VtSequenceTranslatorDomainService::new();
+        let modes = InputModes {
+            kitty_keyboard_flags: KittyKeyboardFlags::from_bits(KittyKeyboardFlags::DISAMBIGUATE),
+            ..InputModes::default()
+        };
+        assert_eq!(
+            translator.translate(InputKey::from_spec(0x41, "C"), modes),
+            Some(b"\x1b[97;5u".to_vec())
+        );
+        // CSI u を送らないキーは従来通り DECCKM に従う
+        let modes = InputModes {
+            is_application_cursor_keys: true,
+            ..modes
+        };
+        assert_eq!(
+            translator.translate(InputKey::new(0x26, Modifiers::none()), modes),
+            Some(b"\x1bOA".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_mouse_legacy_encodings() {
+        let translator = VtSequenceTranslatorDomainService::new();
+        let left_down = MouseEvent::new(
+            MouseButton::Left,
+            10,
+            5,
+            Modifiers::from_spec("C"),
+            false,
+            false,
+        );
+        let left_up = MouseEvent::new(MouseButton::Left, 10, 5, Modifiers::none(), true, false);
+
+        assert_eq!(
+            translator.translate_mouse(left_down, MouseEncoding::Default),
+            Some(vec![0x1B, b'[', b'M', 32 + 16, 32 + 11, 32 + 6])
+        );
+        // 解放はボタン 3 として送る
+        assert_eq!(
+            translator.translate_mouse(left_up, MouseEncoding::Default),
+            Some(b"\x1b[M#+&".to_vec())
+        );
+        assert_eq!(
+            translator.translate_mouse(left_down, MouseEncoding::Urxvt),
+            Some(b"\x1b[48;11;6M".to_vec())
+        );
+        assert_eq!(
+            translator.translate_mouse(left_up, MouseEncoding::Urxvt),
+            Some(b"\x1b[35;11;6M".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_mouse_large_coordinates() {
+        let translator = VtSequenceTranslatorDomainService::new();
+        let far = MouseEvent::new(MouseButton::Left, 299, 0, Modifiers::none(), false, false);
+
+        // 1 バイトで表せる 223 列目に留める
+        assert_eq!(
+            translator.translate_mouse(far, MouseEncoding::Default),
+            Some(vec![0x1B, b'[', b'M', 32, 255, 33])
+        );
+        // UTF-8 では 300 + 32 = U+014C を 2 バイトで送る
+        assert_eq!(
+            translator.translate_mouse(far, MouseEncoding::Utf8),
+            Some("\x1b[M \u{14C}!".as_bytes().to_vec())
+        );
+        let farther = MouseEvent::new(MouseButton::Left, 2999, 0, Modifiers::none(), false, false);
+        assert_eq!(
+            translator.translate_mouse(farther, MouseEncoding::Utf8),
+            Some("\x1b[M \u{7FF}!".as_bytes().to_vec())
+        );
+        assert_eq!(
+            translator.translate_mouse(far, MouseEncoding::Urxvt),
+            Some(b"\x1b[32;300;1M".to_vec())
+        );
+    }
 }
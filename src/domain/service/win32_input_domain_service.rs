@@ -0,0 +1,109 @@
+use crate::domain::model::input_value::InputKey;
+
+// dwControlKeyState のフラグ (wincon.h)。Domain 層が windows クレートに依存しないよう再定義している。
+const RIGHT_ALT_PRESSED: u32 = 0x0001;
+const LEFT_ALT_PRESSED: u32 = 0x0002;
+const RIGHT_CTRL_PRESSED: u32 = 0x0004;
+const LEFT_CTRL_PRESSED: u32 = 0x0008;
+const SHIFT_PRESSED: u32 = 0x0010;
+const ENHANCED_KEY: u32 = 0x0100;
+
+/// win32-input-mode (DECSET 9001) のキーイベントを組み立てるドメインサービス
+/// ConPTY は `CSI Vk ; Sc ; Uc ; Kd ; Cs ; Rc _` を Win32 の KEY_EVENT_RECORD に復元して
+/// コンソールアプリケーションへ渡す。
+pub(crate) struct Win32InputDomainService;
+
+impl Win32InputDomainService {
+    /// キーイベントを `CSI Vk;Sc;Uc;Kd;Cs;Rc _` に変換する。
+    /// `unicode_char` はキーが入力する文字 (UTF-16)。文字を伴わないキーでは 0 を指定する。
+    pub(crate) fn encode(key: InputKey, unicode_char: u16) -> Vec<u8> {
+        let key_down = if key.is_key_up { 0 } else { 1 };
+        format!(
+            "\x1b[{};{};{};{};{};1_",
+            key.vk_code,
+            key.scan_code,
+            unicode_char,
+            key_down,
+            Self::control_key_state(key)
+        )
+        .into_bytes()
+    }
+
+    /// Ctrl / Alt は右側のキーが押されていれば右側、それ以外は左側として送る。
+    /// AltGr は左 Ctrl + 右 Alt として届くため、アプリケーションは Ctrl+Alt の組み合わせと区別できる。
+    fn control_key_state(key: InputKey) -> u32 {
+        let mut state = 0;
+        if key.modifiers.is_shift_pressed {
+            state |= SHIFT_PRESSED;
+        }
+        if key.modifiers.is_ctrl_pressed {
+            state |= if key.is_right_ctrl_pressed {
+                RIGHT_CTRL_PRESSED
+            } else {
+                LEFT_CTRL_PRESSED
+            };
+        }
+        if key.modifiers.is_alt_pressed {
+            state |= if key.is_right_alt_pressed {
+                RIGHT_ALT_PRESSED
+            } else {
+                LEFT_ALT_PRESSED
+            };
+        }
+        if key.is_extended {
+            state |= ENHANCED_KEY;
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::input_value::Modifiers;
+
+    #[test]
+    fn test_encode_key_down_and_up() {
+        // Ctrl+Shift+Right (拡張キー)
+        let key = InputKey::new(
+            0x27,
+            Modifiers {
+                is_ctrl_pressed: true,
+                is_shift_pressed: true,
+                is_alt_pressed: false,
+            },
+        )
+        .with_key_state(0x4D, true, false);
+        assert_eq!(
+            Win32InputDomainService::encode(key, 0),
+            b"\x1b[39;77;0;1;280;1_".to_vec()
+        );
+
+        let key = InputKey::new(0x41, Modifiers::none()).with_key_state(0x1E, false, true);
+        assert_eq!(
+            Win32InputDomainService::encode(key, b'a' as u16),
+            b"\x1b[65;30;97;0;0;1_".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_right_ctrl_and_alt() {
+        // AltGr+Q (ドイツ語配列の @) は左 Ctrl + 右 Alt
+        let key = InputKey::from_spec(0x51, "CA")
+            .with_key_state(0x10, false, false)
+            .with_right_modifiers(false, true);
+        assert_eq!(
+            Win32InputDomainService::encode(key, b'@' as u16),
+            b"\x1b[81;16;64;1;9;1_".to_vec()
+        );
+
+        // 右 Ctrl + 左 Alt
+        let key = InputKey::from_spec(0x41, "CA")
+            .with_key_state(0x1E, false, false)
+            .with_right_modifiers(true, false);
+        assert_eq!(
+            Win32InputDomainService::encode(key, 0),
+            b"\x1b[65;30;0;1;6;1_".to_vec()
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use crate::domain::model::window_id_value::WindowId;
+use windows::Win32::Foundation::{GlobalFree, HANDLE, HGLOBAL, HWND};
+use windows::Win32::System::DataExchange::{
+    CloseClipboard, EmptyClipboard, GetClipboardData, IsClipboardFormatAvailable, OpenClipboard,
+    SetClipboardData,
+};
+use windows::Win32::System::Memory::{GMEM_MOVEABLE, GlobalAlloc, GlobalLock, GlobalUnlock};
+
+/// Unicode テキストのクリップボード形式 (winuser.h の CF_UNICODETEXT)
+const CF_UNICODETEXT: u32 = 13;
+
+/// Win32 クリップボードへのテキスト読み書きをカプセル化するドライバ
+pub(crate) struct ClipboardGuiDriver;
+
+impl ClipboardGuiDriver {
+    /// クリップボードのテキストを取得する。テキストが無い場合は None を返す。
+    pub(crate) fn get_text(window_id: WindowId) -> Option<String> {
+        let hwnd = HWND(window_id.0 as _);
+        // SAFETY: OpenClipboard に成功した場合のみ操作を行い、必ず CloseClipboard する。
+        // GetClipboardData のハンドルはクリップボードが所有しており、ロック中のみ参照する。
+        unsafe {
+            IsClipboardFormatAvailable(CF_UNICODETEXT).ok()?;
+            if let Err(e) = OpenClipboard(Some(hwnd)) {
+                log::error!("OpenClipboard failed: {:?}", e);
+                return None;
+            }
+            let text = GetClipboardData(CF_UNICODETEXT).ok().and_then(|handle| {
+                let hglobal = HGLOBAL(handle.0);
+                let ptr = GlobalLock(hglobal) as *const u16;
+                if ptr.is_null() {
+                    return None;
+                }
+                let mut len = 0;
+                while *ptr.add(len) != 0 {
+                    len += 1;
+                }
+                let text = String::from_utf16_lossy(std::slice::from_raw_parts(ptr, len));
+                let _ = GlobalUnlock(hglobal);
+                Some(text)
+            });
+            let _ = CloseClipboard();
+            text
+        }
+    }
+
+    /// クリップボードにテキストを設定する。
+    pub(crate) fn set_text(window_id: WindowId, text: &str) -> bool {
+        let hwnd = HWND(window_id.0 as _);
+        let wide_text: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+        let byte_len = wide_text.len() * std::mem::size_of::<u16>();
+        // SAFETY: 確保したグローバルメモリへは確保したサイズの範囲でのみ書き込む。
+        // SetClipboardData に成功した後はメモリの所有権がシステムへ移るため解放しない。
+        unsafe {
+            let hglobal = match GlobalAlloc(GMEM_MOVEABLE, byte_len) {
+                Ok(h) => h,
+                Err(e) => {
+                    log::error!("GlobalAlloc failed: {:?}", e);
+                    return false;
+                }
+            };
+            let ptr = GlobalLock(hglobal) as *mut u16;
+            if ptr.is_null() {
+                let _ = GlobalFree(Some(hglobal));
+                return false;
+            }
+            std::ptr::copy_nonoverlapping(wide_text.as_ptr(), ptr, wide_text.len());
+            let _ = GlobalUnlock(hglobal);
+
+            if let Err(e) = OpenClipboard(Some(hwnd)) {
+                log::error!("OpenClipboard failed: {:?}", e);
+                let _ = GlobalFree(Some(hglobal));
+                return false;
+            }
+            let _ = EmptyClipboard();
+            let result = SetClipboardData(CF_UNICODETEXT, Some(HANDLE(hglobal.0)));
+            let _ = CloseClipboard();
+            match result {
+                Ok(_) => true,
+                Err(e) => {
+                    log::error!("SetClipboardData failed: {:?}", e);
+                    let _ = GlobalFree(Some(hglobal));
+                    false
+                }
+            }
+        }
+    }
+}
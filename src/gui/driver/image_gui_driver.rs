@@ -0,0 +1,86 @@
+use crate::domain::model::sixel_image_value::SixelImage;
+use std::ffi::c_void;
+use windows::Win32::Foundation::{COLORREF, RECT};
+use windows::Win32::Graphics::Gdi::{
+    BI_RGB, BITMAPINFO, BITMAPINFOHEADER, COLORONCOLOR, DIB_RGB_COLORS, HDC, SRCCOPY,
+    SetStretchBltMode, StretchDIBits,
+};
+
+/// Sixel 画像を GDI で描画するドライバ
+pub(crate) struct ImageGuiDriver;
+
+impl ImageGuiDriver {
+    /// 画像の `src` 矩形 (left, top, right, bottom) を `dest` へ拡大縮小して描画する。
+    /// GDI の StretchDIBits はアルファ値を扱わないため、透過部分は `background` と合成しておく。
+    pub(crate) fn draw(
+        hdc: HDC,
+        dest: &RECT,
+        image: &SixelImage,
+        src: (usize, usize, usize, usize),
+        background: COLORREF,
+    ) {
+        let (left, top, right, bottom) = src;
+        if right <= left || bottom <= top {
+            return;
+        }
+        let (width, height) = (right - left, bottom - top);
+        let bg_r = background.0 & 0xFF;
+        let bg_g = (background.0 >> 8) & 0xFF;
+        let bg_b = (background.0 >> 16) & 0xFF;
+
+        // RGBA を DIB の BGRx に並べ替える
+        let stride = image.get_width() * 4;
+        let mut bits = Vec::with_capacity(width * height * 4);
+        for row in image
+            .get_pixels()
+            .chunks_exact(stride)
+            .skip(top)
+            .take(height)
+        {
+            for pixel in row[left * 4..right * 4].chunks_exact(4) {
+                let alpha = pixel[3] as u32;
+                let blend = |c: u8, bg: u32| ((c as u32 * alpha + bg * (255 - alpha)) / 255) as u8;
+                bits.extend_from_slice(&[
+                    blend(pixel[2], bg_b),
+                    blend(pixel[1], bg_g),
+                    blend(pixel[0], bg_r),
+                    0,
+                ]);
+            }
+        }
+
+        let info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width as i32,
+                // 負の高さでトップダウンの DIB として扱う
+                biHeight: -(height as i32),
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        // SAFETY: bits は info で宣言した幅・高さ・32bpp の画素をすべて保持しており、
+        // StretchDIBits の呼び出し中は有効である。
+        unsafe {
+            SetStretchBltMode(hdc, COLORONCOLOR);
+            StretchDIBits(
+                hdc,
+                dest.left,
+                dest.top,
+                dest.right - dest.left,
+                dest.bottom - dest.top,
+                0,
+                0,
+                width as i32,
+                height as i32,
+                Some(bits.as_ptr() as *const c_void),
+                &info,
+                DIB_RGB_COLORS,
+                SRCCOPY,
+            );
+        }
+    }
+}
@@ -1,7 +1,10 @@
+pub(crate) mod clipboard_gui_driver;
 pub(crate) mod config_gui_driver;
+pub(crate) mod image_gui_driver;
 pub(crate) mod ime_gui_driver;
 pub(crate) mod keyboard_gui_driver;
 pub(crate) mod resource;
 pub(crate) mod scroll_gui_driver;
 pub(crate) mod terminal_gui_driver;
+pub(crate) mod text_decoration_gui_driver;
 pub(crate) mod window_gui_driver;
@@ -1,3 +1,4 @@
+use crate::domain::model::wheel_scroll_value::WheelScroll;
 use crate::domain::model::window_id_value::WindowId;
 use crate::gui::resolver::terminal_window_resolver::get_terminal_data;
 use std::mem::size_of;
@@ -87,6 +88,8 @@ pub struct ScrollGuiDriver {
     pub max: i32,
     pub page: u32,
     pub pos: i32,
+    /// 1 行に満たなかったホイールの回転量
+    wheel: WheelScroll,
 }
 
 impl ScrollGuiDriver {
@@ -96,6 +99,7 @@ impl ScrollGuiDriver {
             max: 0,
             page: 0,
             pos: 0,
+            wheel: WheelScroll::new(),
         }
     }
 
@@ -128,12 +132,12 @@ impl ScrollGuiDriver {
         }
     }
 
-    pub fn handle_mousewheel(&self, wparam: usize) -> ScrollAction {
+    pub fn handle_mousewheel(&mut self, wparam: usize) -> ScrollAction {
         let z_delta = (wparam >> 16) as i16;
         // 3 lines per notch
-        let lines = (z_delta as f32 / 120.0 * 3.0) as isize;
+        let lines = self.wheel.take_lines(z_delta, 3);
         if lines != 0 {
-            ScrollAction::ScrollBy(lines)
+            ScrollAction::ScrollBy(lines as isize)
         } else {
             ScrollAction::None
         }
@@ -209,7 +213,7 @@ mod tests {
 
     #[test]
     fn test_handle_mousewheel() {
-        let manager = ScrollGuiDriver::new();
+        let mut manager = ScrollGuiDriver::new();
 
         // Delta 120 (1 notch up) -> 3 lines
         let wparam = (120i16 as u16 as usize) << 16;
@@ -220,5 +224,13 @@ mod tests {
         let wparam = ((-120i16) as u16 as usize) << 16;
         let action = manager.handle_mousewheel(wparam);
         assert_eq!(action, ScrollAction::ScrollBy(-3));
+
+        // Delta -20 x 2 -> remainder carried over to the second event
+        let wparam = ((-20i16) as u16 as usize) << 16;
+        assert_eq!(manager.handle_mousewheel(wparam), ScrollAction::None);
+        assert_eq!(
+            manager.handle_mousewheel(wparam),
+            ScrollAction::ScrollBy(-1)
+        );
     }
 }
@@ -1,8 +1,14 @@
+use crate::domain::model::color_palette_entity::{ColorPaletteEntity, DynamicColor};
 use crate::domain::model::terminal_buffer_entity::{
     CursorStyle, TerminalBufferEntity, TerminalColor,
 };
+use crate::domain::model::terminal_line_entity::TerminalLine;
+use crate::domain::model::terminal_types_entity::{TerminalAttribute, UnderlineStyle};
 use crate::gui::common::points_to_pixels_from_hdc;
+use crate::gui::driver::image_gui_driver::ImageGuiDriver;
+use crate::gui::driver::text_decoration_gui_driver::TextDecorationGuiDriver;
 use std::collections::HashMap;
+use std::sync::Arc;
 use unicode_width::UnicodeWidthStr;
 use windows::Win32::Foundation::{COLORREF, RECT, SIZE};
 use windows::Win32::Graphics::Gdi::{
@@ -41,8 +47,7 @@ unsafe impl Sync for SendHFONT {}
 
 const STYLE_BOLD: u32 = 1 << 0;
 const STYLE_ITALIC: u32 = 1 << 1;
-const STYLE_UNDERLINE: u32 = 1 << 2;
-const STYLE_STRIKEOUT: u32 = 1 << 3;
+const STYLE_STRIKEOUT: u32 = 1 << 2;
 
 const HGDI_ERROR_VALUE: isize = -1;
 
@@ -58,7 +63,7 @@ impl Drop for CreatedDcGuard {
     }
 }
 
-struct GdiObjectGuard(HGDIOBJ);
+pub(crate) struct GdiObjectGuard(pub(crate) HGDIOBJ);
 impl Drop for GdiObjectGuard {
     fn drop(&mut self) {
         if !self.0.0.is_null() {
@@ -102,6 +107,10 @@ struct RenderContext {
 pub struct TerminalGuiDriver {
     fonts: HashMap<u32, SendHFONT>,
     pub(crate) metrics: Option<TerminalMetrics>,
+    /// 点滅属性 (SGR 5) の文字を表示する期間か
+    is_blink_visible: bool,
+    /// 直前の描画で点滅属性の文字を描いたか
+    has_blinking_cells: bool,
 }
 
 impl Default for TerminalGuiDriver {
@@ -115,6 +124,8 @@ impl TerminalGuiDriver {
         Self {
             fonts: HashMap::new(),
             metrics: None,
+            is_blink_visible: true,
+            has_blinking_cells: false,
         }
     }
 
@@ -133,6 +144,21 @@ impl TerminalGuiDriver {
         log::info!("TerminalGuiDriver: All cached font handles and metrics cleared");
     }
 
+    /// 直前の描画に点滅属性の文字が含まれていたか
+    pub fn has_blinking_cells(&self) -> bool {
+        self.has_blinking_cells
+    }
+
+    /// 点滅属性の文字の表示・非表示を切り替える
+    pub fn toggle_blink_phase(&mut self) {
+        self.is_blink_visible = !self.is_blink_visible;
+    }
+
+    /// 点滅属性の文字を表示状態に戻す
+    pub fn reset_blink_phase(&mut self) {
+        self.is_blink_visible = true;
+    }
+
     pub fn get_metrics(&self) -> Option<&TerminalMetrics> {
         self.metrics.as_ref()
     }
@@ -230,11 +256,6 @@ impl TerminalGuiDriver {
                 } else {
                     0
                 },
-                lfUnderline: if (style_mask & STYLE_UNDERLINE) != 0 {
-                    1
-                } else {
-                    0
-                },
                 lfStrikeOut: if (style_mask & STYLE_STRIKEOUT) != 0 {
                     1
                 } else {
@@ -275,50 +296,83 @@ impl TerminalGuiDriver {
         &self,
         color: &TerminalColor,
         is_background: bool,
-        theme: &crate::domain::model::color_theme_value::ColorTheme,
+        palette: &ColorPaletteEntity,
     ) -> COLORREF {
-        match color {
-            TerminalColor::Default => {
-                let rgb = if is_background {
-                    &theme.default_bg
-                } else {
-                    &theme.default_fg
-                };
-                Self::rgb_to_colorref(rgb)
-            }
-            TerminalColor::Ansi(n) => {
-                let idx = if *n < 16 { *n as usize } else { 15 };
-                Self::rgb_to_colorref(&theme.ansi_palette[idx])
-            }
-            TerminalColor::Xterm(n) => match *n {
-                0..=15 => Self::rgb_to_colorref(&theme.ansi_palette[*n as usize]),
-                16..=231 => {
-                    let idx = *n - 16;
-                    let r = if (idx / 36) > 0 {
-                        (idx / 36) * 40 + 55
-                    } else {
-                        0
-                    };
-                    let g = if ((idx % 36) / 6) > 0 {
-                        ((idx % 36) / 6) * 40 + 55
-                    } else {
-                        0
-                    };
-                    let b = if (idx % 6) > 0 {
-                        (idx % 6) * 40 + 55
-                    } else {
-                        0
-                    };
-                    COLORREF((r as u32) | ((g as u32) << 8) | ((b as u32) << 16))
-                }
-                232..=255 => {
-                    let val = (*n - 232) * 10 + 8;
-                    COLORREF((val as u32) | ((val as u32) << 8) | ((val as u32) << 16))
+        Self::rgb_to_colorref(&palette.resolve(color, is_background))
+    }
+
+    /// 下線・上線を描画する。下線色が指定されていない場合は前景色を使用する。
+    fn draw_decorations(
+        &self,
+        hdc: HDC,
+        run_rect: &RECT,
+        attr: &TerminalAttribute,
+        fg: COLORREF,
+        palette: &ColorPaletteEntity,
+    ) {
+        // OSC 8 のハイパーリンクはクリック可能であることを示すため下線で描画する
+        let underline = match attr.underline {
+            UnderlineStyle::None if attr.hyperlink_id.is_some() => UnderlineStyle::Single,
+            style => style,
+        };
+        let underline_color = match attr.underline_color {
+            TerminalColor::Default => fg,
+            ref color => self.color_to_colorref(color, false, palette),
+        };
+        TextDecorationGuiDriver::draw_underline(hdc, run_rect, underline, underline_color);
+        if attr.is_overline {
+            TextDecorationGuiDriver::draw_overline(hdc, run_rect, fg);
+        }
+    }
+
+    /// 行内の Sixel 画像の区画を描画する。
+    /// 同じ画像の同じ段で横に連続する区画はまとめて 1 回で描画する。
+    fn draw_images(
+        &self,
+        hdc: HDC,
+        line: &TerminalLine,
+        y: i32,
+        base_width: i32,
+        char_height: i32,
+        palette: &ColorPaletteEntity,
+    ) {
+        let mut col = 0;
+        while col < line.len() {
+            let Some(first) = line[col].image.as_ref() else {
+                col += 1;
+                continue;
+            };
+            let mut end = col + 1;
+            while let Some(next) = line.get(end).and_then(|c| c.image.as_ref()) {
+                let prev = line[end - 1].image.as_ref().unwrap();
+                if !Arc::ptr_eq(&next.image, &first.image)
+                    || next.row != first.row
+                    || next.column != prev.column + 1
+                {
+                    break;
                 }
-            },
-            TerminalColor::Rgb(r, g, b) => {
-                COLORREF((*r as u32) | ((*g as u32) << 8) | ((*b as u32) << 16))
+                end += 1;
             }
+            let (left, top, _, bottom) = first.source_rect();
+            let (_, _, right, _) = line[end - 1].image.as_ref().unwrap().source_rect();
+            let (cell_width, cell_height) = first.cell_size;
+            // 画像の端で切り詰められた区画は、セルの大きさに対する比率で描画する
+            let dest = RECT {
+                left: col as i32 * base_width,
+                top: y,
+                right: col as i32 * base_width
+                    + ((right - left) as i32 * base_width) / cell_width.max(1) as i32,
+                bottom: y + ((bottom - top) as i32 * char_height) / cell_height.max(1) as i32,
+            };
+            let background = self.color_to_colorref(&line[col].attribute.bg, true, palette);
+            ImageGuiDriver::draw(
+                hdc,
+                &dest,
+                &first.image,
+                (left, top, right, bottom),
+                background,
+            );
+            col = end;
         }
     }
 
@@ -328,7 +382,6 @@ impl TerminalGuiDriver {
         client_rect: &RECT,
         buffer: &TerminalBufferEntity,
         composition: Option<&CompositionInfo>,
-        theme: &crate::domain::model::color_theme_value::ColorTheme,
         config: &crate::domain::model::terminal_config_value::TerminalConfig,
     ) {
         let width = client_rect.right - client_rect.left;
@@ -353,7 +406,7 @@ impl TerminalGuiDriver {
             let h_old_bm = SelectObject(h_mem_dc, HGDIOBJ(h_bm.0));
             let _bm_select_guard = SelectedObjectGuard::new(h_mem_dc, h_old_bm);
 
-            self.render_internal(h_mem_dc, client_rect, buffer, composition, theme, config);
+            self.render_internal(h_mem_dc, client_rect, buffer, composition, config);
 
             let _ = BitBlt(
                 hdc,
@@ -375,9 +428,10 @@ impl TerminalGuiDriver {
         client_rect: &RECT,
         buffer: &TerminalBufferEntity,
         composition: Option<&CompositionInfo>,
-        theme: &crate::domain::model::color_theme_value::ColorTheme,
         config: &crate::domain::model::terminal_config_value::TerminalConfig,
     ) {
+        let palette = buffer.get_palette();
+        self.has_blinking_cells = false;
         let width = client_rect.right - client_rect.left;
         let height = client_rect.bottom - client_rect.top;
         let relative_rect = RECT {
@@ -387,7 +441,7 @@ impl TerminalGuiDriver {
             bottom: height,
         };
 
-        let bg_colorref = self.color_to_colorref(&TerminalColor::Default, true, theme);
+        let bg_colorref = self.color_to_colorref(&TerminalColor::Default, true, palette);
         // SAFETY: 背景塗りつぶし用のブラシ作成と描画。
         unsafe {
             let h_brush = CreateSolidBrush(bg_colorref);
@@ -462,9 +516,6 @@ impl TerminalGuiDriver {
                             if start_attr.is_italic {
                                 style_mask |= STYLE_ITALIC;
                             }
-                            if start_attr.is_underline {
-                                style_mask |= STYLE_UNDERLINE;
-                            }
                             if start_attr.is_strikethrough {
                                 style_mask |= STYLE_STRIKEOUT;
                             }
@@ -473,8 +524,8 @@ impl TerminalGuiDriver {
                             let old_font = SelectObject(hdc, HGDIOBJ(h_font.0));
                             let _font_guard = SelectedObjectGuard::new(hdc, old_font);
 
-                            let mut fg = self.color_to_colorref(&start_attr.fg, false, theme);
-                            let mut bg = self.color_to_colorref(&start_attr.bg, true, theme);
+                            let mut fg = self.color_to_colorref(&start_attr.fg, false, palette);
+                            let mut bg = self.color_to_colorref(&start_attr.bg, true, palette);
                             if start_attr.is_inverse {
                                 std::mem::swap(&mut fg, &mut bg);
                             }
@@ -482,11 +533,20 @@ impl TerminalGuiDriver {
                                 && start_attr.bg != TerminalColor::Default
                                 && start_attr.fg == TerminalColor::Default
                             {
-                                fg = self.color_to_colorref(&TerminalColor::Default, true, theme);
+                                fg = self.color_to_colorref(&TerminalColor::Default, true, palette);
                             }
                             if start_attr.is_dim {
                                 fg = Self::apply_dim_effect(fg);
                             }
+                            if start_attr.is_blink {
+                                self.has_blinking_cells = true;
+                            }
+                            // 非表示 (SGR 8) と点滅の消灯期間は文字を背景色で描く
+                            let is_text_hidden = start_attr.is_concealed
+                                || (start_attr.is_blink && !self.is_blink_visible);
+                            if is_text_hidden {
+                                fg = bg;
+                            }
 
                             SetTextColor(hdc, fg);
                             SetBkColor(hdc, bg);
@@ -506,9 +566,13 @@ impl TerminalGuiDriver {
                                 wide_run.len() as u32,
                                 Some(run_dx.as_ptr()),
                             );
+                            if !is_text_hidden {
+                                self.draw_decorations(hdc, &run_rect, start_attr, fg, palette);
+                            }
                         }
                         x_offset += run_pixel_width;
                     }
+                    self.draw_images(hdc, line, current_y, base_width, char_height, palette);
                 }
 
                 if viewport_offset == 0 && visual_row == cursor_y {
@@ -521,7 +585,7 @@ impl TerminalGuiDriver {
                             char_height,
                             base_width,
                         };
-                        self.render_composition(hdc, &ctx, comp, theme, config);
+                        self.render_composition(hdc, &ctx, comp, palette, config);
                     } else if buffer.is_cursor_visible() {
                         let style = buffer.get_cursor_style();
                         let dw = if let Some(line) = buffer.get_line_at_visual_row(visual_row) {
@@ -552,7 +616,54 @@ impl TerminalGuiDriver {
                                 bottom: current_y + char_height,
                             },
                         };
-                        let _ = InvertRect(hdc, &rect);
+                        match palette.get_cursor_override() {
+                            // OSC 12 で色が指定されている場合はその色で塗り、ブロックでは文字を背景色で重ねる
+                            Some(cursor_color) => {
+                                let cursor_ref = Self::rgb_to_colorref(&cursor_color);
+                                let is_block = matches!(
+                                    style,
+                                    CursorStyle::BlinkingBlock | CursorStyle::SteadyBlock
+                                );
+                                let cell_text = buffer
+                                    .get_line_at_visual_row(visual_row)
+                                    .and_then(|line| line.get(safe_x))
+                                    .map(|cell| cell.text.clone());
+                                if let (true, Some(text)) = (is_block, cell_text) {
+                                    let wide_text: Vec<u16> = text.encode_utf16().collect();
+                                    let h_font = self.get_font_for_style(hdc, 0, config);
+                                    let old_font = SelectObject(hdc, HGDIOBJ(h_font.0));
+                                    let _font_guard = SelectedObjectGuard::new(hdc, old_font);
+                                    SetBkColor(hdc, cursor_ref);
+                                    SetTextColor(
+                                        hdc,
+                                        self.color_to_colorref(
+                                            &TerminalColor::Default,
+                                            true,
+                                            palette,
+                                        ),
+                                    );
+                                    let _ = ExtTextOutW(
+                                        hdc,
+                                        rect.left,
+                                        rect.top,
+                                        ETO_OPTIONS(ETO_OPAQUE.0),
+                                        Some(&rect),
+                                        PCWSTR(wide_text.as_ptr()),
+                                        wide_text.len() as u32,
+                                        None,
+                                    );
+                                } else {
+                                    let h_brush = CreateSolidBrush(cursor_ref);
+                                    if !h_brush.0.is_null() {
+                                        let _brush_guard = GdiObjectGuard(HGDIOBJ(h_brush.0));
+                                        FillRect(hdc, &rect, h_brush);
+                                    }
+                                }
+                            }
+                            None => {
+                                let _ = InvertRect(hdc, &rect);
+                            }
+                        }
                     }
                 }
                 current_y += char_height;
@@ -565,7 +676,7 @@ impl TerminalGuiDriver {
         hdc: HDC,
         ctx: &RenderContext,
         comp: &CompositionInfo,
-        theme: &crate::domain::model::color_theme_value::ColorTheme,
+        palette: &ColorPaletteEntity,
         config: &crate::domain::model::terminal_config_value::TerminalConfig,
     ) {
         let comp_wide: Vec<u16> = comp.text.encode_utf16().collect();
@@ -593,8 +704,12 @@ impl TerminalGuiDriver {
             let old_font = SelectObject(hdc, HGDIOBJ(h_font.0));
             let _font_guard = SelectedObjectGuard::new(hdc, old_font);
 
-            SetBkColor(hdc, Self::rgb_to_colorref(&theme.default_bg));
-            SetTextColor(hdc, Self::rgb_to_colorref(&theme.default_fg));
+            let fg = Self::rgb_to_colorref(&palette.get_dynamic(DynamicColor::Foreground));
+            SetBkColor(
+                hdc,
+                Self::rgb_to_colorref(&palette.get_dynamic(DynamicColor::Background)),
+            );
+            SetTextColor(hdc, fg);
             let _ = ExtTextOutW(
                 hdc,
                 ctx.x,
@@ -613,7 +728,7 @@ impl TerminalGuiDriver {
                     right: ctx.x + pixel_width,
                     bottom: ctx.y + ctx.char_height,
                 };
-                let h_brush = CreateSolidBrush(Self::rgb_to_colorref(&theme.default_fg));
+                let h_brush = CreateSolidBrush(fg);
                 if !h_brush.0.is_null() {
                     let _brush_guard = GdiObjectGuard(HGDIOBJ(h_brush.0));
                     FillRect(hdc, &underline_rect, h_brush);
@@ -0,0 +1,104 @@
+use crate::domain::model::terminal_types_entity::UnderlineStyle;
+use crate::gui::driver::terminal_gui_driver::GdiObjectGuard;
+use windows::Win32::Foundation::{COLORREF, RECT};
+use windows::Win32::Graphics::Gdi::{CreateSolidBrush, FillRect, HBRUSH, HDC, HGDIOBJ};
+
+/// 下線・上線などの文字装飾を GDI で描画するドライバ
+/// フォントの下線属性では種類や色を指定できないため、矩形の塗りつぶしで描く。
+pub(crate) struct TextDecorationGuiDriver;
+
+impl TextDecorationGuiDriver {
+    /// `rect` で示される文字列の下端に下線を描画する
+    pub(crate) fn draw_underline(hdc: HDC, rect: &RECT, style: UnderlineStyle, color: COLORREF) {
+        if style == UnderlineStyle::None {
+            return;
+        }
+        let thickness = Self::line_thickness(rect);
+        let base = rect.bottom - thickness * 2;
+        Self::with_brush(color, |brush| match style {
+            UnderlineStyle::None => {}
+            UnderlineStyle::Single => {
+                Self::fill(hdc, rect.left, base, rect.right, thickness, brush);
+            }
+            UnderlineStyle::Double => {
+                Self::fill(hdc, rect.left, base, rect.right, thickness, brush);
+                Self::fill(
+                    hdc,
+                    rect.left,
+                    base - thickness * 2,
+                    rect.right,
+                    thickness,
+                    brush,
+                );
+            }
+            UnderlineStyle::Curly => {
+                // 振幅 amplitude の三角波を 1 ピクセル幅の矩形で近似する
+                let amplitude = (thickness * 2).max(2);
+                let period = amplitude * 4;
+                for x in rect.left..rect.right {
+                    let phase = (x - rect.left) % period;
+                    let offset = if phase < period / 2 {
+                        phase - amplitude
+                    } else {
+                        period - phase - amplitude
+                    };
+                    Self::fill(hdc, x, base - offset / 2, x + 1, thickness, brush);
+                }
+            }
+            UnderlineStyle::Dotted => {
+                Self::fill_dashes(hdc, rect, base, thickness, thickness, brush);
+            }
+            UnderlineStyle::Dashed => {
+                let dash = ((rect.bottom - rect.top) / 4).max(thickness * 2);
+                Self::fill_dashes(hdc, rect, base, dash, thickness, brush);
+            }
+        });
+    }
+
+    /// `rect` で示される文字列の上端に上線を描画する
+    pub(crate) fn draw_overline(hdc: HDC, rect: &RECT, color: COLORREF) {
+        let thickness = Self::line_thickness(rect);
+        Self::with_brush(color, |brush| {
+            Self::fill(hdc, rect.left, rect.top, rect.right, thickness, brush);
+        });
+    }
+
+    /// 文字の高さに応じた線の太さ
+    fn line_thickness(rect: &RECT) -> i32 {
+        ((rect.bottom - rect.top) / 16).max(1)
+    }
+
+    /// 長さ `dash` の線分を同じ長さの間隔で並べる
+    fn fill_dashes(hdc: HDC, rect: &RECT, top: i32, dash: i32, thickness: i32, brush: HBRUSH) {
+        let mut x = rect.left;
+        while x < rect.right {
+            Self::fill(hdc, x, top, (x + dash).min(rect.right), thickness, brush);
+            x += dash * 2;
+        }
+    }
+
+    fn fill(hdc: HDC, left: i32, top: i32, right: i32, thickness: i32, brush: HBRUSH) {
+        let rect = RECT {
+            left,
+            top,
+            right,
+            bottom: top + thickness,
+        };
+        // SAFETY: 有効な HDC と呼び出し元が管理するブラシで矩形を塗りつぶす。
+        unsafe {
+            FillRect(hdc, &rect, brush);
+        }
+    }
+
+    fn with_brush(color: COLORREF, draw: impl FnOnce(HBRUSH)) {
+        // SAFETY: 作成したブラシは GdiObjectGuard により描画後に確実に破棄される。
+        unsafe {
+            let brush = CreateSolidBrush(color);
+            if brush.0.is_null() {
+                return;
+            }
+            let _brush_guard = GdiObjectGuard(HGDIOBJ(brush.0));
+            draw(brush);
+        }
+    }
+}
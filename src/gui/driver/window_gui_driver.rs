@@ -1,13 +1,16 @@
 use crate::domain::model::window_id_value::WindowId;
 use crate::gui::resolver::terminal_window_resolver::get_terminal_data;
+use std::time::Duration;
 use windows::Win32::Foundation::{HWND, LPARAM, RECT, WPARAM};
 use windows::Win32::Graphics::Gdi::{
     BeginPaint, EndPaint, InvalidateRect, PAINTSTRUCT, UpdateWindow,
 };
 use windows::Win32::UI::Input::KeyboardAndMouse::SetFocus;
 use windows::Win32::UI::WindowsAndMessaging::{
-    DefWindowProcW, DestroyWindow, GetClientRect, IsWindow,
+    DefWindowProcW, DestroyWindow, GetClientRect, IDYES, IsWindow, KillTimer, MB_ICONWARNING,
+    MB_YESNO, MessageBoxW, PostMessageW, SetTimer, SetWindowTextW,
 };
+use windows::core::PCWSTR;
 
 /// Win32 ウィンドウ操作をカプセル化するドライバ
 pub(crate) struct WindowGuiDriver;
@@ -49,6 +52,64 @@ impl WindowGuiDriver {
         }
     }
 
+    /// ウィンドウのテキスト（キャプション）を変更する。
+    pub(crate) fn set_window_text(window_id: WindowId, text: &str) {
+        let hwnd = HWND(window_id.0 as _);
+        let wide_text: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+        // SAFETY: IsWindow で存在を確認し、ヌル終端された文字列ポインタを渡す。
+        unsafe {
+            if IsWindow(Some(hwnd)).as_bool() {
+                let _ = SetWindowTextW(hwnd, PCWSTR(wide_text.as_ptr()));
+            }
+        }
+    }
+
+    /// はい／いいえで確認するメッセージボックスを表示し、「はい」が選ばれたかを返す。
+    pub(crate) fn confirm(window_id: WindowId, message: &str, caption: &str) -> bool {
+        let hwnd = HWND(window_id.0 as _);
+        let wide_message: Vec<u16> = message.encode_utf16().chain(std::iter::once(0)).collect();
+        let wide_caption: Vec<u16> = caption.encode_utf16().chain(std::iter::once(0)).collect();
+        // SAFETY: ヌル終端された文字列ポインタを渡し、モーダルなメッセージボックスを表示する。
+        unsafe {
+            MessageBoxW(
+                Some(hwnd),
+                PCWSTR(wide_message.as_ptr()),
+                PCWSTR(wide_caption.as_ptr()),
+                MB_YESNO | MB_ICONWARNING,
+            ) == IDYES
+        }
+    }
+
+    /// 自ウィンドウへメッセージを非同期に通知する。
+    pub(crate) fn post_message(window_id: WindowId, msg: u32) {
+        let hwnd = HWND(window_id.0 as _);
+        // SAFETY: PostMessageW はスレッドセーフであり、無効なハンドルの場合は失敗するだけである。
+        unsafe {
+            let _ = PostMessageW(Some(hwnd), msg, WPARAM(0), LPARAM(0));
+        }
+    }
+
+    /// 指定した時間後に WM_TIMER を通知するタイマーを設定する。同じ ID のタイマーは置き換えられる。
+    pub(crate) fn set_timer(window_id: WindowId, timer_id: usize, delay: Duration) {
+        let hwnd = HWND(window_id.0 as _);
+        let elapse = delay.as_millis().clamp(1, u32::MAX as u128) as u32;
+        // SAFETY: 有効なウィンドウハンドルに対してタイマーを設定する。
+        unsafe {
+            if SetTimer(Some(hwnd), timer_id, elapse, None) == 0 {
+                log::warn!("SetTimer failed: id={}", timer_id);
+            }
+        }
+    }
+
+    /// タイマーを停止する。
+    pub(crate) fn kill_timer(window_id: WindowId, timer_id: usize) {
+        let hwnd = HWND(window_id.0 as _);
+        // SAFETY: 有効なウィンドウハンドルに対してタイマーを停止する。存在しない場合は失敗するだけである。
+        unsafe {
+            let _ = KillTimer(Some(hwnd), timer_id);
+        }
+    }
+
     /// ウィンドウの再描画を要求する。
     pub(crate) fn invalidate_rect(window_id: WindowId, erase: bool) {
         let hwnd = HWND(window_id.0 as _);
@@ -6,6 +6,9 @@ use crate::gui::driver::scroll_gui_driver::ScrollGuiDriver;
 use crate::gui::driver::terminal_gui_driver::{CompositionInfo, TerminalGuiDriver};
 use std::sync::{Arc, Mutex, OnceLock};
 
+/// タイトル未設定時のカスタムバーのキャプション
+pub const DEFAULT_CAPTION: &str = "Terminal";
+
 static TERMINAL_DATA: OnceLock<Arc<Mutex<TerminalWindowResolver>>> = OnceLock::new();
 
 pub struct TerminalWindowResolver {
@@ -13,10 +16,18 @@ pub struct TerminalWindowResolver {
     pub renderer: TerminalGuiDriver,
     pub window_handle: Option<SendHWND>,
     pub editor_handle: Option<SendHWND>,
+    /// EmEditor が生成したカスタムバー本体のハンドル（キャプション更新に使用）
+    pub custom_bar_handle: Option<SendHWND>,
+    /// カスタムバーに現在表示しているキャプション
+    pub caption: String,
     pub composition: Option<CompositionInfo>,
     pub scroll_manager: ScrollGuiDriver,
     pub caret: Option<CaretHandle>,
     pub is_conpty_started: bool,
+    /// 点滅属性の文字のためのタイマーが動作しているか
+    pub is_blink_timer_active: bool,
+    /// ハイパーリンクを開いた左ボタンの押下に対応する解放を、アプリケーションへ送らずに捨てるか
+    pub is_lbutton_release_swallowed: bool,
 }
 
 impl TerminalWindowResolver {
@@ -37,10 +48,14 @@ impl TerminalWindowResolver {
             renderer: TerminalGuiDriver::new(),
             window_handle: None,
             editor_handle: None,
+            custom_bar_handle: None,
+            caption: DEFAULT_CAPTION.to_string(),
             composition: None,
             scroll_manager: ScrollGuiDriver::new(),
             caret: None,
             is_conpty_started: false,
+            is_blink_timer_active: false,
+            is_lbutton_release_swallowed: false,
         }
     }
 
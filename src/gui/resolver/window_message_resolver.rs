@@ -1,6 +1,8 @@
-use super::terminal_window_resolver::{TerminalWindowResolver, get_terminal_data};
+use super::terminal_window_resolver::{DEFAULT_CAPTION, TerminalWindowResolver, get_terminal_data};
+use crate::domain::model::clipboard_value::ClipboardRequest;
 use crate::domain::model::input_value::{Modifiers, MouseButton, MouseEvent};
 use crate::domain::model::window_id_value::WindowId;
+use crate::gui::driver::clipboard_gui_driver::ClipboardGuiDriver;
 use crate::gui::driver::ime_gui_driver::{
     ImeResult, handle_composition, handle_end_composition, handle_start_composition,
     sync_system_caret,
@@ -9,8 +11,13 @@ use crate::gui::driver::keyboard_gui_driver::KeyboardGuiDriver;
 use crate::gui::driver::scroll_gui_driver::{ScrollAction, update_window_scroll_info};
 use crate::gui::driver::terminal_gui_driver::CompositionInfo;
 use crate::gui::driver::window_gui_driver::WindowGuiDriver;
+use crate::gui::window::{
+    BLINK_INTERVAL, BLINK_TIMER_ID, SYNCHRONIZED_OUTPUT_TIMER_ID, WM_APP_PASTE_CHUNK,
+};
+use crate::infra::driver::{emeditor_io_driver, shell_io_driver};
 use windows::Win32::Graphics::Gdi::ScreenToClient;
 use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, VK_CONTROL, VK_MENU, VK_SHIFT};
+use windows::Win32::UI::WindowsAndMessaging::UNICODE_NOCHAR;
 
 /// Win32 固有の定数定義
 const ISC_SHOWUICOMPOSITIONWINDOW: u32 = 0x80000000;
@@ -25,6 +32,8 @@ const WM_MBUTTONDOWN: u32 = 0x0207;
 const WM_MBUTTONUP: u32 = 0x0208;
 const WM_MOUSEHWHEEL: u32 = 0x020E;
 
+const MK_CONTROL: usize = 0x0008;
+
 pub fn on_vscroll(window_id: WindowId, wparam: usize, lparam: isize) -> isize {
     let data_arc = get_terminal_data();
     let action = {
@@ -104,6 +113,17 @@ pub fn on_mousewheel(window_id: WindowId, wparam: usize, lparam: isize) -> isize
                 }
             }
         }
+
+        // 代替画面ではホイールをカーソルキーとしてアプリケーションへ送る (DECSET 1007)
+        if !is_handled && !is_shift_pressed {
+            is_handled = window_data
+                .service
+                .send_alternate_scroll(delta)
+                .unwrap_or_else(|e| {
+                    log::error!("Failed to send alternate scroll: {}", e);
+                    false
+                });
+        }
     }
 
     if is_handled {
@@ -187,10 +207,11 @@ pub fn on_paint(window_id: WindowId) -> isize {
         let data_arc = get_terminal_data();
         let mut window_data = data_arc.lock().unwrap();
         let TerminalWindowResolver {
-            ref service,
+            ref mut service,
             ref mut renderer,
             ref composition,
             ref caret,
+            ref mut is_blink_timer_active,
             ..
         } = *window_data;
         renderer.render(
@@ -198,9 +219,17 @@ pub fn on_paint(window_id: WindowId) -> isize {
             &ctx.rect,
             service.get_buffer(),
             composition.as_ref(),
-            &service.color_theme,
             &service.config,
         );
+        // Sixel 画像が占めるセル数を実際のセルの大きさで計算できるよう通知する
+        if let Some(metrics) = renderer.get_metrics() {
+            service.set_cell_pixel_size(metrics.base_width as usize, metrics.char_height as usize);
+        }
+        // 点滅属性の文字が表示されている間だけタイマーを動かす
+        if renderer.has_blinking_cells() && !*is_blink_timer_active {
+            WindowGuiDriver::set_timer(window_id, BLINK_TIMER_ID, BLINK_INTERVAL);
+            *is_blink_timer_active = true;
+        }
         sync_system_caret(
             window_id,
             service.get_buffer().get_ime_anchor_pos(),
@@ -215,10 +244,75 @@ pub fn on_paint(window_id: WindowId) -> isize {
 pub fn on_lbuttondown(window_id: WindowId, wparam: usize, lparam: isize) -> isize {
     log::info!("WM_LBUTTONDOWN: Setting focus");
     WindowGuiDriver::focus_existing_window(window_id);
+    let is_link_opened = (wparam & MK_CONTROL) != 0 && open_hyperlink_at(lparam);
+    // リンクを開いた押下はアプリケーションへ送っていないため、対応する解放も送らない
+    get_terminal_data()
+        .lock()
+        .unwrap()
+        .is_lbutton_release_swallowed = is_link_opened;
+    if is_link_opened {
+        return 0;
+    }
     dispatch_mouse_event(window_id, WM_LBUTTONDOWN, wparam, lparam)
 }
 
+/// クリック位置に OSC 8 ハイパーリンクがあれば開く。リンクが存在した場合は true を返す。
+fn open_hyperlink_at(lparam: isize) -> bool {
+    let px = (lparam as i16) as i32;
+    let py = (lparam >> 16) as i16 as i32;
+
+    let local_host = shell_io_driver::get_computer_name();
+    let (link, file_path, editor_handle) = {
+        let data_arc = get_terminal_data();
+        let window_data = data_arc.lock().unwrap();
+        let Some(metrics) = window_data.renderer.get_metrics() else {
+            return false;
+        };
+        let x = (px / metrics.base_width).max(0) as usize;
+        let y = (py / metrics.char_height).max(0) as usize;
+        let link = window_data.service.get_hyperlink_at(x, y).cloned();
+        // 相対パスのファイルリンクはシェルのカレントディレクトリ (OSC 7) を基準に解決する
+        let file_path = link.as_ref().and_then(|l| {
+            l.to_file_path(
+                window_data.service.get_current_directory(),
+                local_host.as_deref(),
+            )
+        });
+        (link, file_path, window_data.editor_handle)
+    };
+    let Some(link) = link else {
+        return false;
+    };
+
+    if let Some(path) = file_path {
+        log::info!("Opening hyperlink in EmEditor: {}", path);
+        let is_loaded = editor_handle
+            .is_some_and(|hwnd| emeditor_io_driver::load_file(WindowId(hwnd.0.0 as isize), &path));
+        if !is_loaded {
+            log::error!("Failed to open '{}' in EmEditor", path);
+        }
+    } else if link.is_web_uri() {
+        log::info!("Opening hyperlink with default handler: {}", link.uri);
+        shell_io_driver::open_with_default_handler(&link.uri);
+    } else {
+        log::warn!(
+            "Refusing to open hyperlink with unsupported scheme: {}",
+            link.uri
+        );
+    }
+    true
+}
+
 pub fn on_lbuttonup(window_id: WindowId, wparam: usize, lparam: isize) -> isize {
+    let is_swallowed = std::mem::take(
+        &mut get_terminal_data()
+            .lock()
+            .unwrap()
+            .is_lbutton_release_swallowed,
+    );
+    if is_swallowed {
+        return 0;
+    }
     dispatch_mouse_event(window_id, WM_LBUTTONUP, wparam, lparam)
 }
 
@@ -340,6 +434,9 @@ pub fn on_set_focus(window_id: WindowId) -> isize {
             window_id, cw, ch,
         ));
         KeyboardGuiDriver::install(window_id);
+        if let Err(e) = window_data.service.report_focus(true) {
+            log::error!("Failed to report focus in: {}", e);
+        }
     }
     0
 }
@@ -351,6 +448,9 @@ pub fn on_kill_focus() -> isize {
         let mut window_data = data_arc.lock().unwrap();
         window_data.caret = None;
         KeyboardGuiDriver::uninstall();
+        if let Err(e) = window_data.service.report_focus(false) {
+            log::error!("Failed to report focus out: {}", e);
+        }
     }
     0
 }
@@ -399,9 +499,22 @@ pub fn on_char(window_id: WindowId, wparam: usize) -> isize {
     {
         let data_arc = get_terminal_data();
         let mut window_data = data_arc.lock().unwrap();
-        window_data.service.reset_viewport();
-        let s = String::from_utf16_lossy(&[char_code]);
-        let _ = window_data.service.send_input(s.as_bytes());
+        let _ = window_data.service.send_char(char_code);
+    }
+    update_window_scroll_info(window_id);
+    WindowGuiDriver::invalidate_rect(window_id, false);
+    0
+}
+
+pub fn on_unichar(window_id: WindowId, wparam: usize) -> isize {
+    // UNICODE_NOCHAR は WM_UNICHAR に対応しているかの問い合わせであり、TRUE を返す
+    if wparam as u32 == UNICODE_NOCHAR {
+        return 1;
+    }
+    {
+        let data_arc = get_terminal_data();
+        let mut window_data = data_arc.lock().unwrap();
+        let _ = window_data.service.send_code_point(wparam as u32);
     }
     update_window_scroll_info(window_id);
     WindowGuiDriver::invalidate_rect(window_id, false);
@@ -514,14 +627,23 @@ pub fn on_destroy() -> isize {
         let mut window_data = data_arc.lock().unwrap();
         window_data.renderer.clear_resources();
         window_data.window_handle = None;
+        window_data.custom_bar_handle = None;
+        window_data.caption = DEFAULT_CAPTION.to_string();
         window_data.caret = None;
     }
     0
 }
 
 pub fn on_app_repaint(window_id: WindowId) -> isize {
-    update_window_scroll_info(window_id);
     let data_arc = get_terminal_data();
+    // 同期出力中はフレームの途中を描画しないよう、終了か期限切れまで保留する
+    let repaint_hold = data_arc.lock().unwrap().service.get_repaint_hold();
+    if let Some(hold) = repaint_hold {
+        WindowGuiDriver::set_timer(window_id, SYNCHRONIZED_OUTPUT_TIMER_ID, hold);
+        return 0;
+    }
+    WindowGuiDriver::kill_timer(window_id, SYNCHRONIZED_OUTPUT_TIMER_ID);
+    update_window_scroll_info(window_id);
     let is_composing = {
         let window_data = data_arc.lock().unwrap();
         let TerminalWindowResolver {
@@ -544,5 +666,153 @@ pub fn on_app_repaint(window_id: WindowId) -> isize {
     if is_composing {
         WindowGuiDriver::update_window(window_id);
     }
+    sync_custom_bar_caption();
+    fulfil_clipboard_requests(window_id);
+    0
+}
+
+pub fn on_timer(window_id: WindowId, timer_id: usize) -> isize {
+    match timer_id {
+        SYNCHRONIZED_OUTPUT_TIMER_ID => {
+            on_app_repaint(window_id);
+        }
+        BLINK_TIMER_ID => on_blink_timer(window_id),
+        _ => {}
+    }
+    0
+}
+
+/// 点滅属性の文字の表示を切り替える。点滅する文字が無くなった場合はタイマーを止める。
+fn on_blink_timer(window_id: WindowId) {
+    let data_arc = get_terminal_data();
+    let mut window_data = data_arc.lock().unwrap();
+    if window_data.renderer.has_blinking_cells() {
+        window_data.renderer.toggle_blink_phase();
+    } else {
+        WindowGuiDriver::kill_timer(window_id, BLINK_TIMER_ID);
+        window_data.is_blink_timer_active = false;
+        window_data.renderer.reset_blink_phase();
+    }
+    drop(window_data);
+    WindowGuiDriver::invalidate_rect(window_id, false);
+}
+
+/// クリップボードの内容をターミナルへ貼り付ける
+pub fn on_app_paste(window_id: WindowId) -> isize {
+    let Some(text) = ClipboardGuiDriver::get_text(window_id) else {
+        return 0;
+    };
+    let data_arc = get_terminal_data();
+    let needs_confirmation = data_arc
+        .lock()
+        .unwrap()
+        .service
+        .needs_paste_confirmation(&text);
+    // 確認ダイアログの表示中はロックを保持しない
+    if needs_confirmation
+        && !WindowGuiDriver::confirm(
+            window_id,
+            "複数行のテキストを貼り付けようとしています。\n貼り付けたコマンドはすぐに実行される可能性があります。続行しますか?",
+            DEFAULT_CAPTION,
+        )
+    {
+        return 0;
+    }
+    let result = data_arc.lock().unwrap().service.paste(&text);
+    handle_paste_result(window_id, result);
     0
 }
+
+/// 分割された貼り付けデータの続きを送信する
+pub fn on_app_paste_chunk(window_id: WindowId) -> isize {
+    let result = get_terminal_data().lock().unwrap().service.pump_paste();
+    handle_paste_result(window_id, result);
+    0
+}
+
+fn handle_paste_result(window_id: WindowId, result: std::io::Result<bool>) {
+    match result {
+        // 他のメッセージを処理できるよう、残りはメッセージキュー経由で送信する
+        Ok(true) => WindowGuiDriver::post_message(window_id, WM_APP_PASTE_CHUNK),
+        Ok(false) => {}
+        Err(e) => log::error!("Failed to paste: {}", e),
+    }
+    update_window_scroll_info(window_id);
+    WindowGuiDriver::invalidate_rect(window_id, false);
+}
+
+/// OSC 133 で区切られた直近のコマンドの出力をクリップボードへコピーする
+pub fn on_app_copy_command_output(window_id: WindowId) -> isize {
+    // クリップボード操作中はロックを保持しない
+    let last = get_terminal_data()
+        .lock()
+        .unwrap()
+        .service
+        .get_last_command_output();
+    let Some((block, output)) = last else {
+        log::info!("No finished command to copy");
+        return 0;
+    };
+    if ClipboardGuiDriver::set_text(window_id, &output) {
+        log::info!(
+            "Copied output of '{}' (exit code: {:?}, duration: {:?})",
+            block.command,
+            block.exit_code,
+            block.duration()
+        );
+    } else {
+        log::error!("Failed to copy command output to clipboard");
+    }
+    0
+}
+
+/// OSC 52 で要求されたクリップボード操作を実行する
+/// クリップボード操作中はロックを保持しないよう、要求を取り出してから処理する。
+fn fulfil_clipboard_requests(window_id: WindowId) {
+    let data_arc = get_terminal_data();
+    let requests = data_arc.lock().unwrap().service.take_clipboard_requests();
+    for request in requests {
+        match request {
+            ClipboardRequest::Write { selection, text } => {
+                log::info!("OSC 52: writing {} chars to {:?}", text.len(), selection);
+                ClipboardGuiDriver::set_text(window_id, &text);
+            }
+            ClipboardRequest::Read { selection } => {
+                let text = ClipboardGuiDriver::get_text(window_id).unwrap_or_default();
+                let window_data = data_arc.lock().unwrap();
+                if let Err(e) = window_data.service.reply_clipboard_read(selection, &text) {
+                    log::error!("Failed to reply OSC 52 clipboard read: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// ターミナルのタイトルからカスタムバーのキャプションを組み立てる
+fn build_caption(title: &str) -> String {
+    if title.is_empty() {
+        DEFAULT_CAPTION.to_string()
+    } else {
+        format!("{} - {}", DEFAULT_CAPTION, title)
+    }
+}
+
+/// OSC 0/2 で変更されたタイトルをカスタムバーのキャプションへ反映する
+fn sync_custom_bar_caption() {
+    let data_arc = get_terminal_data();
+    let update = {
+        let mut window_data = data_arc.lock().unwrap();
+        let caption = build_caption(window_data.service.get_title());
+        match window_data.custom_bar_handle {
+            Some(handle) if caption != window_data.caption => {
+                window_data.caption = caption.clone();
+                Some((handle, caption))
+            }
+            _ => None,
+        }
+    };
+    // SetWindowTextW は同期的にメッセージを送るため、ロック解放後に呼び出す
+    if let Some((handle, caption)) = update {
+        WindowGuiDriver::set_window_text(WindowId(handle.0.0 as isize), &caption);
+    }
+}
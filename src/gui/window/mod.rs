@@ -9,6 +9,7 @@ use crate::infra::driver::emeditor_io_driver::{MB_ICONERROR, MB_OK, MessageBoxW,
 use std::mem::size_of;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
+use std::time::Duration;
 use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
 use windows::Win32::Graphics::Gdi::HBRUSH;
 use windows::Win32::Storage::FileSystem::ReadFile;
@@ -18,8 +19,8 @@ use windows::Win32::UI::WindowsAndMessaging::{
     WM_IME_COMPOSITION, WM_IME_ENDCOMPOSITION, WM_IME_SETCONTEXT, WM_IME_STARTCOMPOSITION,
     WM_KEYDOWN, WM_KEYUP, WM_KILLFOCUS, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP,
     WM_MOUSEHWHEEL, WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_PAINT, WM_RBUTTONDOWN, WM_RBUTTONUP,
-    WM_SETFOCUS, WM_SIZE, WM_SYSCHAR, WM_SYSCOMMAND, WM_SYSKEYDOWN, WM_SYSKEYUP, WM_VSCROLL,
-    WNDCLASSW, WS_CHILD, WS_CLIPCHILDREN, WS_CLIPSIBLINGS, WS_VISIBLE,
+    WM_SETFOCUS, WM_SIZE, WM_SYSCHAR, WM_SYSCOMMAND, WM_SYSKEYDOWN, WM_SYSKEYUP, WM_TIMER,
+    WM_UNICHAR, WM_VSCROLL, WNDCLASSW, WS_CHILD, WS_CLIPCHILDREN, WS_CLIPSIBLINGS, WS_VISIBLE,
 };
 use windows::core::{PCWSTR, w};
 
@@ -27,6 +28,18 @@ use crate::gui::resolver::window_message_resolver as handlers;
 
 /// 描画更新を通知するメッセージ
 const WM_APP_REPAINT: u32 = WM_APP + 1;
+/// クリップボードからの貼り付けを要求するメッセージ
+pub(crate) const WM_APP_PASTE: u32 = WM_APP + 2;
+/// 分割された貼り付けデータの残りの送信を要求するメッセージ
+pub(crate) const WM_APP_PASTE_CHUNK: u32 = WM_APP + 3;
+/// 直近のコマンドの出力をクリップボードへコピーするよう要求するメッセージ
+pub(crate) const WM_APP_COPY_COMMAND_OUTPUT: u32 = WM_APP + 4;
+/// 同期出力 (DECSET 2026) で保留した描画を期限切れで実行するタイマー
+pub(crate) const SYNCHRONIZED_OUTPUT_TIMER_ID: usize = 1;
+/// 点滅属性 (SGR 5) の文字の表示を切り替えるタイマー
+pub(crate) const BLINK_TIMER_ID: usize = 2;
+/// 点滅属性の文字の表示を切り替える間隔
+pub(crate) const BLINK_INTERVAL: Duration = Duration::from_millis(500);
 
 static CLASS_REGISTERED: AtomicBool = AtomicBool::new(false);
 const CLASS_NAME: PCWSTR = w!("EmEditorTerminalClass");
@@ -221,6 +234,12 @@ pub fn open_custom_bar(hwnd_editor: HWND) -> bool {
                     Some(LPARAM(&mut info as *mut _ as isize)),
                 );
 
+                if !info.hwndCustomBar.is_invalid() {
+                    let mut window_data = data_arc.lock().unwrap();
+                    window_data.custom_bar_handle =
+                        Some(crate::gui::common::SendHWND(info.hwndCustomBar));
+                }
+
                 WindowGuiDriver::focus_existing_window(WindowId(hwnd_client.0 as isize));
                 true
             }
@@ -288,7 +307,14 @@ pub extern "system" fn wnd_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LP
         WM_SYSCOMMAND => LRESULT(handlers::on_syscommand(window_id, msg, wparam.0, lparam.0)),
         WM_GETDLGCODE => LRESULT(handlers::on_get_dlg_code()),
         WM_CHAR => LRESULT(handlers::on_char(window_id, wparam.0)),
+        WM_UNICHAR => LRESULT(handlers::on_unichar(window_id, wparam.0)),
         msg if msg == WM_APP_REPAINT => LRESULT(handlers::on_app_repaint(window_id)),
+        msg if msg == WM_APP_PASTE => LRESULT(handlers::on_app_paste(window_id)),
+        msg if msg == WM_APP_PASTE_CHUNK => LRESULT(handlers::on_app_paste_chunk(window_id)),
+        msg if msg == WM_APP_COPY_COMMAND_OUTPUT => {
+            LRESULT(handlers::on_app_copy_command_output(window_id))
+        }
+        WM_TIMER => LRESULT(handlers::on_timer(window_id, wparam.0)),
         WM_SIZE => LRESULT(handlers::on_size(window_id, lparam.0)),
         WM_IME_SETCONTEXT => LRESULT(handlers::on_ime_set_context(
             window_id, msg, wparam.0, lparam.0,
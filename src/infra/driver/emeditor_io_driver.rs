@@ -8,6 +8,7 @@ use windows::core::{PCWSTR, w};
 const WM_USER: u32 = 0x0400;
 #[allow(dead_code)]
 pub const EE_FIRST: u32 = WM_USER + 0x400; // 2048
+const EE_LOAD_FILE: u32 = EE_FIRST + 12;
 #[allow(dead_code)]
 pub const EE_OUTPUT_STRING: u32 = EE_FIRST + 90;
 #[allow(dead_code)]
@@ -20,6 +21,11 @@ const EE_REG_SET_VALUE: u32 = EE_FIRST + 85; // 2133
 
 const EEREG_EMEDITORPLUGIN: u32 = 0x7fffff30;
 
+/// LOAD_FILE_INFO_EX.nFlags: 現在のウィンドウを置き換えず、新しいタブ (ウィンドウ) で開く
+const LFI_ALLOW_NEW_WINDOW: u32 = 0x0000_0001;
+/// 文字コードを判定できなかった場合に使用するコードページ
+const CP_UTF8: u32 = 65001;
+
 const REG_SZ: u32 = 1;
 const REG_DWORD: u32 = 4;
 
@@ -49,6 +55,17 @@ struct REG_SET_VALUE_INFO {
     dwFlags: u32,
 }
 
+#[repr(C)]
+#[allow(non_snake_case)]
+struct LOAD_FILE_INFO_EX {
+    cbSize: usize,
+    nCP: u32,
+    bDetectUnicode: i32,
+    bDetectAll: i32,
+    bDetectHTML: i32,
+    nFlags: u32,
+}
+
 pub const CUSTOM_BAR_BOTTOM: i32 = 3;
 
 #[repr(C)]
@@ -225,6 +242,31 @@ pub fn emeditor_set_u32(window_id: WindowId, value_name: &str, value: u32) -> i3
     }
 }
 
+/// 実行中の EmEditor でファイルを開く (Editor_LoadFileW)。
+/// 文字コードは EmEditor に判定させ、判定できなかった場合は UTF-8 として開く。
+pub fn load_file(window_id: WindowId, path: &str) -> bool {
+    let hwnd = HWND(window_id.0 as _);
+    let path_wide: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+    let mut info = LOAD_FILE_INFO_EX {
+        cbSize: size_of::<LOAD_FILE_INFO_EX>(),
+        nCP: CP_UTF8,
+        bDetectUnicode: 1,
+        bDetectAll: 1,
+        bDetectHTML: 0,
+        nFlags: LFI_ALLOW_NEW_WINDOW,
+    };
+    // SAFETY: ファイル名はヌル終端されており、構造体と共に SendMessageW の呼び出し中のみ参照される。
+    let result = unsafe {
+        SendMessageW(
+            hwnd,
+            EE_LOAD_FILE,
+            Some(WPARAM(&mut info as *mut _ as usize)),
+            Some(LPARAM(path_wide.as_ptr() as isize)),
+        )
+    };
+    result.0 != 0
+}
+
 #[allow(dead_code)]
 pub fn output_string(window_id: WindowId, text: &str) {
     let hwnd = HWND(window_id.0 as _);
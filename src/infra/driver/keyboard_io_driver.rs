@@ -1,21 +1,87 @@
 use crate::domain::model::input_value::{InputKey, Modifiers};
 use crate::domain::model::window_id_value::WindowId;
-use crate::domain::repository::key_translator_repository::KeyTranslatorRepository;
-use crate::domain::service::vt_sequence_translator_domain_service::VtSequenceTranslatorDomainService;
 use std::cell::RefCell;
 use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
-use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, VK_CONTROL, VK_MENU, VK_SHIFT};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    GetKeyState, GetKeyboardState, ToUnicode, VK_CONTROL, VK_DOWN, VK_INSERT, VK_MENU, VK_RCONTROL,
+    VK_RMENU, VK_SHIFT, VK_UP,
+};
 use windows::Win32::UI::WindowsAndMessaging::{
     CallNextHookEx, HHOOK, PostMessageW, SetWindowsHookExW, UnhookWindowsHookEx, WH_KEYBOARD,
 };
 
 /// 描画更新を通知するメッセージ
 const WM_APP_REPAINT: u32 = 0x8001;
+/// 'V' キーの仮想キーコード
+const VK_V: u16 = 0x56;
+/// 'O' キーの仮想キーコード
+const VK_O: u16 = 0x4F;
+/// ToUnicode の wFlags: デッドキー等のキーボードの入力状態を変更しない
+const TO_UNICODE_KEEP_STATE: u32 = 0x0004;
 
 thread_local! {
     static KEYBOARD_HOOK: RefCell<Option<HHOOK>> = const { RefCell::new(None) };
     static TARGET_HWND: RefCell<Option<HWND>> = const { RefCell::new(None) };
     static HOOK_INSTANCE: RefCell<Option<KeyboardIoDriver >> = const { RefCell::new(None) };
+    static SWALLOWED_KEYS: RefCell<SwallowedKeys> = const { RefCell::new(SwallowedKeys::new()) };
+}
+
+/// ショートカットとして押下を処理したキー。
+/// 押下だけを握りつぶすとアプリケーションに対応の無い解放が届く (win32-input-mode 等) ため、解放も握りつぶす。
+struct SwallowedKeys {
+    vk_codes: Vec<u16>,
+}
+
+impl SwallowedKeys {
+    const fn new() -> Self {
+        Self {
+            vk_codes: Vec::new(),
+        }
+    }
+
+    /// 押下を処理したキーを記録する
+    fn mark(&mut self, vk_code: u16) {
+        if !self.vk_codes.contains(&vk_code) {
+            self.vk_codes.push(vk_code);
+        }
+    }
+
+    /// キーの解放を受け取り、押下を処理したキーであれば記録を消して true を返す
+    fn take_release(&mut self, vk_code: u16) -> bool {
+        let len = self.vk_codes.len();
+        self.vk_codes.retain(|&vk| vk != vk_code);
+        self.vk_codes.len() != len
+    }
+}
+
+/// ショートカットとして処理したキーの押下を握りつぶし、対応する解放も握りつぶすよう記録する
+fn swallow_key_down(vk_code: u16) -> LRESULT {
+    SWALLOWED_KEYS.with(|keys| keys.borrow_mut().mark(vk_code));
+    LRESULT(1)
+}
+
+/// キーボードレイアウトに従って、キーが入力する文字を求める (win32-input-mode の Uc)。
+/// 文字を伴わないキーや、デッドキー・サロゲートペア等 1 単位にならない場合は 0 を返す。
+fn get_unicode_char(vk_code: u16, scan_code: u16) -> u16 {
+    let mut key_state = [0u8; 256];
+    let mut buffer = [0u16; 2];
+    // SAFETY: キーの状態と出力先のバッファは呼び出し中のみ参照される。
+    // TO_UNICODE_KEEP_STATE によりデッドキーの入力状態は変更しないため、以降の WM_CHAR に影響しない。
+    unsafe {
+        if GetKeyboardState(&mut key_state).is_err() {
+            return 0;
+        }
+        match ToUnicode(
+            vk_code as u32,
+            scan_code as u32,
+            Some(&key_state),
+            &mut buffer,
+            TO_UNICODE_KEEP_STATE,
+        ) {
+            1 => buffer[0],
+            _ => 0,
+        }
+    }
 }
 
 /// Windowsの低レベルキーボードフックを管理する構造体
@@ -108,22 +174,97 @@ impl Drop for KeyboardIoDriver {
 extern "system" fn keyboard_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
     if code >= 0 {
         let vk_code = wparam.0 as u16;
-        let key_up = (lparam.0 >> 31) & 1;
+        // lParam: bit 16-23 スキャンコード, bit 24 拡張キー, bit 30 直前のキーの状態, bit 31 キーの解放
+        let scan_code = ((lparam.0 >> 16) & 0xFF) as u16;
+        let is_extended = (lparam.0 >> 24) & 1 != 0;
+        let is_key_up = (lparam.0 >> 31) & 1 != 0;
+        // 既に押されていたキーの押下はオートリピート
+        let is_repeat = !is_key_up && (lparam.0 >> 30) & 1 != 0;
+
+        if is_key_up && SWALLOWED_KEYS.with(|keys| keys.borrow_mut().take_release(vk_code)) {
+            return LRESULT(1);
+        }
 
-        if key_up == 0
-            && let Some(hwnd) = TARGET_HWND.with(|h| *h.borrow())
+        if let Some(hwnd) = TARGET_HWND.with(|h| *h.borrow())
             && !crate::gui::window::is_ime_composing(hwnd)
         {
             // SAFETY: キーの状態（Ctrl/Shift/Alt）を同期的に取得する。
             let is_ctrl_pressed = unsafe { GetKeyState(VK_CONTROL.0 as i32) } < 0;
             let is_shift_pressed = unsafe { GetKeyState(VK_SHIFT.0 as i32) } < 0;
             let is_alt_pressed = unsafe { GetKeyState(VK_MENU.0 as i32) } < 0;
+            // win32-input-mode では左右の Ctrl / Alt を区別して送る (AltGr は左 Ctrl + 右 Alt)
+            let is_right_ctrl_pressed = unsafe { GetKeyState(VK_RCONTROL.0 as i32) } < 0;
+            let is_right_alt_pressed = unsafe { GetKeyState(VK_RMENU.0 as i32) } < 0;
+
+            // Ctrl+Shift+V / Shift+Insert: クリップボードから貼り付ける
+            let is_paste_key = if is_ctrl_pressed {
+                vk_code == VK_V
+            } else {
+                vk_code == VK_INSERT.0
+            };
+            if !is_key_up && is_shift_pressed && !is_alt_pressed && is_paste_key {
+                // SAFETY: 有効なウィンドウハンドルに対して貼り付けを通知する。
+                unsafe {
+                    let _ = PostMessageW(
+                        Some(hwnd),
+                        crate::gui::window::WM_APP_PASTE,
+                        WPARAM(0),
+                        LPARAM(0),
+                    );
+                }
+                return swallow_key_down(vk_code);
+            }
+
+            // Ctrl+Shift+O: 直近のコマンドの出力をコピーする
+            if !is_key_up
+                && is_ctrl_pressed
+                && is_shift_pressed
+                && !is_alt_pressed
+                && vk_code == VK_O
+            {
+                // SAFETY: 有効なウィンドウハンドルに対してコピーを通知する。
+                unsafe {
+                    let _ = PostMessageW(
+                        Some(hwnd),
+                        crate::gui::window::WM_APP_COPY_COMMAND_OUTPUT,
+                        WPARAM(0),
+                        LPARAM(0),
+                    );
+                }
+                return swallow_key_down(vk_code);
+            }
+
+            // Ctrl+Shift+Up/Down: OSC 133 のプロンプト間を移動する（プロンプトが無ければシェルへ送る）
+            if !is_key_up
+                && is_ctrl_pressed
+                && is_shift_pressed
+                && !is_alt_pressed
+                && (vk_code == VK_UP.0 || vk_code == VK_DOWN.0)
+            {
+                let data_arc = crate::gui::resolver::terminal_window_resolver::get_terminal_data();
+                let is_jumped = {
+                    let mut window_data = data_arc.lock().unwrap();
+                    if vk_code == VK_UP.0 {
+                        window_data.service.jump_to_previous_prompt()
+                    } else {
+                        window_data.service.jump_to_next_prompt()
+                    }
+                };
+                if is_jumped {
+                    // SAFETY: 有効なウィンドウハンドルに対して描画更新を通知する。
+                    unsafe {
+                        let _ = PostMessageW(Some(hwnd), WM_APP_REPAINT, WPARAM(0), LPARAM(0));
+                    }
+                    return swallow_key_down(vk_code);
+                }
+            }
 
             if !crate::gui::driver::window_gui_driver::WindowGuiDriver::is_system_shortcut(
                 vk_code,
                 is_alt_pressed,
             ) {
-                let translator = VtSequenceTranslatorDomainService::new();
+                // キーの解放は win32-input-mode (DECSET 9001) か kitty キーボードプロトコルの場合のみ送信される。
+                // win32-input-mode では文字入力のキーも送信済みとなるため、WM_CHAR は発生しない
                 let input_key = InputKey::new(
                     vk_code,
                     Modifiers {
@@ -131,16 +272,19 @@ extern "system" fn keyboard_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM)
                         is_shift_pressed,
                         is_alt_pressed,
                     },
-                );
-
-                if let Some(seq) = translator.translate(input_key) {
-                    let data_arc =
-                        crate::gui::resolver::terminal_window_resolver::get_terminal_data();
-                    let mut window_data = data_arc.lock().unwrap();
-                    window_data.service.reset_viewport();
-                    let _ = window_data.service.send_input(&seq);
-                    drop(window_data);
+                )
+                .with_key_state(scan_code, is_extended, is_key_up)
+                .with_repeat(is_repeat)
+                .with_right_modifiers(is_right_ctrl_pressed, is_right_alt_pressed)
+                .with_unicode_char(get_unicode_char(vk_code, scan_code));
 
+                let data_arc = crate::gui::resolver::terminal_window_resolver::get_terminal_data();
+                let result = data_arc.lock().unwrap().service.send_key(input_key);
+                let is_sent = result.unwrap_or_else(|e| {
+                    log::error!("Failed to send key input: {}", e);
+                    true
+                });
+                if is_sent {
                     // SAFETY: 有効なウィンドウハンドルに対して描画更新を通知する。
                     // PostMessageW はスレッドセーフである。
                     unsafe {
@@ -162,3 +306,25 @@ extern "system" fn keyboard_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM)
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swallowed_key_release() {
+        let mut keys = SwallowedKeys::new();
+        keys.mark(VK_V);
+        // 押下を処理していないキーの解放はアプリケーションへ送る
+        assert!(!keys.take_release(VK_INSERT.0));
+        // 押下を処理したキーの解放は 1 回だけ握りつぶす
+        assert!(keys.take_release(VK_V));
+        assert!(!keys.take_release(VK_V));
+
+        // オートリピートで押下が続いても、解放は 1 回だけ
+        keys.mark(VK_UP.0);
+        keys.mark(VK_UP.0);
+        assert!(keys.take_release(VK_UP.0));
+        assert!(!keys.take_release(VK_UP.0));
+    }
+}
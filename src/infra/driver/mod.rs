@@ -1,3 +1,4 @@
 pub(crate) mod conpty_io_driver;
 pub(crate) mod emeditor_io_driver;
 pub(crate) mod keyboard_io_driver;
+pub(crate) mod shell_io_driver;
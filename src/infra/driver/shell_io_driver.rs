@@ -0,0 +1,31 @@
+use windows::Win32::UI::Shell::ShellExecuteW;
+use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+use windows::core::{PCWSTR, w};
+
+/// URI を OS の既定のハンドラ（ブラウザ等）で開く
+pub fn open_with_default_handler(uri: &str) -> bool {
+    let uri_wide: Vec<u16> = uri.encode_utf16().chain(std::iter::once(0)).collect();
+    // SAFETY: ヌル終端された文字列ポインタを渡しており、呼び出し中のみ参照される。
+    let result = unsafe {
+        ShellExecuteW(
+            None,
+            w!("open"),
+            PCWSTR(uri_wide.as_ptr()),
+            PCWSTR::null(),
+            PCWSTR::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    // ShellExecuteW は成功時に 32 より大きい値を返す
+    if result.0 as isize > 32 {
+        true
+    } else {
+        log::error!("ShellExecuteW failed for '{}': {:?}", uri, result.0);
+        false
+    }
+}
+
+/// このマシンのコンピューター名を取得する。file:// URI のホストがローカルかどうかの判定に使う
+pub fn get_computer_name() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
@@ -1,4 +1,4 @@
-use crate::domain::model::terminal_config_value::{TerminalConfig, ThemeType};
+use crate::domain::model::terminal_config_value::{ClipboardPolicy, TerminalConfig, ThemeType};
 use crate::domain::model::window_id_value::WindowId;
 use crate::domain::repository::configuration_repository::{ConfigError, ConfigurationRepository};
 use crate::infra::driver::emeditor_io_driver;
@@ -77,6 +77,37 @@ impl ConfigurationRepository for EmEditorConfigRepositoryImpl {
             }
         };
 
+        let clipboard_policy = ClipboardPolicy::from_index(
+            self.query_dword("ClipboardPolicy", default.clipboard_policy.to_index()),
+        );
+
+        let confirm_multiline_paste = self.query_dword(
+            "ConfirmMultilinePaste",
+            if default.confirm_multiline_paste {
+                1
+            } else {
+                0
+            },
+        ) != 0;
+
+        let mut alternate_scroll_lines =
+            self.query_dword("AlternateScrollLines", default.alternate_scroll_lines);
+        if alternate_scroll_lines <= 0 {
+            log::warn!(
+                "EmEditorConfigRepositoryImpl: Invalid AlternateScrollLines loaded ({}). Falling back to default ({}).",
+                alternate_scroll_lines,
+                default.alternate_scroll_lines
+            );
+            alternate_scroll_lines = default.alternate_scroll_lines;
+        } else if alternate_scroll_lines > TerminalConfig::MAX_ALTERNATE_SCROLL_LINES {
+            log::warn!(
+                "EmEditorConfigRepositoryImpl: AlternateScrollLines too large ({}). Clamping to {}.",
+                alternate_scroll_lines,
+                TerminalConfig::MAX_ALTERNATE_SCROLL_LINES
+            );
+            alternate_scroll_lines = TerminalConfig::MAX_ALTERNATE_SCROLL_LINES;
+        }
+
         TerminalConfig {
             theme_type,
             font_face,
@@ -84,6 +115,9 @@ impl ConfigurationRepository for EmEditorConfigRepositoryImpl {
             font_weight,
             font_italic,
             shell_path,
+            clipboard_policy,
+            confirm_multiline_paste,
+            alternate_scroll_lines,
         }
     }
 
@@ -110,6 +144,12 @@ impl ConfigurationRepository for EmEditorConfigRepositoryImpl {
         results.push(self.set_dword("FontWeight", config.font_weight));
         results.push(self.set_dword("FontItalic", if config.font_italic { 1 } else { 0 }));
         results.push(self.set_string("ShellPath", &config.shell_path));
+        results.push(self.set_dword("ClipboardPolicy", config.clipboard_policy.to_index()));
+        results.push(self.set_dword(
+            "ConfirmMultilinePaste",
+            if config.confirm_multiline_paste { 1 } else { 0 },
+        ));
+        results.push(self.set_dword("AlternateScrollLines", config.alternate_scroll_lines));
 
         if results.iter().any(|&r| r != 0) {
             let err_msg = format!(